@@ -0,0 +1,464 @@
+use crate::ffmpeg;
+use crate::subtitle::timing::TimingOperation;
+use crate::subtitle::SubtitleFile;
+use serde::{Deserialize, Serialize};
+
+/// Taxa de amostragem usada na análise (mono)
+const SAMPLE_RATE: u32 = 16_000;
+
+/// Duração de cada frame de análise (ms)
+const FRAME_MS: u64 = 10;
+
+/// Amostras por frame de análise
+const FRAME_SAMPLES: usize = (SAMPLE_RATE as u64 * FRAME_MS / 1000) as usize;
+
+/// Deslocamento máximo procurado quando não informado (ms)
+pub const DEFAULT_MAX_OFFSET_MS: u64 = 60_000;
+
+/// Margem acima do ruído de fundo para um frame contar como fala (dB)
+const SPEECH_MARGIN_DB: f32 = 9.0;
+
+/// Energia mínima para um frame contar como fala (dBFS)
+const MIN_SPEECH_DB: f32 = -55.0;
+
+/// Pausas menores que isso não quebram um trecho de fala
+const MIN_SILENCE_MS: u64 = 200;
+
+/// Trechos de fala menores que isso são tratados como ruído
+const MIN_SPEECH_MS: u64 = 100;
+
+/// Quantidade de janelas usadas para estimar o drift
+const DRIFT_WINDOWS: usize = 6;
+
+/// Entradas mínimas para uma janela participar da estimativa de drift
+const MIN_WINDOW_ENTRIES: usize = 5;
+
+/// Raio da busca local de cada janela em volta do offset global (ms)
+const DRIFT_SEARCH_MS: u64 = 2_000;
+
+/// Drift residual máximo aceito depois da conversão de frame rate
+const MAX_RESIDUAL_DRIFT: f64 = 0.01;
+
+/// Conversões de frame rate testadas (tempo no vídeo / tempo na legenda)
+const FRAME_RATE_RATIOS: [f64; 7] = [
+    1.0,
+    23.976 / 25.0,
+    25.0 / 23.976,
+    24.0 / 25.0,
+    25.0 / 24.0,
+    23.976 / 24.0,
+    24.0 / 23.976,
+];
+
+/// Trecho contínuo de fala detectado no áudio
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// Ajuste estimado para alinhar a legenda ao áudio
+/// O tempo corrigido é `t * drift_factor + offset_ms`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncEstimate {
+    pub offset_ms: i64,
+    /// Fator de escala (1.0 = sem drift)
+    pub drift_factor: f64,
+    /// Drift acumulado por hora de vídeo (ms)
+    pub drift_ms_per_hour: f64,
+    /// Fração do tempo de legenda que cai sobre fala depois do ajuste (0.0 a 1.0)
+    pub confidence: f64,
+    pub speech_segments: Vec<SpeechSegment>,
+}
+
+/// Legenda sincronizada e o ajuste aplicado
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoSyncResult {
+    pub file: SubtitleFile,
+    pub estimate: SyncEstimate,
+}
+
+/// Sincroniza a legenda com a faixa de áudio do vídeo
+/// Decodifica o áudio com o ffmpeg, detecta fala por energia e procura o
+/// offset (e o drift, comparando janelas ao longo do arquivo) que melhor
+/// encaixa as entradas nos trechos de fala
+pub fn auto_sync(
+    video_path: &str,
+    file: &SubtitleFile,
+    audio_track: usize,
+    max_offset_ms: u64,
+) -> Result<AutoSyncResult, String> {
+    let mut energies = FrameEnergies::default();
+    ffmpeg::decode_audio(video_path, audio_track, SAMPLE_RATE, |samples| {
+        energies.push_samples(samples)
+    })?;
+
+    let speech = detect_speech(&energies.finish());
+    let estimate = estimate(file, &speech, max_offset_ms)?;
+
+    let mut synced = file.clone();
+    synced.retime(
+        &TimingOperation::Scale {
+            factor: estimate.drift_factor,
+            offset_ms: estimate.offset_ms,
+        },
+        None,
+    )?;
+
+    Ok(AutoSyncResult {
+        file: synced,
+        estimate,
+    })
+}
+
+/// Energia (dBFS) de cada frame de análise, calculada em streaming
+#[derive(Default)]
+struct FrameEnergies {
+    energies: Vec<f32>,
+    sum_squares: f64,
+    count: usize,
+}
+
+impl FrameEnergies {
+    fn push_samples(&mut self, samples: &[i16]) {
+        for &sample in samples {
+            let normalized = sample as f64 / i16::MAX as f64;
+            self.sum_squares += normalized * normalized;
+            self.count += 1;
+            if self.count == FRAME_SAMPLES {
+                self.close_frame();
+            }
+        }
+    }
+
+    fn close_frame(&mut self) {
+        let mean = self.sum_squares / self.count.max(1) as f64;
+        self.energies.push((10.0 * (mean + 1e-10).log10()) as f32);
+        self.sum_squares = 0.0;
+        self.count = 0;
+    }
+
+    fn finish(mut self) -> Vec<f32> {
+        if self.count > 0 {
+            self.close_frame();
+        }
+        self.energies
+    }
+}
+
+/// Marca os frames com fala (detecção de atividade de voz por energia)
+/// O limiar acompanha o ruído de fundo (10º percentil); pausas curtas são
+/// preenchidas e trechos curtos demais descartados
+fn detect_speech(energies: &[f32]) -> Vec<bool> {
+    if energies.is_empty() {
+        return Vec::new();
+    }
+
+    let mut sorted = energies.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let noise_floor = sorted[sorted.len() / 10];
+    let threshold = (noise_floor + SPEECH_MARGIN_DB).max(MIN_SPEECH_DB);
+
+    let mut speech: Vec<bool> = energies.iter().map(|&e| e >= threshold).collect();
+    fill_runs(&mut speech, false, (MIN_SILENCE_MS / FRAME_MS) as usize);
+    fill_runs(&mut speech, true, (MIN_SPEECH_MS / FRAME_MS) as usize);
+    speech
+}
+
+/// Inverte sequências internas de `value` mais curtas que `min_len` frames
+fn fill_runs(flags: &mut [bool], value: bool, min_len: usize) {
+    let mut i = 0;
+    while i < flags.len() {
+        if flags[i] != value {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < flags.len() && flags[i] == value {
+            i += 1;
+        }
+        // Silêncio no início/fim do áudio não é pausa
+        let internal = value || (start > 0 && i < flags.len());
+        if internal && i - start < min_len {
+            flags[start..i].iter_mut().for_each(|f| *f = !value);
+        }
+    }
+}
+
+/// Agrupa frames com fala em trechos
+fn speech_segments(speech: &[bool]) -> Vec<SpeechSegment> {
+    let mut segments = Vec::new();
+    let mut start = None;
+    for (i, &active) in speech.iter().chain(std::iter::once(&false)).enumerate() {
+        match (active, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                segments.push(SpeechSegment {
+                    start_ms: s as u64 * FRAME_MS,
+                    end_ms: i as u64 * FRAME_MS,
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    segments
+}
+
+/// Frames com fala acumulados, para contar fala em qualquer intervalo em O(1)
+struct SpeechIndex {
+    prefix: Vec<u32>,
+}
+
+impl SpeechIndex {
+    fn new(speech: &[bool]) -> Self {
+        let mut prefix = Vec::with_capacity(speech.len() + 1);
+        prefix.push(0);
+        for &active in speech {
+            prefix.push(prefix.last().unwrap() + active as u32);
+        }
+        Self { prefix }
+    }
+
+    /// Frames com fala em [start, end); fora do áudio conta como silêncio
+    fn count(&self, start: i64, end: i64) -> i64 {
+        let last = (self.prefix.len() - 1) as i64;
+        let clamp = |f: i64| f.clamp(0, last) as usize;
+        (self.prefix[clamp(end)] - self.prefix[clamp(start)]) as i64
+    }
+}
+
+/// Entradas de diálogo como intervalos de frames, em ordem cronológica
+fn entry_frames(file: &SubtitleFile) -> Vec<(i64, i64)> {
+    let mut frames: Vec<(i64, i64)> = file
+        .entries
+        .iter()
+        .filter(|e| e.is_translatable(true))
+        .map(|e| {
+            (
+                (e.start_time.as_millis() / FRAME_MS) as i64,
+                (e.end_time.as_millis() / FRAME_MS) as i64,
+            )
+        })
+        .filter(|(start, end)| end > start)
+        .collect();
+    frames.sort();
+    frames
+}
+
+/// Pontuação de um offset: frames de legenda sobre fala menos frames sobre silêncio
+fn score(entries: &[(i64, i64)], index: &SpeechIndex, offset: i64) -> i64 {
+    entries
+        .iter()
+        .map(|&(start, end)| 2 * index.count(start + offset, end + offset) - (end - start))
+        .sum()
+}
+
+/// Offset (em frames) com a melhor pontuação dentro de [center - radius, center + radius]
+/// Empates ficam com o offset mais próximo do centro
+fn best_offset(entries: &[(i64, i64)], index: &SpeechIndex, center: i64, radius: i64) -> i64 {
+    (center - radius..=center + radius)
+        .map(|offset| {
+            (
+                score(entries, index, offset),
+                -(offset - center).abs(),
+                offset,
+            )
+        })
+        .max()
+        .map_or(center, |(_, _, offset)| offset)
+}
+
+/// Estima offset e drift da legenda em relação aos frames com fala
+/// Primeiro testa as conversões de frame rate comuns (cada uma com o melhor
+/// offset global); depois ajusta um drift residual pela regressão dos offsets
+/// locais de janelas ao longo do arquivo
+fn estimate(
+    file: &SubtitleFile,
+    speech: &[bool],
+    max_offset_ms: u64,
+) -> Result<SyncEstimate, String> {
+    let entries = entry_frames(file);
+    if entries.is_empty() {
+        return Err("Subtitle has no timed dialogue to sync".to_string());
+    }
+    let segments = speech_segments(speech);
+    if segments.is_empty() {
+        return Err("No speech detected in the audio track".to_string());
+    }
+
+    let index = SpeechIndex::new(speech);
+    let radius = (max_offset_ms / FRAME_MS) as i64;
+
+    // (pontuação, fator, offset em frames); empates ficam com o primeiro (1.0)
+    let mut best = (i64::MIN, 1.0, 0.0);
+    for factor in FRAME_RATE_RATIOS {
+        let scaled = transform(&entries, factor, 0.0);
+        let offset = best_offset(&scaled, &index, 0, radius);
+        let candidate = score(&scaled, &index, offset);
+        if candidate > best.0 {
+            best = (candidate, factor, offset as f64);
+        }
+    }
+    let (best_score, mut factor, mut offset) = best;
+
+    // Drift residual: offset local de cada janela já alinhada
+    let aligned = transform(&entries, factor, offset);
+    let window_len = aligned.len().div_ceil(DRIFT_WINDOWS).max(1);
+    let points: Vec<(f64, f64)> = aligned
+        .chunks(window_len)
+        .filter(|window| window.len() >= MIN_WINDOW_ENTRIES)
+        .map(|window| {
+            let middle = (window[0].0 + window[window.len() - 1].1) as f64 / 2.0;
+            let local = best_offset(window, &index, 0, (DRIFT_SEARCH_MS / FRAME_MS) as i64);
+            (middle, local as f64)
+        })
+        .collect();
+    if let Some((a, b)) = linear_fit(&points) {
+        let refined = (factor * (1.0 + b), offset * (1.0 + b) + a);
+        let refined_score = score(&transform(&entries, refined.0, refined.1), &index, 0);
+        if points.len() >= 3 && b.abs() <= MAX_RESIDUAL_DRIFT && refined_score > best_score {
+            (factor, offset) = refined;
+        }
+    }
+
+    let aligned = transform(&entries, factor, offset);
+    let total: i64 = aligned.iter().map(|(s, e)| e - s).sum();
+    let overlapping: i64 = aligned.iter().map(|&(s, e)| index.count(s, e)).sum();
+
+    Ok(SyncEstimate {
+        offset_ms: (offset * FRAME_MS as f64).round() as i64,
+        drift_factor: factor,
+        drift_ms_per_hour: (factor - 1.0) * 3_600_000.0,
+        confidence: if total > 0 {
+            overlapping as f64 / total as f64
+        } else {
+            0.0
+        },
+        speech_segments: segments,
+    })
+}
+
+/// Aplica t * factor + offset (em frames) aos intervalos
+fn transform(entries: &[(i64, i64)], factor: f64, offset: f64) -> Vec<(i64, i64)> {
+    let map = |f: i64| (f as f64 * factor + offset).round() as i64;
+    entries
+        .iter()
+        .map(|&(start, end)| (map(start), map(end)))
+        .collect()
+}
+
+/// Mínimos quadrados: retorna (a, b) de y = a + b * x
+fn linear_fit(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let n = points.len() as f64;
+    if points.len() < 2 {
+        return None;
+    }
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let covariance: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        return None;
+    }
+    let b = covariance / variance;
+    Some((mean_y - b * mean_x, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subtitle::{SubtitleEntry, SubtitleFormat, Timestamp};
+
+    /// Início (ms) e duração da fala `i`, com intervalos irregulares
+    fn line(i: usize) -> (u64, u64) {
+        let start = i as u64 * 4_000 + (i as u64 * 7_919) % 1_500;
+        (start, 1_000 + (i as u64 * 3_571) % 1_500)
+    }
+
+    fn subtitle(count: usize, first_ms: u64) -> SubtitleFile {
+        let entries = (0..count)
+            .map(|i| {
+                let (start, duration) = line(i);
+                SubtitleEntry {
+                    index: i + 1,
+                    start_time: Timestamp::from_millis(first_ms + start),
+                    end_time: Timestamp::from_millis(first_ms + start + duration),
+                    text: format!("Line {}", i + 1),
+                    metadata: None,
+                }
+            })
+            .collect();
+        SubtitleFile::new(SubtitleFormat::Srt, entries)
+    }
+
+    /// Frames de fala com o mesmo padrão da legenda, com offset e drift
+    fn speech(count: usize, first_ms: u64, factor: f64) -> Vec<bool> {
+        let frame = |ms: u64| (ms as f64 * factor / FRAME_MS as f64).round() as usize;
+        let mut flags = vec![false; frame(first_ms + line(count).0) + 500];
+        for i in 0..count {
+            let (start, duration) = line(i);
+            let start = first_ms + start;
+            flags[frame(start)..frame(start + duration)]
+                .iter_mut()
+                .for_each(|f| *f = true);
+        }
+        flags
+    }
+
+    #[test]
+    fn test_detect_speech_from_energy() {
+        let mut energies = FrameEnergies::default();
+        let silence = vec![0i16; FRAME_SAMPLES * 50];
+        let tone: Vec<i16> = (0..FRAME_SAMPLES * 50)
+            .map(|i| ((i as f64 * 0.3).sin() * 8_000.0) as i16)
+            .collect();
+        // Fala, pausa curta (50 ms), fala, silêncio longo
+        energies.push_samples(&silence);
+        energies.push_samples(&tone);
+        energies.push_samples(&silence[..FRAME_SAMPLES * 5]);
+        energies.push_samples(&tone);
+        energies.push_samples(&silence);
+
+        let speech = detect_speech(&energies.finish());
+        assert_eq!(
+            speech_segments(&speech),
+            vec![SpeechSegment {
+                start_ms: 500,
+                end_ms: 1550
+            }]
+        );
+    }
+
+    #[test]
+    fn test_estimate_offset() {
+        let file = subtitle(40, 10_000);
+        let estimate = estimate(&file, &speech(40, 12_500, 1.0), 30_000).unwrap();
+        assert_eq!(estimate.offset_ms, 2_500);
+        assert!((estimate.drift_factor - 1.0).abs() < 1e-6);
+        assert!(estimate.confidence > 0.99);
+    }
+
+    #[test]
+    fn test_estimate_frame_rate_drift() {
+        // Legenda em 23.976 fps sobre um vídeo acelerado para 25 fps
+        let factor = 23.976 / 25.0;
+        let file = subtitle(120, 5_000);
+        let estimate = estimate(&file, &speech(120, 5_000, factor), 30_000).unwrap();
+        assert!((estimate.drift_factor - factor).abs() < 0.001);
+        assert!(estimate.offset_ms.abs() < 100);
+        assert!(estimate.confidence > 0.95);
+    }
+
+    #[test]
+    fn test_estimate_without_speech() {
+        let file = subtitle(5, 0);
+        assert!(estimate(&file, &[false; 1000], 1_000).is_err());
+    }
+}
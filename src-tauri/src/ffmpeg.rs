@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::io::Read;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
@@ -8,6 +9,9 @@ use std::os::windows::process::CommandExt;
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
+/// Quanto do stderr do ffmpeg é guardado para a mensagem de erro
+const STDERR_LIMIT: u64 = 64 * 1024;
+
 /// Cria um Command que não abre janela de terminal no Windows
 fn create_command(program: &str) -> Command {
     #[allow(unused_mut)]
@@ -194,6 +198,99 @@ pub fn mux_subtitle_track(
     Ok(())
 }
 
+/// Decodifica uma faixa de áudio como PCM mono 16-bit na taxa informada
+/// As amostras são entregues em blocos para `on_samples` conforme o ffmpeg
+/// decodifica, sem manter o áudio inteiro em memória
+pub fn decode_audio(
+    video_path: &str,
+    audio_track: usize,
+    sample_rate: u32,
+    mut on_samples: impl FnMut(&[i16]),
+) -> Result<(), String> {
+    let mut child = create_command("ffmpeg")
+        .args([
+            "-v",
+            "error",
+            "-i",
+            video_path,
+            "-map",
+            &format!("0:a:{}", audio_track),
+            "-vn",
+            "-ac",
+            "1", // Mono
+            "-ar",
+            &sample_rate.to_string(),
+            "-f",
+            "s16le",
+            "-",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg: {}. Is FFmpeg installed?", e))?;
+
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| "Failed to read ffmpeg output".to_string())?;
+
+    // stderr é esvaziado em paralelo: se o pipe enchesse, o ffmpeg pararia
+    // de escrever no stdout e a leitura abaixo nunca terminaria
+    let stderr_reader = child.stderr.take().map(|mut stderr| {
+        std::thread::spawn(move || {
+            let mut captured = Vec::new();
+            let _ = stderr
+                .by_ref()
+                .take(STDERR_LIMIT)
+                .read_to_end(&mut captured);
+            let _ = std::io::copy(&mut stderr, &mut std::io::sink());
+            captured
+        })
+    });
+
+    let mut buffer = vec![0u8; 64 * 1024];
+    // Byte que sobrou de uma leitura com tamanho ímpar
+    let mut pending: Option<u8> = None;
+    let mut samples = Vec::with_capacity(buffer.len() / 2 + 1);
+    loop {
+        let read = match stdout.read(&mut buffer) {
+            Ok(read) => read,
+            Err(e) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("Failed to read ffmpeg output: {}", e));
+            }
+        };
+        if read == 0 {
+            break;
+        }
+
+        samples.clear();
+        let mut bytes = &buffer[..read];
+        if let Some(low) = pending.take() {
+            samples.push(i16::from_le_bytes([low, bytes[0]]));
+            bytes = &bytes[1..];
+        }
+        let chunks = bytes.chunks_exact(2);
+        pending = chunks.remainder().first().copied();
+        samples.extend(chunks.map(|c| i16::from_le_bytes([c[0], c[1]])));
+        on_samples(&samples);
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    let stderr = stderr_reader
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+    if !status.success() {
+        let stderr = String::from_utf8_lossy(&stderr);
+        return Err(format!("ffmpeg audio decoding failed: {}", stderr));
+    }
+
+    Ok(())
+}
+
 /// Verifica se FFmpeg está instalado
 pub fn check_ffmpeg() -> Result<String, String> {
     let output = create_command("ffmpeg")
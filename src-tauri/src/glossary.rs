@@ -0,0 +1,358 @@
+use crate::translator::{strip_think_blocks, LlmClient};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Linhas de legenda enviadas por requisição na extração do glossário
+const EXTRACTION_CHUNK_LINES: usize = 400;
+
+// Regex para blocos de override do ASS ({\i1}, {\pos(...)})
+static OVERRIDE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{[^}]*\}").unwrap());
+
+/// Termo do glossário: nomes, honoríficos e termos inventados que devem ser
+/// traduzidos sempre do mesmo jeito
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlossaryTerm {
+    pub source: String,
+    pub target: String,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Observações para o modelo (gênero, contexto, quando não traduzir)
+    #[serde(default)]
+    pub notes: String,
+}
+
+/// Entrada cuja tradução não usou a forma definida no glossário
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlossaryViolation {
+    pub index: usize,
+    pub source_term: String,
+    pub expected: String,
+}
+
+impl GlossaryTerm {
+    /// Se o termo original aparece no texto
+    pub fn appears_in(&self, text: &str) -> bool {
+        contains_term(&self.source, text, self.case_sensitive)
+    }
+
+    /// Se a tradução usa a forma definida (termos sem alvo não são cobrados)
+    fn respected_in(&self, translation: &str) -> bool {
+        self.target.trim().is_empty()
+            || contains_term(&self.target, translation, self.case_sensitive)
+    }
+}
+
+/// Procura o termo como palavra inteira; bordas que não são letras ou
+/// números (ex: "-san", termos em CJK) casam em qualquer posição
+fn contains_term(term: &str, text: &str, case_sensitive: bool) -> bool {
+    let term = term.trim();
+    if term.is_empty() {
+        return false;
+    }
+
+    let starts_with_word = term.chars().next().is_some_and(char::is_alphanumeric);
+    let ends_with_word = term.chars().last().is_some_and(char::is_alphanumeric);
+    let pattern = format!(
+        "{}{}{}{}",
+        if case_sensitive { "" } else { "(?i)" },
+        if starts_with_word { r"\b" } else { "" },
+        regex::escape(term),
+        if ends_with_word { r"\b" } else { "" },
+    );
+
+    match Regex::new(&pattern) {
+        Ok(regex) => regex.is_match(text),
+        Err(_) => text.contains(term),
+    }
+}
+
+/// Termos do glossário presentes em pelo menos um dos textos do batch
+pub fn terms_in_batch<'a>(
+    glossary: &'a [GlossaryTerm],
+    entries: &[(usize, String)],
+) -> Vec<&'a GlossaryTerm> {
+    glossary
+        .iter()
+        .filter(|term| entries.iter().any(|(_, text)| term.appears_in(text)))
+        .collect()
+}
+
+/// Bloco de instruções com os termos obrigatórios do batch
+pub fn format_prompt_block(terms: &[&GlossaryTerm]) -> String {
+    if terms.is_empty() {
+        return String::new();
+    }
+
+    let lines = terms
+        .iter()
+        .map(|term| {
+            let mut line = format!("- {} => {}", term.source.trim(), term.target.trim());
+            if !term.notes.trim().is_empty() {
+                line.push_str(&format!(" ({})", term.notes.trim()));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        r#"
+
+---
+GLOSSARY (MANDATORY):
+Whenever a term on the left appears, translate it EXACTLY as shown on the right.
+{}"#,
+        lines
+    )
+}
+
+/// Confere as traduções contra o glossário, uma violação por termo e entrada
+pub fn check_translations(
+    glossary: &[GlossaryTerm],
+    sources: &[(usize, String)],
+    translations: &[(usize, String)],
+) -> Vec<GlossaryViolation> {
+    if glossary.is_empty() {
+        return Vec::new();
+    }
+
+    let sources: HashMap<usize, &str> = sources
+        .iter()
+        .map(|(idx, text)| (*idx, text.as_str()))
+        .collect();
+
+    let mut violations = Vec::new();
+    for (idx, translation) in translations {
+        let Some(source) = sources.get(idx) else {
+            continue;
+        };
+        for term in glossary {
+            if term.appears_in(source) && !term.respected_in(translation) {
+                violations.push(GlossaryViolation {
+                    index: *idx,
+                    source_term: term.source.clone(),
+                    expected: term.target.clone(),
+                });
+            }
+        }
+    }
+    violations
+}
+
+/// Termo proposto pelo modelo na extração
+#[derive(Debug, Deserialize)]
+struct ExtractedTerm {
+    source: String,
+    #[serde(default)]
+    target: String,
+    #[serde(default)]
+    notes: String,
+}
+
+/// Pré-passada com o LLM que sugere um glossário (nomes próprios, termos
+/// recorrentes e falantes) com traduções propostas
+/// `translation_prompt` é o prompt da tradução real, de onde sai o idioma
+/// alvo; termos que não aparecem nas linhas nem entre os falantes são
+/// descartados. O resultado vem ordenado pelo número de linhas em que o
+/// termo aparece e deve ser revisado pelo usuário antes do uso
+pub async fn extract_terms(
+    client: &LlmClient,
+    translation_prompt: &str,
+    lines: &[String],
+    speakers: &[String],
+) -> Result<Vec<GlossaryTerm>, String> {
+    let lines: Vec<String> = lines
+        .iter()
+        .map(|line| plain_text(line))
+        .filter(|line| !line.is_empty())
+        .collect();
+    if lines.is_empty() {
+        return Err("No subtitle text to extract terms from".to_string());
+    }
+
+    let mut terms: Vec<GlossaryTerm> = Vec::new();
+    for chunk in lines.chunks(EXTRACTION_CHUNK_LINES) {
+        let prompt = extraction_prompt(translation_prompt, speakers);
+        let response = client.translate(&prompt, &chunk.join("\n")).await?;
+        for term in parse_extracted_terms(&response)? {
+            let known = terms
+                .iter()
+                .any(|t| t.source.to_lowercase() == term.source.to_lowercase());
+            if !known {
+                terms.push(term);
+            }
+        }
+    }
+
+    let speakers: HashSet<String> = speakers.iter().map(|s| s.to_lowercase()).collect();
+    let mut counted: Vec<(usize, GlossaryTerm)> = terms
+        .into_iter()
+        .map(|term| {
+            let count = lines.iter().filter(|line| term.appears_in(line)).count();
+            (count, term)
+        })
+        .filter(|(count, term)| *count > 0 || speakers.contains(&term.source.to_lowercase()))
+        .collect();
+    counted.sort_by_key(|(count, _)| std::cmp::Reverse(*count));
+
+    Ok(counted.into_iter().map(|(_, term)| term).collect())
+}
+
+fn extraction_prompt(translation_prompt: &str, speakers: &[String]) -> String {
+    let speakers = if speakers.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "\n\nSpeaker names found in the file: {}",
+            speakers.join(", ")
+        )
+    };
+
+    format!(
+        r#"You are preparing a translation glossary for a subtitle file. The translation will follow this instruction:
+[{}]
+
+From the subtitle lines in the user message, extract:
+- proper nouns (characters, places, organizations)
+- honorifics and forms of address
+- invented or recurring terms (techniques, items, titles)
+- speaker names{}
+
+For each term propose the translation to use in the target language of the instruction above (keep names unchanged unless there is an established localized form).
+
+Respond with ONLY a JSON array (no markdown, no extra text) in this exact format:
+[{{"source": "Nerve Gear", "target": "NerveGear", "notes": "VR headset"}}]
+
+Use "notes" for gender, context or register when relevant, otherwise an empty string. Return [] if there are no such terms."#,
+        translation_prompt, speakers
+    )
+}
+
+/// Texto da linha sem tags de override e com quebras do ASS normalizadas
+pub(crate) fn plain_text(line: &str) -> String {
+    OVERRIDE_REGEX
+        .replace_all(line, "")
+        .replace("\\N", " ")
+        .replace("\\n", " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Lê o array JSON da resposta, tolerando blocos <think> e cercas markdown
+fn parse_extracted_terms(response: &str) -> Result<Vec<GlossaryTerm>, String> {
+    let cleaned = strip_think_blocks(response);
+    let json = match (cleaned.find('['), cleaned.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &cleaned[start..=end],
+        _ => {
+            return Err(format!(
+                "Failed to parse glossary extraction response: {}",
+                response.trim()
+            ))
+        }
+    };
+
+    let extracted: Vec<ExtractedTerm> = serde_json::from_str(json).map_err(|e| {
+        format!(
+            "Failed to parse glossary extraction response: {}. Response was: {}",
+            e,
+            response.trim()
+        )
+    })?;
+
+    Ok(extracted
+        .into_iter()
+        .filter(|term| !term.source.trim().is_empty())
+        .map(|term| GlossaryTerm {
+            source: term.source.trim().to_string(),
+            target: term.target.trim().to_string(),
+            case_sensitive: false,
+            notes: term.notes.trim().to_string(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(source: &str, target: &str, case_sensitive: bool) -> GlossaryTerm {
+        GlossaryTerm {
+            source: source.to_string(),
+            target: target.to_string(),
+            case_sensitive,
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_terms_in_batch_match_whole_words() {
+        let glossary = vec![
+            term("Nerve Gear", "NerveGear", false),
+            term("Kirito", "Kirito", true),
+            term("-san", "-san", false),
+            term("Art", "Arte", false),
+        ];
+        let entries = vec![
+            (1, "Put on the nerve gear, kirito.".to_string()),
+            (2, "Asuna-san, wait!".to_string()),
+            (3, "Such artistry".to_string()),
+        ];
+
+        let found: Vec<&str> = terms_in_batch(&glossary, &entries)
+            .iter()
+            .map(|t| t.source.as_str())
+            .collect();
+        assert_eq!(found, vec!["Nerve Gear", "-san"]);
+
+        let block = format_prompt_block(&terms_in_batch(&glossary, &entries));
+        assert!(block.contains("- Nerve Gear => NerveGear"));
+        assert!(format_prompt_block(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_check_translations_reports_per_entry() {
+        let glossary = vec![
+            term("Kirito", "Kirito", true),
+            term("Sword Skill", "Habilidade de Espada", false),
+        ];
+        let sources = vec![
+            (1, "Kirito used a Sword Skill!".to_string()),
+            (2, "Kirito, run!".to_string()),
+        ];
+        let translations = vec![
+            (1, "Kirito usou uma técnica de espada!".to_string()),
+            (2, "Kirito, corra!".to_string()),
+        ];
+
+        let violations = check_translations(&glossary, &sources, &translations);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations,
+            vec![GlossaryViolation {
+                index: 1,
+                source_term: "Sword Skill".to_string(),
+                expected: "Habilidade de Espada".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_extracted_terms() {
+        let response = "<think>names...</think>```json\n[{\"source\": \" Kirito \", \"target\": \"Kirito\"}, {\"source\": \"\", \"target\": \"x\"}, {\"source\": \"Aincrad\", \"target\": \"Aincrad\", \"notes\": \"floating castle\"}]\n```";
+        let terms = parse_extracted_terms(response).unwrap();
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0].source, "Kirito");
+        assert_eq!(terms[1].notes, "floating castle");
+        assert!(parse_extracted_terms("no terms here").is_err());
+
+        assert_eq!(
+            plain_text("{\\i1}Link start!{\\i0}\\NKirito"),
+            "Link start! Kirito"
+        );
+    }
+}
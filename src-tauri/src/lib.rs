@@ -1,6 +1,10 @@
+mod audio_sync;
 mod ffmpeg;
+mod glossary;
+mod retry;
 mod subtitle;
 mod text_cleaner;
+mod translation_memory;
 mod translator;
 
 use std::collections::HashMap;
@@ -11,52 +15,112 @@ use std::sync::{
     Arc, Mutex,
 };
 
+use audio_sync::AutoSyncResult;
 use ffmpeg::SubtitleTrack;
+use glossary::{GlossaryTerm, GlossaryViolation};
 use serde::{Deserialize, Serialize};
+use subtitle::timing::{EntryRange, TimingOperation};
 use subtitle::{SubtitleFile, SubtitleFormat};
 use tauri::{Emitter, Manager};
 
 use translator::{
     ApiFormat, BatchTranslationResult, LlmClient, LlmConfig, LlmModel, TranslationBatchReport,
-    TranslationProgress, TranslationSettings, TRANSLATION_CANCELLED_ERROR,
+    TranslationProgress, TranslationSettings, UntranslatedEntry, TRANSLATION_CANCELLED_ERROR,
 };
 use text_cleaner::{TextCleanerConfig, clean_subtitle_entries, reapply_all_tags};
+use translation_memory::TranslationMemory;
 
 // ============================================================================
 // Comandos de Legendas
 // ============================================================================
 
 /// Carrega e faz parse de um arquivo de legenda
+/// `encoding` força um encoding específico em vez da detecção automática
+/// `strict` falha em vez de descartar entradas mal formadas; fora do modo
+/// estrito as entradas descartadas são listadas em `diagnostics`
+/// `frame_rate` corrige o frame rate de arquivos MicroDVD (.sub por frames)
 #[tauri::command]
-async fn load_subtitle(path: String) -> Result<SubtitleFile, String> {
+async fn load_subtitle(
+    path: String,
+    encoding: Option<String>,
+    strict: Option<bool>,
+    frame_rate: Option<f64>,
+) -> Result<SubtitleFile, String> {
     let content = fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?;
 
-    // Tenta detectar encoding e converter para UTF-8
-    let (content, _, _) = encoding_rs::UTF_8.decode(&content);
-    let content = content.to_string();
+    // Detecta encoding (BOM, UTF-16, estatística) e converte para UTF-8
+    let decoded = subtitle::encoding::decode(&content, encoding.as_deref())?;
 
-    let format =
-        SubtitleFile::detect_format(&path).ok_or_else(|| "Unknown subtitle format".to_string())?;
+    // Extensão ausente ou contraditória: o conteúdo decide
+    let format = subtitle::detect::resolve(&path, &decoded.text)
+        .ok_or_else(|| "Unknown subtitle format".to_string())?;
 
-    SubtitleFile::parse(&content, format)
+    let mut file = if strict.unwrap_or(false) {
+        SubtitleFile::parse_strict(&decoded.text, format)?
+    } else {
+        SubtitleFile::parse(&decoded.text, format)?
+    };
+    file.encoding = Some(decoded.encoding.name().to_string());
+    file.bom = decoded.bom;
+    if let Some(fps) = frame_rate {
+        subtitle::microdvd::set_frame_rate(&mut file, fps)?;
+    }
+    Ok(file)
 }
 
 /// Salva um arquivo de legenda
+/// Mantém encoding, BOM e quebras de linha do original, a menos que `encoding`
+/// seja informado. Retorna o encoding efetivamente usado.
 #[tauri::command]
-async fn save_subtitle(path: String, file: SubtitleFile) -> Result<(), String> {
-    let content = file.serialize();
-    fs::write(&path, content).map_err(|e| format!("Failed to write file: {}", e))
+async fn save_subtitle(
+    path: String,
+    file: SubtitleFile,
+    encoding: Option<String>,
+) -> Result<String, String> {
+    let (content, used) = file.to_bytes(encoding.as_deref())?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write file: {}", e))?;
+    Ok(used.name().to_string())
 }
 
 /// Detecta o formato de um arquivo de legenda
 #[tauri::command]
 fn detect_subtitle_format(filename: String) -> Option<String> {
-    SubtitleFile::detect_format(&filename).map(|f| match f {
-        SubtitleFormat::Srt => "srt".to_string(),
-        SubtitleFormat::Ass => "ass".to_string(),
-        SubtitleFormat::Ssa => "ssa".to_string(),
-        SubtitleFormat::Vtt => "vtt".to_string(),
-    })
+    SubtitleFile::detect_format(&filename).map(|f| f.extension().to_string())
+}
+
+/// Converte uma legenda para outro formato ("srt", "ass", "ssa", "vtt",
+/// "microdvd", "subviewer", "sbv", "lrc", "ttml" ou "smi")
+/// `frame_rate` é usado nos tempos por frame ao gerar MicroDVD
+#[tauri::command]
+fn convert_subtitle(
+    file: SubtitleFile,
+    target_format: String,
+    frame_rate: Option<f64>,
+) -> Result<SubtitleFile, String> {
+    let format = SubtitleFormat::from_name(&target_format)
+        .ok_or_else(|| format!("Unknown subtitle format: {}", target_format))?;
+    let mut converted = file.convert_to(format);
+    if let Some(fps) = frame_rate {
+        if !(fps.is_finite() && fps > 0.0) {
+            return Err(format!("Invalid frame rate: {}", fps));
+        }
+        // Os tempos já estão em milissegundos; só muda a conversão para frames
+        converted.frame_rate = Some(fps);
+    }
+    Ok(converted)
+}
+
+/// Ajusta os tempos de uma legenda (deslocamento, escala linear, conversão de
+/// frame rate ou sincronização por dois pontos)
+/// `range` limita a operação às entradas com índice dentro da faixa
+#[tauri::command]
+fn retime_subtitle(
+    mut file: SubtitleFile,
+    operation: TimingOperation,
+    range: Option<EntryRange>,
+) -> Result<SubtitleFile, String> {
+    file.retime(&operation, range)?;
+    Ok(file)
 }
 
 // ============================================================================
@@ -103,6 +167,24 @@ async fn mux_subtitle_to_video(
     )
 }
 
+/// Sincroniza a legenda com a fala da faixa de áudio do vídeo
+/// Retorna a legenda ajustada e o offset/drift estimado; `max_offset_ms`
+/// limita o deslocamento procurado (padrão: 60 s)
+#[tauri::command]
+async fn auto_sync_subtitle(
+    video_path: String,
+    file: SubtitleFile,
+    audio_track: Option<usize>,
+    max_offset_ms: Option<u64>,
+) -> Result<AutoSyncResult, String> {
+    audio_sync::auto_sync(
+        &video_path,
+        &file,
+        audio_track.unwrap_or(0),
+        max_offset_ms.unwrap_or(audio_sync::DEFAULT_MAX_OFFSET_MS),
+    )
+}
+
 // ============================================================================
 // Comandos de Tradução (LLM)
 // ============================================================================
@@ -114,25 +196,38 @@ async fn list_llm_models(config: LlmConfig) -> Result<Vec<LlmModel>, String> {
     client.list_models().await
 }
 
-/// Traduz um arquivo de legenda
+/// Traduz um arquivo de legenda numa única requisição
+/// Linhas que o modelo não devolver são pedidas de novo; as que seguirem
+/// faltando saem em `untranslated`
+/// `settings` define retries e backoff; sem ele valem os padrões
 #[tauri::command]
 async fn translate_subtitle(
     config: LlmConfig,
     system_prompt: String,
     mut file: SubtitleFile,
-) -> Result<SubtitleFile, String> {
-    let client = LlmClient::new(config);
+    settings: Option<TranslationSettings>,
+) -> Result<SubtitleTranslationResult, String> {
+    let settings = settings.unwrap_or_default();
+    let client = LlmClient::new(config).with_retry_policy(settings.retry_policy());
 
     // Extrai textos para tradução
-    let texts = file.extract_texts();
+    let texts = file.extract_texts_filtered(settings.skip_comments);
 
-    // Traduz
-    let translations = client.translate_subtitles(&system_prompt, &texts).await?;
+    // Traduz tudo como um único lote
+    let result = client
+        .translate_batch(&system_prompt, &texts, 0, texts.len(), &settings)
+        .await?;
 
     // Apply translations back
-    file.apply_translations(translations);
+    file.apply_translations(result.translations);
 
-    Ok(file)
+    Ok(SubtitleTranslationResult {
+        file,
+        progress: result.progress,
+        error_message: None,
+        glossary_violations: Vec::new(),
+        untranslated: result.untranslated,
+    })
 }
 
 /// Traduz texto livre (para testes)
@@ -153,9 +248,16 @@ struct SubtitleTranslationResult {
     file: SubtitleFile,
     progress: TranslationProgress,
     error_message: Option<String>,
+    /// Entradas que não seguiram o glossário
+    #[serde(default)]
+    glossary_violations: Vec<GlossaryViolation>,
+    /// Entradas que ficaram com o texto original (modelo não devolveu)
+    #[serde(default)]
+    untranslated: Vec<UntranslatedEntry>,
 }
 
 /// Traduz um lote específico de legendas (para continue functionality)
+/// `settings` define retries e backoff; sem ele valem os padrões
 #[tauri::command]
 async fn translate_subtitle_batch(
     config: LlmConfig,
@@ -163,15 +265,17 @@ async fn translate_subtitle_batch(
     file: SubtitleFile,
     start_index: usize,
     batch_size: usize,
+    settings: Option<TranslationSettings>,
 ) -> Result<BatchTranslationResult, String> {
-    let client = LlmClient::new(config);
+    let settings = settings.unwrap_or_default();
+    let client = LlmClient::new(config).with_retry_policy(settings.retry_policy());
 
     // Extrai textos para tradução
-    let texts = file.extract_texts();
+    let texts = file.extract_texts_filtered(settings.skip_comments);
 
     // Traduz apenas o batch
     client
-        .translate_batch(&system_prompt, &texts, start_index, batch_size)
+        .translate_batch(&system_prompt, &texts, start_index, batch_size, &settings)
         .await
 }
 
@@ -263,137 +367,166 @@ impl Drop for CancelHandle {
     }
 }
 
+/// Memória de tradução compartilhada entre as traduções em andamento
+struct TranslationMemoryState {
+    path: std::path::PathBuf,
+    memory: Mutex<TranslationMemory>,
+}
+
+impl TranslationMemoryState {
+    fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        let path = get_translation_memory_path(app)?;
+        // Arquivo corrompido não impede o app de abrir: começa do zero
+        let memory = TranslationMemory::load(&path).unwrap_or_default();
+        Ok(Self {
+            path,
+            memory: Mutex::new(memory),
+        })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        self.memory.lock().unwrap().save(&self.path)
+    }
+}
+
+/// Sinopses já geradas, por arquivo, para que retries e `continue_translation`
+/// não precisem resumir o mesmo arquivo de novo
+#[derive(Default)]
+struct SynopsisCache {
+    synopses: Mutex<HashMap<u64, String>>,
+}
+
+impl SynopsisCache {
+    async fn get_or_create(
+        &self,
+        client: &LlmClient,
+        system_prompt: &str,
+        file: &SubtitleFile,
+        entries: &[(usize, String)],
+    ) -> Result<String, String> {
+        let key = synopsis_key(client.model(), system_prompt, file);
+        let cached = self.synopses.lock().unwrap().get(&key).cloned();
+        if let Some(synopsis) = cached {
+            return Ok(synopsis);
+        }
+
+        let synopsis = client
+            .summarize_subtitles(system_prompt, entries, &speaker_names(file))
+            .await
+            .map_err(|e| format!("Synopsis failed: {}", e))?;
+        self.synopses.lock().unwrap().insert(key, synopsis.clone());
+        Ok(synopsis)
+    }
+}
+
+/// Identifica o arquivo original pelo conteúdo, junto com modelo e prompt
+fn synopsis_key(model: &str, system_prompt: &str, file: &SubtitleFile) -> u64 {
+    let mut content = format!("{}\0{}", model, system_prompt);
+    for entry in &file.entries {
+        content.push('\0');
+        content.push_str(&entry.text);
+    }
+    translation_memory::fnv1a(content.as_bytes())
+}
+
+/// Nomes de falantes (campo Name/Actor do ASS) na ordem em que aparecem
+fn speaker_names(file: &SubtitleFile) -> Vec<String> {
+    let mut speakers: Vec<String> = Vec::new();
+    for entry in file.entries.iter().filter(|e| e.is_translatable(true)) {
+        let name = entry.metadata.as_ref().and_then(|m| m.name.as_deref());
+        if let Some(name) = name.map(str::trim).filter(|n| !n.is_empty()) {
+            if !speakers.iter().any(|s| s == name) {
+                speakers.push(name.to_string());
+            }
+        }
+    }
+    speakers
+}
+
 /// Traduz arquivo completo com batching e auto-continue
+/// `glossary` é enviado aos batches que contêm os termos e conferido depois
+/// `speaker_labels` inclui falante e estilo (ASS) em cada linha enviada
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn translate_subtitle_full(
     app: tauri::AppHandle,
     cancel_state: tauri::State<'_, TranslationCancelState>,
+    memory_state: tauri::State<'_, TranslationMemoryState>,
+    synopsis_cache: tauri::State<'_, SynopsisCache>,
     config: LlmConfig,
     system_prompt: String,
     mut file: SubtitleFile,
     settings: TranslationSettings,
     file_id: String,
     text_cleaner_config: Option<TextCleanerConfig>,
+    glossary: Option<Vec<GlossaryTerm>>,
+    speaker_labels: Option<bool>,
 ) -> Result<SubtitleTranslationResult, String> {
     let cancel_handle = cancel_state.register(&file_id);
     if cancel_handle.is_cancelled() {
         return Err(TRANSLATION_CANCELLED_ERROR.to_string());
     }
 
-    let client = LlmClient::new(config);
+    let glossary = glossary.unwrap_or_default();
+    let mut client = LlmClient::new(config)
+        .with_glossary(glossary.clone())
+        .with_retry_policy(settings.retry_policy());
+    if speaker_labels.unwrap_or(false) {
+        client = client.with_speaker_labels(translator::speaker_labels(&file));
+    }
 
     // Configuração do text cleaner
     let cleaner_config = text_cleaner_config.unwrap_or_default();
     let use_cleaner = cleaner_config.enabled;
 
     // Prepara dados para tradução (com ou sem limpeza)
-    let (texts_to_translate, cleaned_data, total) = if use_cleaner {
+    let (texts_to_translate, cleaned_data) = if use_cleaner {
         // Extrai textos com metadados de estilo para limpeza
         let entries_with_style: Vec<(usize, String, Option<String>)> = file.entries.iter()
+            .filter(|e| e.is_translatable(settings.skip_comments))
             .map(|e| (e.index, e.text.clone(), e.metadata.as_ref().and_then(|m| m.style.clone())))
             .collect();
         
         let cleaned = clean_subtitle_entries(&entries_with_style, &cleaner_config);
-        let total = cleaned.mappings.len();
         let texts: Vec<(usize, String)> = cleaned.texts_to_translate.clone();
-        (texts, Some(cleaned), total)
+        (texts, Some(cleaned))
     } else {
-        let texts = file.extract_texts();
-        let total = texts.len();
-        (texts, None, total)
+        (file.extract_texts_filtered(settings.skip_comments), None)
     };
 
-    // If streaming is enabled, use streaming mode
-    if settings.streaming {
-        let file_id_stream = file_id.clone();
-        let app_stream = app.clone();
-
-        let translations = client
-            .translate_subtitles_streaming(
-                &system_prompt,
-                &texts_to_translate,
-                settings.batch_size,
-                settings.parallel_requests,
-                settings.max_retries,
-                Some(cancel_handle.flag()),
-                move |entry| {
-                    // Emit event for each translated entry
-                    let _ = app_stream.emit(
-                        "translation:entry",
-                        StreamingEntryEvent {
-                            file_id: file_id_stream.clone(),
-                            index: entry.index,
-                            text: entry.text,
-                        },
-                    );
-                },
-            )
+    // Sinopse do arquivo inteiro, enviada junto com as instruções de cada batch
+    let client = if settings.synopsis {
+        let synopsis = synopsis_cache
+            .get_or_create(&client, &system_prompt, &file, &texts_to_translate)
             .await?;
-
         if cancel_handle.is_cancelled() {
             return Err(TRANSLATION_CANCELLED_ERROR.to_string());
         }
+        client.with_synopsis(Some(synopsis))
+    } else {
+        client
+    };
 
-        let translated_count = translations.len();
-
-        // Reaplica tags se usou cleaner, senão aplica normal
-        let final_translations = if let Some(ref cleaned) = cleaned_data {
-            let translations_map: std::collections::HashMap<usize, String> = translations.into_iter().collect();
-            reapply_all_tags(cleaned, &translations_map, &cleaner_config)
-        } else {
-            translations
-        };
-
-        // Apply translations back
-        file.apply_translations(final_translations);
-
-        let progress = TranslationProgress {
-            total_entries: total,
-            translated_entries: translated_count,
-            last_translated_index: if translated_count > 0 {
-                translated_count - 1
-            } else {
-                0
-            },
-            is_partial: translated_count < total,
-            can_continue: translated_count < total,
-        };
-
-        // Emit final progress
-        let _ = app.emit(
-            "translation:progress",
-            ProgressEvent {
-                file_id: file_id.clone(),
-                progress: 100.0,
-                translated: translated_count,
-                total,
-            },
-        );
-
-        return Ok(SubtitleTranslationResult {
-            file,
-            progress,
-            error_message: None,
-        });
-    }
-
-    // Default batch mode
+    // Batches com contexto, memória, recuperação de linhas e divisão;
+    // com streaming, cada linha é emitida assim que chega
     let file_id_progress = file_id.clone();
     let file_id_retry = file_id.clone();
     let file_id_error = file_id.clone();
+    let file_id_stream = file_id.clone();
     let app_progress = app.clone();
     let app_retry = app.clone();
+    let app_memory = app.clone();
+    let app_stream = app.clone();
 
     // Translate with batching
-    let TranslationBatchReport {
-        translations,
-        progress,
-        error_message,
-    } = client
+    let report = client
         .translate_all_batched(
             &system_prompt,
             &texts_to_translate,
             &settings,
+            settings
+                .use_translation_memory
+                .then_some(&memory_state.memory),
             Some(cancel_handle.flag()),
             move |prog| {
                 let percent = if prog.total_entries > 0 {
@@ -431,13 +564,48 @@ async fn translate_subtitle_full(
                     },
                 );
             },
+            move |entry| {
+                let _ = app_stream.emit(
+                    "translation:entry",
+                    StreamingEntryEvent {
+                        file_id: file_id_stream.clone(),
+                        index: entry.index,
+                        text: entry.text,
+                    },
+                );
+            },
         )
-        .await?;
+        .await;
+
+    // Grava o que já foi traduzido mesmo com erro ou cancelamento; falha ao
+    // gravar a memória não invalida a tradução, só é avisada no log
+    if settings.use_translation_memory {
+        if let Err(e) = memory_state.save() {
+            let _ = app_memory.emit(
+                "translation:error",
+                ErrorEvent {
+                    file_id: file_id.clone(),
+                    error: format!("Failed to save translation memory: {}", e),
+                    retry_count: 0,
+                },
+            );
+        }
+    }
+
+    let TranslationBatchReport {
+        translations,
+        progress,
+        error_message,
+        untranslated,
+    } = report?;
 
     if cancel_handle.is_cancelled() {
         return Err(TRANSLATION_CANCELLED_ERROR.to_string());
     }
 
+    let glossary_violations =
+        glossary::check_translations(&glossary, &texts_to_translate, &translations);
+
     // Reaplica tags se usou cleaner, senão aplica normal
     let final_translations = if let Some(ref cleaned) = cleaned_data {
         let translations_map: std::collections::HashMap<usize, String> = translations.into_iter().collect();
@@ -453,6 +621,8 @@ async fn translate_subtitle_full(
         file,
         progress,
         error_message,
+        glossary_violations,
+        untranslated,
     })
 }
 
@@ -475,6 +645,21 @@ fn cancel_all_translations(
     Ok(())
 }
 
+/// Apaga a memória de tradução; retorna quantas entradas foram removidas
+#[tauri::command]
+fn clear_translation_memory(
+    memory_state: tauri::State<TranslationMemoryState>,
+) -> Result<usize, String> {
+    let removed = {
+        let mut memory = memory_state.memory.lock().unwrap();
+        let removed = memory.len();
+        memory.clear();
+        removed
+    };
+    memory_state.save()?;
+    Ok(removed)
+}
+
 // ============================================================================
 // Detecção de Idioma
 // ============================================================================
@@ -578,27 +763,101 @@ Examples:
     })
 }
 
+/// Sugere um glossário (nomes, termos recorrentes e falantes) a partir de
+/// um ou mais arquivos, para o usuário revisar antes da tradução
+/// Termos que já estão em `existing` não são repetidos
+#[tauri::command]
+async fn extract_glossary(
+    config: LlmConfig,
+    translation_prompt: String,
+    files: Vec<SubtitleFile>,
+    existing: Option<Vec<GlossaryTerm>>,
+) -> Result<Vec<GlossaryTerm>, String> {
+    let client = LlmClient::new(config);
+
+    let mut lines = Vec::new();
+    let mut speakers: Vec<String> = Vec::new();
+    for file in &files {
+        lines.extend(
+            file.entries
+                .iter()
+                .filter(|e| e.is_translatable(true))
+                .map(|e| e.text.clone()),
+        );
+        for name in speaker_names(file) {
+            if !speakers.contains(&name) {
+                speakers.push(name);
+            }
+        }
+    }
+
+    let existing = existing.unwrap_or_default();
+    let terms = glossary::extract_terms(&client, &translation_prompt, &lines, &speakers).await?;
+    Ok(terms
+        .into_iter()
+        .filter(|term| {
+            !existing
+                .iter()
+                .any(|e| e.source.to_lowercase() == term.source.to_lowercase())
+        })
+        .collect())
+}
+
 /// Continua tradução de um arquivo parcialmente traduzido
+/// Com `use_synopsis`, reaproveita a sinopse já gerada para o arquivo original
+/// `settings` define retries e backoff; sem ele valem os padrões
+/// `glossary` é enviado aos batches e conferido nas novas traduções
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn continue_translation(
+    synopsis_cache: tauri::State<'_, SynopsisCache>,
     config: LlmConfig,
     system_prompt: String,
     original_file: SubtitleFile,
     mut translated_file: SubtitleFile,
     start_from_index: usize,
     batch_size: usize,
+    use_synopsis: Option<bool>,
+    speaker_labels: Option<bool>,
+    settings: Option<TranslationSettings>,
+    glossary: Option<Vec<GlossaryTerm>>,
 ) -> Result<SubtitleTranslationResult, String> {
-    let client = LlmClient::new(config);
+    let settings = settings.unwrap_or_default();
+    let glossary = glossary.unwrap_or_default();
+    let mut client = LlmClient::new(config)
+        .with_glossary(glossary.clone())
+        .with_retry_policy(settings.retry_policy());
+    if speaker_labels.unwrap_or(false) {
+        client = client.with_speaker_labels(translator::speaker_labels(&original_file));
+    }
 
     // Extrai textos do original
-    let texts = original_file.extract_texts();
+    let texts = original_file.extract_texts_filtered(settings.skip_comments);
     let total = texts.len();
 
+    let client = if use_synopsis.unwrap_or(false) {
+        let synopsis = synopsis_cache
+            .get_or_create(&client, &system_prompt, &original_file, &texts)
+            .await?;
+        client.with_synopsis(Some(synopsis))
+    } else {
+        client
+    };
+
     // Traduz apenas a partir do índice especificado
     let result = client
-        .translate_batch(&system_prompt, &texts, start_from_index, batch_size)
+        .translate_batch(
+            &system_prompt,
+            &texts,
+            start_from_index,
+            batch_size,
+            &settings,
+        )
         .await?;
 
+    let glossary_violations =
+        glossary::check_translations(&glossary, &texts, &result.translations);
+
     // Aplica novas traduções ao arquivo já traduzido
     translated_file.apply_translations(result.translations);
 
@@ -620,6 +879,8 @@ async fn continue_translation(
             can_continue: translated_count < total,
         },
         error_message: None,
+        glossary_violations,
+        untranslated: result.untranslated,
     })
 }
 
@@ -704,6 +965,18 @@ struct AppSettings {
     #[serde(default)]
     streaming: bool,
     #[serde(default)]
+    skip_comments: bool,
+    #[serde(default = "default_translation_memory")]
+    translation_memory: bool,
+    #[serde(default = "default_context_lines")]
+    context_lines: usize,
+    #[serde(default)]
+    synopsis: bool,
+    #[serde(default = "default_retry_base_delay_ms")]
+    retry_base_delay_ms: u64,
+    #[serde(default = "default_retry_max_delay_ms")]
+    retry_max_delay_ms: u64,
+    #[serde(default)]
     reasoning_effort: translator::ReasoningEffort,
     #[serde(default)]
     anthropic_thinking_enabled: bool,
@@ -765,6 +1038,12 @@ impl Default for AppSettings {
             max_retries: default_max_retries(),
             concurrency: default_concurrency(),
             streaming: false,
+            skip_comments: false,
+            translation_memory: default_translation_memory(),
+            context_lines: default_context_lines(),
+            synopsis: false,
+            retry_base_delay_ms: default_retry_base_delay_ms(),
+            retry_max_delay_ms: default_retry_max_delay_ms(),
             reasoning_effort: translator::ReasoningEffort::default(),
             anthropic_thinking_enabled: false,
             anthropic_thinking_budget_tokens: default_anthropic_thinking_budget_tokens(),
@@ -811,6 +1090,22 @@ fn default_concurrency() -> usize {
     1
 }
 
+fn default_translation_memory() -> bool {
+    true
+}
+
+fn default_context_lines() -> usize {
+    3
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    1000
+}
+
+fn default_retry_max_delay_ms() -> u64 {
+    60_000
+}
+
 fn default_anthropic_thinking_budget_tokens() -> u32 {
     1024
 }
@@ -919,6 +1214,12 @@ struct PromptTemplate {
     id: String,
     name: String,
     content: String,
+    /// Termos com tradução fixa usados junto com este prompt
+    #[serde(default)]
+    glossary: Vec<GlossaryTerm>,
+    /// Envia falante e estilo (ASS) junto de cada linha
+    #[serde(default)]
+    speaker_labels: bool,
     #[serde(default)]
     created_at: u64,
     #[serde(default)]
@@ -945,6 +1246,19 @@ fn get_templates_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, Stri
     Ok(app_data_dir.join("templates.json"))
 }
 
+/// Get translation memory file path
+fn get_translation_memory_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+
+    Ok(app_data_dir.join("translation_memory.json"))
+}
+
 /// Load templates from file
 #[tauri::command]
 async fn load_templates(app: tauri::AppHandle) -> Result<Vec<PromptTemplate>, String> {
@@ -984,6 +1298,8 @@ async fn add_template(
     app: tauri::AppHandle,
     name: String,
     content: String,
+    glossary: Option<Vec<GlossaryTerm>>,
+    speaker_labels: Option<bool>,
 ) -> Result<PromptTemplate, String> {
     let mut templates = load_templates(app.clone()).await?;
 
@@ -996,6 +1312,8 @@ async fn add_template(
         id: format!("{}-{}", now, rand_id()),
         name,
         content,
+        glossary: glossary.unwrap_or_default(),
+        speaker_labels: speaker_labels.unwrap_or(false),
         created_at: now,
         updated_at: now,
     };
@@ -1021,6 +1339,8 @@ async fn update_template(
     template_id: String,
     name: Option<String>,
     content: Option<String>,
+    glossary: Option<Vec<GlossaryTerm>>,
+    speaker_labels: Option<bool>,
 ) -> Result<PromptTemplate, String> {
     let mut templates = load_templates(app.clone()).await?;
 
@@ -1040,6 +1360,12 @@ async fn update_template(
     if let Some(c) = content {
         template.content = c;
     }
+    if let Some(g) = glossary {
+        template.glossary = g;
+    }
+    if let Some(labels) = speaker_labels {
+        template.speaker_labels = labels;
+    }
     template.updated_at = now;
 
     let updated = template.clone();
@@ -1090,7 +1416,10 @@ fn get_file_info(path: String) -> Result<FileInfo, String> {
         "mkv" | "mp4" | "avi" | "mov" | "webm" | "m4v" | "ts"
     );
 
-    let is_subtitle = matches!(extension.as_str(), "srt" | "ass" | "ssa" | "vtt");
+    let is_subtitle = matches!(
+        extension.as_str(),
+        "srt" | "ass" | "ssa" | "vtt" | "sub" | "sbv" | "lrc" | "ttml" | "dfxp" | "smi" | "sami"
+    );
 
     Ok(FileInfo {
         path: path.to_string_lossy().to_string(),
@@ -1241,6 +1570,12 @@ mod tests {
 pub fn run() {
     tauri::Builder::default()
         .manage(TranslationCancelState::default())
+        .manage(SynopsisCache::default())
+        .setup(|app| {
+            let memory_state = TranslationMemoryState::load(app.handle())?;
+            app.manage(memory_state);
+            Ok(())
+        })
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
@@ -1249,11 +1584,14 @@ pub fn run() {
             load_subtitle,
             save_subtitle,
             detect_subtitle_format,
+            convert_subtitle,
+            retime_subtitle,
             // FFmpeg
             check_ffmpeg_installed,
             list_video_subtitle_tracks,
             extract_subtitle_track,
             mux_subtitle_to_video,
+            auto_sync_subtitle,
             // Tradução
             list_llm_models,
             translate_subtitle,
@@ -1263,7 +1601,9 @@ pub fn run() {
             continue_translation,
             cancel_translation,
             cancel_all_translations,
+            clear_translation_memory,
             detect_language,
+            extract_glossary,
             // Text Cleaner
             analyze_subtitle_clutter,
             preview_cleaned_text,
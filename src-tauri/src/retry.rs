@@ -0,0 +1,321 @@
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Espera máxima pedida pelo servidor que ainda é respeitada; acima disso o
+/// valor é tratado como lixo e vale só o backoff
+const MAX_SERVER_DELAY: Duration = Duration::from_secs(300);
+
+/// Cabeçalhos x-ratelimit-reset-* (OpenAI e compatíveis); o tipo é cruzado
+/// com x-ratelimit-remaining-* para saber qual limite estourou
+const RATE_LIMIT_KINDS: [&str; 2] = ["requests", "tokens"];
+
+/// Falha de uma chamada à API, com o necessário para decidir o retry
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub message: String,
+    /// 429, 5xx, timeout e respostas malformadas valem nova tentativa;
+    /// 400, 401, 403... não mudam tentando de novo
+    pub retryable: bool,
+    /// Status 429: dividir o batch não ajuda, só esperar
+    pub rate_limited: bool,
+    /// Espera indicada pelo servidor (Retry-After ou reset do rate limit)
+    pub retry_after: Option<Duration>,
+}
+
+impl ApiError {
+    /// Falha que pode dar certo na próxima tentativa (resposta fora do formato,
+    /// tags perdidas...)
+    pub fn retryable(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            retryable: true,
+            rate_limited: false,
+            retry_after: None,
+        }
+    }
+
+    /// Erro HTTP, classificado pelo status e pelos cabeçalhos da resposta
+    pub fn from_status(status: StatusCode, headers: &HeaderMap, message: String) -> Self {
+        let rate_limited = status == StatusCode::TOO_MANY_REQUESTS;
+        let retryable = rate_limited
+            || status.is_server_error()
+            || status == StatusCode::REQUEST_TIMEOUT
+            || status == StatusCode::CONFLICT;
+        Self {
+            message,
+            retryable,
+            rate_limited,
+            retry_after: if retryable {
+                server_delay(headers)
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Falha de envio: timeout e conexão são temporários, requisição mal
+    /// montada não
+    pub fn from_request(error: &reqwest::Error, message: String) -> Self {
+        Self {
+            message,
+            retryable: !error.is_builder(),
+            rate_limited: false,
+            retry_after: None,
+        }
+    }
+}
+
+impl From<ApiError> for String {
+    fn from(error: ApiError) -> Self {
+        error.message
+    }
+}
+
+/// Backoff exponencial com jitter entre tentativas
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_millis(1000),
+            max_delay: Duration::from_millis(60_000),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Espera antes da tentativa `attempt` (1 = primeiro retry): metade fixa
+    /// e metade aleatória de base * 2^(attempt-1), limitada a `max_delay`
+    /// A espera pedida pelo servidor, quando maior, prevalece
+    pub fn delay(&self, attempt: usize, error: Option<&ApiError>) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16) as u32;
+        let backoff = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay.max(self.base_delay));
+        let half = backoff / 2;
+        let jitter = half.mul_f64(random_fraction());
+        let delay = half + jitter;
+
+        match error.and_then(|e| e.retry_after) {
+            Some(server) => delay.max(server),
+            None => delay,
+        }
+    }
+
+    /// Dorme o tempo da tentativa `attempt`
+    pub async fn wait(&self, attempt: usize, error: Option<&ApiError>) {
+        tokio::time::sleep(self.delay(attempt, error)).await;
+    }
+}
+
+/// Número em [0, 1) diferente a cada chamada, sem depender de crate de rand
+fn random_fraction() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    let random = RandomState::new().hash_one(nanos);
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Espera pedida pelo servidor: Retry-After (segundos ou data HTTP, usado
+/// também pela Anthropic), retry-after-ms, ou o reset do limite que zerou
+fn server_delay(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    let delay = header("retry-after-ms")
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .and_then(|ms| seconds(ms / 1000.0))
+        .or_else(|| header("retry-after").and_then(parse_retry_after))
+        .or_else(|| {
+            RATE_LIMIT_KINDS
+                .iter()
+                .filter(|kind| {
+                    header(&format!("x-ratelimit-remaining-{}", kind))
+                        .map(|remaining| remaining.trim() == "0")
+                        .unwrap_or(true)
+                })
+                .filter_map(|kind| header(&format!("x-ratelimit-reset-{}", kind)))
+                .filter_map(parse_reset)
+                .max()
+        })?;
+
+    (delay <= MAX_SERVER_DELAY).then_some(delay)
+}
+
+/// Retry-After: segundos ("30") ou data HTTP ("Wed, 21 Oct 2015 07:28:00 GMT")
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(value) = value.parse::<f64>() {
+        return seconds(value);
+    }
+    let at = parse_http_date(value)?;
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+/// Reset do rate limit: duração no formato Go ("1s", "6m0s", "250ms") ou
+/// segundos; números muito grandes são timestamps Unix
+fn parse_reset(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(value) = value.parse::<f64>() {
+        if value > 86_400.0 {
+            let at = UNIX_EPOCH.checked_add(seconds(value)?)?;
+            return Some(at.duration_since(SystemTime::now()).unwrap_or_default());
+        }
+        return seconds(value);
+    }
+
+    let mut total = 0.0;
+    let mut number = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+        let amount: f64 = number.parse().ok()?;
+        number.clear();
+        total += match c {
+            'h' => amount * 3600.0,
+            'm' if chars.peek() == Some(&'s') => {
+                chars.next();
+                amount / 1000.0
+            }
+            'm' => amount * 60.0,
+            's' => amount,
+            _ => return None,
+        };
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    seconds(total)
+}
+
+/// Segundos vindos de header; negativos viram zero e NaN, infinito ou
+/// valores fora do alcance de `Duration` são descartados
+fn seconds(value: f64) -> Option<Duration> {
+    Duration::try_from_secs_f64(if value < 0.0 { 0.0 } else { value }).ok()
+}
+
+/// Data HTTP (IMF-fixdate), o único formato que a RFC 9110 manda gerar
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let [_, day, month, year, time, "GMT"] = parts.as_slice() else {
+        return None;
+    };
+    let day: i64 = day.parse::<u8>().ok()?.into();
+    let month = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ]
+    .iter()
+    .position(|m| m == month)? as i64
+        + 1;
+    let year: i64 = year.parse::<u16>().ok()?.into();
+    let mut clock = time.split(':').map(|p| p.parse::<u8>().ok().map(i64::from));
+    let (hour, minute, second) = (clock.next()??, clock.next()??, clock.next()??);
+
+    // Dias desde 1970-01-01 (algoritmo de Howard Hinnant)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let seconds = days * 86_400 + hour * 3600 + minute * 60 + second;
+    UNIX_EPOCH.checked_add(Duration::from_secs(u64::try_from(seconds).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_classification_and_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("12"));
+        let error = ApiError::from_status(StatusCode::TOO_MANY_REQUESTS, &headers, String::new());
+        assert!(error.retryable && error.rate_limited);
+        assert_eq!(error.retry_after, Some(Duration::from_secs(12)));
+
+        let error = ApiError::from_status(StatusCode::UNAUTHORIZED, &headers, String::new());
+        assert!(!error.retryable);
+        assert_eq!(error.retry_after, None);
+
+        // Só vale o reset do limite que zerou
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-ratelimit-remaining-requests",
+            HeaderValue::from_static("10"),
+        );
+        headers.insert("x-ratelimit-reset-requests", HeaderValue::from_static("2m"));
+        headers.insert(
+            "x-ratelimit-remaining-tokens",
+            HeaderValue::from_static("0"),
+        );
+        headers.insert(
+            "x-ratelimit-reset-tokens",
+            HeaderValue::from_static("1m30.5s"),
+        );
+        assert_eq!(server_delay(&headers), Some(Duration::from_secs_f64(90.5)));
+
+        assert_eq!(parse_reset("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_reset("abc"), None);
+        assert_eq!(
+            parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(UNIX_EPOCH + Duration::from_secs(1_445_412_480))
+        );
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(1000),
+            max_delay: Duration::from_millis(5000),
+        };
+        for _ in 0..20 {
+            let first = policy.delay(1, None);
+            assert!(first >= Duration::from_millis(500) && first <= Duration::from_millis(1000));
+            let capped = policy.delay(10, None);
+            assert!(capped >= Duration::from_millis(2500) && capped <= Duration::from_millis(5000));
+        }
+
+        let error = ApiError {
+            retry_after: Some(Duration::from_secs(30)),
+            ..ApiError::retryable("429")
+        };
+        assert_eq!(policy.delay(1, Some(&error)), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_non_finite_headers() {
+        for (name, value) in [
+            ("retry-after", "1e400"),
+            ("retry-after", "NaN"),
+            ("retry-after-ms", "inf"),
+            ("retry-after-ms", "1e400"),
+        ] {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, HeaderValue::from_static(value));
+            assert_eq!(server_delay(&headers), None, "{}: {}", name, value);
+        }
+
+        assert_eq!(parse_reset("1e300"), None);
+        assert_eq!(parse_reset("1e400s"), None);
+        assert_eq!(
+            parse_http_date("Wed, 21 Oct 99999999999 07:28:00 GMT"),
+            None
+        );
+    }
+}
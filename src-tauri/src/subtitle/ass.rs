@@ -1,73 +1,98 @@
-use super::{AssHeaders, SubtitleEntry, SubtitleFile, SubtitleFormat, SubtitleMetadata};
+use super::{
+    AssEventKind, AssHeaders, AssSection, LineEnding, ParseDiagnostic, SubtitleEntry, SubtitleFile,
+    SubtitleFormat, SubtitleMetadata, Timestamp,
+};
+use std::collections::BTreeMap;
+
+/// Campos padrão de [Events] quando o arquivo não declara "Format:"
+const DEFAULT_EVENTS_FORMAT: [&str; 10] = [
+    "Layer", "Start", "End", "Style", "Name", "MarginL", "MarginR", "MarginV", "Effect", "Text",
+];
 
 /// Faz parse de um arquivo ASS/SSA
-/// Preserva completamente: [Script Info], [V4+ Styles], [Fonts], [Graphics]
-/// Extrai apenas os diálogos de [Events] para tradução
+/// Preserva todas as seções (inclusive desconhecidas, como [Aegisub Project Garbage])
+/// linha a linha e o "Format:" original de [Events]
+/// Extrai apenas os eventos de [Events] para tradução
 pub fn parse(content: &str) -> Result<SubtitleFile, String> {
+    let line_ending = LineEnding::detect(content);
     let content = content.replace("\r\n", "\n").replace('\r', "\n");
 
     let mut headers = AssHeaders::default();
     let mut entries = Vec::new();
-    let mut current_section = String::new();
-    let mut dialogue_format: Vec<String> = Vec::new();
+    let mut current = AssSection::default();
+    let mut dialogue_format: Vec<String> = DEFAULT_EVENTS_FORMAT
+        .iter()
+        .map(|f| f.to_lowercase())
+        .collect();
+    let mut seen_margin = false;
+    let mut diagnostics = Vec::new();
 
-    for line in content.lines() {
-        let line = line.trim();
+    for (line_index, raw_line) in content.split('\n').enumerate() {
+        let line = raw_line.trim();
 
         // Detecta seção
         if line.starts_with('[') && line.ends_with(']') {
-            current_section = line[1..line.len() - 1].to_lowercase();
-            // Salva o header da seção também
-            match current_section.as_str() {
-                "script info" => headers.script_info.push(line.to_string()),
-                "v4+ styles" | "v4 styles" | "v4 styles+" => headers.styles.push(line.to_string()),
-                "fonts" => headers.fonts.push(line.to_string()),
-                "graphics" => headers.graphics.push(line.to_string()),
-                _ => {}
+            if !current.lines.is_empty() {
+                headers.sections.push(std::mem::take(&mut current));
             }
+            current.lines.push(raw_line.to_string());
             continue;
         }
 
-        // Processa linha baseado na seção atual
-        match current_section.as_str() {
-            "script info" => {
-                if !line.is_empty() {
-                    headers.script_info.push(line.to_string());
-                }
-            }
-            "v4+ styles" | "v4 styles" | "v4 styles+" => {
-                if !line.is_empty() {
-                    headers.styles.push(line.to_string());
-                }
-            }
-            "fonts" => {
-                if !line.is_empty() {
-                    headers.fonts.push(line.to_string());
-                }
-            }
-            "graphics" => {
-                if !line.is_empty() {
-                    headers.graphics.push(line.to_string());
+        if !current.is_events() {
+            current.lines.push(raw_line.to_string());
+            continue;
+        }
+
+        // Processa linhas de [Events]
+        if let Some(format_part) = line.strip_prefix("Format:") {
+            headers.events_format = format_part
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect();
+            dialogue_format = headers
+                .events_format
+                .iter()
+                .map(|s| s.to_lowercase())
+                .collect();
+            current.lines.push(raw_line.to_string());
+        } else if let Some((kind, rest)) = AssEventKind::split_line(line) {
+            match parse_dialogue_line(kind, rest, &dialogue_format, entries.len()) {
+                Ok(entry) => {
+                    // Linhas soltas desde o evento anterior ficam antes deste
+                    let between = std::mem::take(&mut current.trailing);
+                    current
+                        .interleaved
+                        .extend(between.into_iter().map(|l| (entries.len(), l)));
+                    if !seen_margin {
+                        if let Some(padded) = margins_padded(rest, &dialogue_format) {
+                            headers.pad_margins = padded;
+                            seen_margin = true;
+                        }
+                    }
+                    entries.push(entry);
                 }
-            }
-            "events" => {
-                // Captura o formato dos diálogos
-                if let Some(format_part) = line.strip_prefix("Format:") {
-                    dialogue_format = format_part
-                        .split(',')
-                        .map(|s| s.trim().to_lowercase())
-                        .collect();
-                } else if line.starts_with("Dialogue:") || line.starts_with("Comment:") {
-                    if let Some(entry) = parse_dialogue_line(line, &dialogue_format, entries.len())
-                    {
-                        entries.push(entry);
+                Err(reason) => {
+                    diagnostics.push(ParseDiagnostic::new(line_index + 1, reason, raw_line));
+                    // Mantida como texto bruto para não sumir no round-trip
+                    if entries.is_empty() {
+                        current.lines.push(raw_line.to_string());
+                    } else {
+                        current.trailing.push(raw_line.to_string());
                     }
                 }
             }
-            _ => {}
+        } else if entries.is_empty() {
+            current.lines.push(raw_line.to_string());
+        } else {
+            current.trailing.push(raw_line.to_string());
         }
     }
 
+    if !current.lines.is_empty() {
+        headers.sections.push(current);
+    }
+
     if entries.is_empty() {
         return Err("No dialogue entries found in ASS file".to_string());
     }
@@ -76,29 +101,52 @@ pub fn parse(content: &str) -> Result<SubtitleFile, String> {
         format: SubtitleFormat::Ass,
         entries,
         headers: Some(headers),
+        vtt_headers: None,
+        encoding: None,
+        bom: false,
+        line_ending,
+        diagnostics,
+        preamble: Vec::new(),
+        frame_rate: None,
+        markup_headers: None,
     })
 }
 
-/// Faz parse de uma linha de Dialogue
-fn parse_dialogue_line(line: &str, format: &[String], index: usize) -> Option<SubtitleEntry> {
-    // Remove "Dialogue: " ou "Comment: "
-    let is_comment = line.starts_with("Comment:");
-    let content = if is_comment {
-        &line["Comment:".len()..]
-    } else {
-        &line["Dialogue:".len()..]
-    };
+/// Verifica se as margens de uma linha de evento usam zeros à esquerda
+fn margins_padded(content: &str, format: &[String]) -> Option<bool> {
+    let parts: Vec<&str> = content.splitn(format.len(), ',').collect();
+    format
+        .iter()
+        .zip(parts)
+        .find(|(field, _)| field.starts_with("margin"))
+        .map(|(_, value)| {
+            let value = value.trim();
+            value.len() > 1 && value.starts_with('0')
+        })
+}
 
+/// Faz parse de uma linha de evento (Dialogue, Comment, etc.)
+/// `content` é a linha sem o prefixo "Tipo:"
+fn parse_dialogue_line(
+    kind: AssEventKind,
+    content: &str,
+    format: &[String],
+    index: usize,
+) -> Result<SubtitleEntry, String> {
     // ASS usa vírgulas como separador, mas o texto pode conter vírgulas
     // O texto é sempre o último campo, então dividimos em N-1 partes
     let parts: Vec<&str> = content.splitn(format.len(), ',').collect();
 
     if parts.len() < format.len() {
-        return None;
+        return Err(format!(
+            "Expected {} fields, found {}",
+            format.len(),
+            parts.len()
+        ));
     }
 
-    let mut start_time = String::new();
-    let mut end_time = String::new();
+    let mut start_time = None;
+    let mut end_time = None;
     let mut style = None;
     let mut name = None;
     let mut margin_l = None;
@@ -107,22 +155,27 @@ fn parse_dialogue_line(line: &str, format: &[String], index: usize) -> Option<Su
     let mut effect = None;
     let mut layer = None;
     let mut text = String::new();
+    let mut extra_fields = BTreeMap::new();
 
     for (i, field_name) in format.iter().enumerate() {
-        let value = parts.get(i).map(|s| s.trim()).unwrap_or("");
+        let raw_value = parts.get(i).copied().unwrap_or("");
+        let value = raw_value.trim();
 
         match field_name.as_str() {
             "layer" => layer = value.parse().ok(),
-            "start" => start_time = value.to_string(),
-            "end" => end_time = value.to_string(),
+            "start" => start_time = Some(Timestamp::parse(value)),
+            "end" => end_time = Some(Timestamp::parse(value)),
             "style" => style = Some(value.to_string()),
             "name" | "actor" => name = Some(value.to_string()),
             "marginl" => margin_l = value.parse().ok(),
             "marginr" => margin_r = value.parse().ok(),
             "marginv" => margin_v = value.parse().ok(),
             "effect" => effect = Some(value.to_string()),
-            "text" => text = value.to_string(),
-            _ => {}
+            // O texto é mantido sem trim para não alterar espaços intencionais
+            "text" => text = raw_value.to_string(),
+            other => {
+                extra_fields.insert(other.to_string(), value.to_string());
+            }
         }
     }
 
@@ -131,7 +184,11 @@ fn parse_dialogue_line(line: &str, format: &[String], index: usize) -> Option<Su
         text = parts.last().unwrap().trim().to_string();
     }
 
-    Some(SubtitleEntry {
+    let missing = |field: &str| Err(format!("Missing {} field", field));
+    let start_time = start_time.unwrap_or_else(|| missing("Start"))?;
+    let end_time = end_time.unwrap_or_else(|| missing("End"))?;
+
+    Ok(SubtitleEntry {
         index: index + 1, // 1-indexed para consistência com SRT
         start_time,
         end_time,
@@ -144,98 +201,105 @@ fn parse_dialogue_line(line: &str, format: &[String], index: usize) -> Option<Su
             margin_v,
             effect,
             layer,
+            event_kind: Some(kind),
+            extra_fields,
+            ..Default::default()
         }),
     })
 }
 
 /// Serializa para formato ASS
+/// As seções preservadas são reescritas como estavam e os eventos seguem
+/// a ordem de campos declarada no "Format:" original
 pub fn serialize(file: &SubtitleFile) -> String {
-    let mut output = String::new();
-
-    // Escreve headers preservados
-    if let Some(headers) = &file.headers {
-        // Script Info
-        if !headers.script_info.is_empty() {
-            for line in &headers.script_info {
-                output.push_str(line);
-                output.push('\n');
-            }
-            output.push('\n');
-        }
+    let default_headers = AssHeaders::default();
+    let headers = file.headers.as_ref().unwrap_or(&default_headers);
 
-        // Styles
-        if !headers.styles.is_empty() {
-            for line in &headers.styles {
-                output.push_str(line);
-                output.push('\n');
-            }
-            output.push('\n');
-        }
+    let format: Vec<String> = if headers.events_format.is_empty() {
+        DEFAULT_EVENTS_FORMAT
+            .iter()
+            .map(|f| f.to_string())
+            .collect()
+    } else {
+        headers.events_format.clone()
+    };
 
-        // Fonts
-        if !headers.fonts.is_empty() {
-            for line in &headers.fonts {
-                output.push_str(line);
-                output.push('\n');
-            }
-            output.push('\n');
-        }
+    let mut lines: Vec<String> = Vec::new();
+    let mut wrote_events = false;
 
-        // Graphics
-        if !headers.graphics.is_empty() {
-            for line in &headers.graphics {
-                output.push_str(line);
-                output.push('\n');
+    for section in &headers.sections {
+        lines.extend(section.lines.iter().cloned());
+        if section.is_events() && !wrote_events {
+            let mut interleaved = section.interleaved.iter().peekable();
+            for (i, entry) in file.entries.iter().enumerate() {
+                while let Some((_, line)) = interleaved.next_if(|(before, _)| *before <= i) {
+                    lines.push(line.clone());
+                }
+                lines.push(serialize_event(entry, &format, headers.pad_margins));
             }
-            output.push('\n');
+            lines.extend(interleaved.map(|(_, line)| line.clone()));
+            lines.extend(section.trailing.iter().cloned());
+            wrote_events = true;
         }
     }
 
-    // Events section
-    output.push_str("[Events]\n");
-    output.push_str(
-        "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
-    );
-
-    for entry in &file.entries {
-        let meta = entry.metadata.as_ref();
-
-        let layer = meta.and_then(|m| m.layer).unwrap_or(0);
-        let style = meta
-            .and_then(|m| m.style.as_ref())
-            .map(|s| s.as_str())
-            .unwrap_or("Default");
-        let name = meta
-            .and_then(|m| m.name.as_ref())
-            .map(|s| s.as_str())
-            .unwrap_or("");
-        let margin_l = meta.and_then(|m| m.margin_l).unwrap_or(0);
-        let margin_r = meta.and_then(|m| m.margin_r).unwrap_or(0);
-        let margin_v = meta.and_then(|m| m.margin_v).unwrap_or(0);
-        let effect = meta
-            .and_then(|m| m.effect.as_ref())
-            .map(|s| s.as_str())
-            .unwrap_or("");
-
-        // Converte \n (newline real) para \N (formato ASS)
-        let text_for_ass = entry.text.replace('\n', "\\N");
-
-        output.push_str(&format!(
-            "Dialogue: {},{},{},{},{},{:04},{:04},{:04},{},{}\n",
-            layer,
-            entry.start_time,
-            entry.end_time,
-            style,
-            name,
-            margin_l,
-            margin_r,
-            margin_v,
-            effect,
-            text_for_ass
-        ));
+    // Sem seção [Events] preservada: gera uma com o formato padrão
+    if !wrote_events {
+        if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.push("[Events]".to_string());
+        lines.push(format!("Format: {}", format.join(", ")));
+        lines.extend(
+            file.entries
+                .iter()
+                .map(|e| serialize_event(e, &format, headers.pad_margins)),
+        );
+        lines.push(String::new());
     }
 
-    output
+    lines.join("\n")
+}
+
+/// Serializa uma entrada como linha de evento seguindo a ordem de `format`
+fn serialize_event(entry: &SubtitleEntry, format: &[String], pad_margins: bool) -> String {
+    let meta = entry.metadata.as_ref();
+
+    let kind = meta.and_then(|m| m.event_kind).unwrap_or_default();
+    let margin = |value: Option<i32>| {
+        let value = value.unwrap_or(0);
+        if pad_margins {
+            format!("{:04}", value)
+        } else {
+            value.to_string()
+        }
+    };
+    let text_field = |value: Option<&String>| value.cloned().unwrap_or_default();
+
+    let fields: Vec<String> = format
+        .iter()
+        .map(|field| match field.to_lowercase().as_str() {
+            "layer" => meta.and_then(|m| m.layer).unwrap_or(0).to_string(),
+            "start" => entry.start_time.to_string_for(&SubtitleFormat::Ass),
+            "end" => entry.end_time.to_string_for(&SubtitleFormat::Ass),
+            "style" => meta
+                .and_then(|m| m.style.clone())
+                .unwrap_or_else(|| "Default".to_string()),
+            "name" | "actor" => text_field(meta.and_then(|m| m.name.as_ref())),
+            "marginl" => margin(meta.and_then(|m| m.margin_l)),
+            "marginr" => margin(meta.and_then(|m| m.margin_r)),
+            "marginv" => margin(meta.and_then(|m| m.margin_v)),
+            "effect" => text_field(meta.and_then(|m| m.effect.as_ref())),
+            "marked" => meta
+                .and_then(|m| m.extra_fields.get("marked").cloned())
+                .unwrap_or_else(|| "Marked=0".to_string()),
+            // Converte \n (newline real) para \N (formato ASS)
+            "text" => entry.text.replace('\n', "\\N"),
+            other => text_field(meta.and_then(|m| m.extra_fields.get(other))),
+        })
+        .collect();
+
+    format!("{}: {}", kind.as_str(), fields.join(","))
 }
 
 #[cfg(test)]
@@ -267,10 +331,17 @@ Dialogue: 0,0:00:05.00,0:00:08.00,Default,,0000,0000,0000,,{\i1}Italic text{\i0}
         // Verifica que os headers foram preservados
         let headers = result.headers.as_ref().unwrap();
         assert!(headers
-            .script_info
+            .section("Script Info")
+            .unwrap()
+            .lines
             .iter()
             .any(|l| l.contains("Title: Test")));
-        assert!(headers.styles.iter().any(|l| l.contains("Style: Default")));
+        assert!(headers
+            .section("V4+ Styles")
+            .unwrap()
+            .lines
+            .iter()
+            .any(|l| l.contains("Style: Default")));
     }
 
     #[test]
@@ -279,8 +350,8 @@ Dialogue: 0,0:00:05.00,0:00:08.00,Default,,0000,0000,0000,,{\i1}Italic text{\i0}
             format: SubtitleFormat::Ass,
             entries: vec![SubtitleEntry {
                 index: 1,
-                start_time: "0:00:01.00".to_string(),
-                end_time: "0:00:04.00".to_string(),
+                start_time: Timestamp::parse("0:00:01.00").unwrap(),
+                end_time: Timestamp::parse("0:00:04.00").unwrap(),
                 text: "Hello".to_string(),
                 metadata: Some(SubtitleMetadata {
                     style: Some("Default".to_string()),
@@ -289,20 +360,180 @@ Dialogue: 0,0:00:05.00,0:00:08.00,Default,,0000,0000,0000,,{\i1}Italic text{\i0}
                 }),
             }],
             headers: Some(AssHeaders {
-                script_info: vec!["[Script Info]".to_string(), "Title: Test".to_string()],
-                styles: vec![
-                    "[V4+ Styles]".to_string(),
-                    "Format: Name, Fontname".to_string(),
-                    "Style: Default,Arial".to_string(),
+                sections: vec![
+                    AssSection {
+                        lines: vec!["[Script Info]".to_string(), "Title: Test".to_string()],
+                        ..Default::default()
+                    },
+                    AssSection {
+                        lines: vec![
+                            "[V4+ Styles]".to_string(),
+                            "Format: Name, Fontname".to_string(),
+                            "Style: Default,Arial".to_string(),
+                        ],
+                        ..Default::default()
+                    },
                 ],
                 ..Default::default()
             }),
+            vtt_headers: None,
+            encoding: None,
+            bom: false,
+            line_ending: LineEnding::Lf,
+            diagnostics: Vec::new(),
+            preamble: Vec::new(),
+            frame_rate: None,
+            markup_headers: None,
         };
 
         let output = serialize(&file);
         assert!(output.contains("[Script Info]"));
         assert!(output.contains("Title: Test"));
         assert!(output.contains("[Events]"));
-        assert!(output.contains("Dialogue:"));
+        assert!(output.contains("Dialogue: 0,0:00:01.00,0:00:04.00,Default,,0000,0000,0000,,Hello"));
+    }
+
+    /// Corpus de arquivos que devem sobreviver a `parse -> serialize` sem alterações
+    const ROUND_TRIP_CORPUS: &[(&str, &str)] = &[
+        (
+            "aegisub.ass",
+            include_str!("../../tests/fixtures/ass/aegisub.ass"),
+        ),
+        (
+            "legacy_padded.ass",
+            include_str!("../../tests/fixtures/ass/legacy_padded.ass"),
+        ),
+        (
+            "custom_format.ass",
+            include_str!("../../tests/fixtures/ass/custom_format.ass"),
+        ),
+        (
+            "ssa_v4.ssa",
+            include_str!("../../tests/fixtures/ass/ssa_v4.ssa"),
+        ),
+        (
+            "interleaved.ass",
+            include_str!("../../tests/fixtures/ass/interleaved.ass"),
+        ),
+    ];
+
+    #[test]
+    fn test_round_trip_corpus() {
+        for (name, content) in ROUND_TRIP_CORPUS {
+            let parsed = parse(content).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert_eq!(
+                &serialize(&parsed),
+                content,
+                "round-trip mismatch in {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_sections_and_format_order() {
+        let parsed = parse(ROUND_TRIP_CORPUS[0].1).unwrap();
+        let headers = parsed.headers.as_ref().unwrap();
+        assert!(headers.section("Aegisub Project Garbage").is_some());
+        assert!(headers.section("Aegisub Extradata").is_some());
+
+        let parsed = parse(ROUND_TRIP_CORPUS[3].1).unwrap();
+        let headers = parsed.headers.as_ref().unwrap();
+        assert_eq!(headers.events_format[0], "Marked");
+        let meta = parsed.entries[0].metadata.as_ref().unwrap();
+        assert_eq!(
+            meta.extra_fields.get("marked").map(String::as_str),
+            Some("Marked=0")
+        );
+    }
+
+    #[test]
+    fn test_interleaved_lines_keep_position() {
+        let mut parsed = parse(ROUND_TRIP_CORPUS[4].1).unwrap();
+        assert_eq!(parsed.entries.len(), 2);
+        assert_eq!(parsed.diagnostics.len(), 1);
+
+        parsed.apply_translations(vec![
+            (1, "Primeiro".to_string()),
+            (2, "Segundo".to_string()),
+        ]);
+        let output = serialize(&parsed);
+        assert!(output.contains(
+            ",,Primeiro\n; Scene 2\n\nDialogue: 0,0:00:03.00,Default,Broken\nDialogue: 0,0:00:04.00"
+        ));
+        assert!(output.ends_with(",,Segundo\n; end of events\n"));
+    }
+
+    #[test]
+    fn test_translated_text_keeps_original_layout() {
+        let mut parsed = parse(ROUND_TRIP_CORPUS[2].1).unwrap();
+        parsed.apply_translations(vec![(1, "Olá\nmundo".to_string())]);
+        let output = serialize(&parsed);
+        assert!(output.contains("Dialogue: 0,0:00:01.00,0:00:04.00,Main,Kirito,Olá\\Nmundo"));
+    }
+
+    #[test]
+    fn test_parse_reports_malformed_events() {
+        let content = r#"[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:01.00,0:00:04.00,Default,,0,0,0,,Hello
+Dialogue: 0,0:00:05.00,Default,Short
+Dialogue: 0,0:00:05.00,0:0x:08.00,Default,,0,0,0,,Bad end
+"#;
+
+        let result = parse(content).unwrap();
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(
+            result.diagnostics,
+            vec![
+                ParseDiagnostic::new(
+                    4,
+                    "Expected 10 fields, found 4",
+                    "Dialogue: 0,0:00:05.00,Default,Short"
+                ),
+                ParseDiagnostic::new(
+                    5,
+                    "Invalid timestamp: 0:0x:08.00",
+                    "Dialogue: 0,0:00:05.00,0:0x:08.00,Default,,0,0,0,,Bad end"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comment_lines_preserved() {
+        let content = r#"[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Comment: 0,0:00:01.00,0:00:04.00,Default,,0000,0000,0000,,old typeset
+Dialogue: 0,0:00:01.00,0:00:04.00,Default,,0000,0000,0000,,Hello
+Sound: 0,0:00:01.00,0:00:04.00,Default,,0000,0000,0000,,ding.wav
+"#;
+
+        let result = parse(content).unwrap();
+        assert_eq!(result.entries.len(), 3);
+        let kinds: Vec<_> = result
+            .entries
+            .iter()
+            .map(|e| e.metadata.as_ref().unwrap().event_kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                Some(AssEventKind::Comment),
+                Some(AssEventKind::Dialogue),
+                Some(AssEventKind::Sound)
+            ]
+        );
+
+        let output = serialize(&result);
+        assert!(output
+            .contains("Comment: 0,0:00:01.00,0:00:04.00,Default,,0000,0000,0000,,old typeset"));
+        assert!(output.contains("Sound: 0,0:00:01.00"));
+
+        assert_eq!(result.extract_texts().len(), 2);
+        assert_eq!(
+            result.extract_texts_filtered(true),
+            vec![(2, "Hello".to_string())]
+        );
     }
 }
@@ -0,0 +1,462 @@
+use super::{
+    AssEventKind, AssHeaders, AssSection, SubtitleEntry, SubtitleFile, SubtitleFormat,
+    SubtitleMetadata,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// [Script Info] gerado ao promover SRT/VTT para ASS
+const DEFAULT_SCRIPT_INFO: [&str; 8] = [
+    "[Script Info]",
+    "; Converted by Translator",
+    "ScriptType: v4.00+",
+    "WrapStyle: 0",
+    "ScaledBorderAndShadow: yes",
+    "PlayResX: 1920",
+    "PlayResY: 1080",
+    "",
+];
+
+/// [V4+ Styles] gerado ao promover SRT/VTT para ASS
+const DEFAULT_STYLES: [&str; 4] = [
+    "[V4+ Styles]",
+    "Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding",
+    "Style: Default,Arial,64,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,0,0,0,0,100,100,0,0,1,3,1,2,60,60,50,1",
+    "",
+];
+
+// Regex para tags HTML-like de SRT/VTT (<i>, </b>, <v Nome>, <c.classe>, <00:00:01.000>)
+static HTML_TAG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"<([^<>]*)>").unwrap());
+
+// Regex para o atributo color de <font color="...">
+static FONT_COLOR_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r##"(?i)color\s*=\s*["']?#?([0-9a-f]{6})"##).unwrap());
+
+/// Converte um arquivo de legenda para outro formato
+/// Timestamps são reformatados pelo serializador de destino; a marcação é
+/// traduzida entre override tags ASS e tags HTML-like de SRT/VTT
+pub fn convert(file: &SubtitleFile, target: SubtitleFormat) -> SubtitleFile {
+    if file.format == target {
+        return file.clone();
+    }
+
+    let source_is_ass = matches!(file.format, SubtitleFormat::Ass | SubtitleFormat::Ssa);
+    let target_is_ass = matches!(target, SubtitleFormat::Ass | SubtitleFormat::Ssa);
+
+    // ASS <-> SSA compartilham o mesmo modelo; o serializador SSA cuida dos headers
+    if source_is_ass && target_is_ass {
+        let mut converted = file.clone();
+        converted.format = target;
+        return converted;
+    }
+
+    let mut entries: Vec<SubtitleEntry> = if source_is_ass {
+        // Comentários e eventos que não são diálogo não têm equivalente em SRT/VTT
+        file.entries
+            .iter()
+            .filter(|e| e.is_translatable(true))
+            .map(|e| ass_entry_to_html(e, &target))
+            .collect()
+    } else if target_is_ass {
+        file.entries
+            .iter()
+            .map(|e| html_entry_to_ass(e, &file.format))
+            .collect()
+    } else {
+        file.entries
+            .iter()
+            .map(|e| html_entry_to_html(e, &file.format, &target))
+            .collect()
+    };
+
+    // SRT e VTT exigem cues em ordem cronológica
+    if !target_is_ass {
+        entries.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+    }
+
+    for (i, entry) in entries.iter_mut().enumerate() {
+        entry.index = i + 1;
+    }
+
+    SubtitleFile {
+        format: target,
+        entries,
+        headers: if target_is_ass {
+            Some(default_ass_headers())
+        } else {
+            None
+        },
+        vtt_headers: None,
+        encoding: file.encoding.clone(),
+        bom: file.bom,
+        line_ending: file.line_ending,
+        diagnostics: Vec::new(),
+        preamble: Vec::new(),
+        frame_rate: file.frame_rate,
+        markup_headers: None,
+    }
+}
+
+/// Headers padrão para um ASS gerado a partir de SRT/VTT
+pub fn default_ass_headers() -> AssHeaders {
+    AssHeaders {
+        sections: vec![
+            AssSection {
+                lines: DEFAULT_SCRIPT_INFO.iter().map(|l| l.to_string()).collect(),
+                ..Default::default()
+            },
+            AssSection {
+                lines: DEFAULT_STYLES.iter().map(|l| l.to_string()).collect(),
+                ..Default::default()
+            },
+        ],
+        events_format: Vec::new(),
+        pad_margins: false,
+    }
+}
+
+fn ass_entry_to_html(entry: &SubtitleEntry, target: &SubtitleFormat) -> SubtitleEntry {
+    let is_vtt = *target == SubtitleFormat::Vtt;
+
+    // VTT exige &, < e > do texto como entidades; as override tags ASS não
+    // usam esses caracteres de forma que importe para a conversão
+    let mut text = if is_vtt {
+        ass_to_html(&escape_entities(&entry.text))
+    } else {
+        ass_to_html(&entry.text)
+    };
+
+    // O ator do ASS vira uma voice tag no VTT
+    if is_vtt {
+        if let Some(name) = entry
+            .metadata
+            .as_ref()
+            .and_then(|m| m.name.as_deref())
+            .filter(|n| !n.trim().is_empty())
+        {
+            text = format!("<v {}>{}", escape_entities(name.trim()), text);
+        }
+    }
+
+    SubtitleEntry {
+        index: entry.index,
+        start_time: entry.start_time.clone(),
+        end_time: entry.end_time.clone(),
+        text,
+        metadata: None,
+    }
+}
+
+fn html_entry_to_ass(entry: &SubtitleEntry, source: &SubtitleFormat) -> SubtitleEntry {
+    let mut speaker = None;
+    let text = rewrite_html_tags(&entry.text, false, |tag| match tag_name(tag).as_str() {
+        "i" | "b" | "u" | "s" => Some(format!("{{\\{}1}}", tag_name(tag))),
+        "/i" | "/b" | "/u" | "/s" => Some(format!("{{\\{}0}}", &tag_name(tag)[1..])),
+        "v" => {
+            speaker = Some(tag[1..].trim().to_string());
+            None
+        }
+        "font" => FONT_COLOR_REGEX.captures(tag).map(|caps| {
+            let rgb = caps[1].to_uppercase();
+            format!("{{\\c&H{}{}{}&}}", &rgb[4..6], &rgb[2..4], &rgb[0..2])
+        }),
+        "/font" => Some("{\\c}".to_string()),
+        _ => None,
+    });
+
+    let text = if *source == SubtitleFormat::Vtt {
+        unescape_entities(&text)
+    } else {
+        text
+    };
+
+    SubtitleEntry {
+        index: entry.index,
+        start_time: entry.start_time.clone(),
+        end_time: entry.end_time.clone(),
+        text,
+        metadata: Some(SubtitleMetadata {
+            style: Some("Default".to_string()),
+            name: speaker,
+            layer: Some(0),
+            event_kind: Some(AssEventKind::Dialogue),
+            ..Default::default()
+        }),
+    }
+}
+
+fn html_entry_to_html(
+    entry: &SubtitleEntry,
+    source: &SubtitleFormat,
+    target: &SubtitleFormat,
+) -> SubtitleEntry {
+    let to_vtt = *target == SubtitleFormat::Vtt;
+
+    // Override tags ASS soltas em SRT ({\an8}) não são válidas em VTT
+    let text = if to_vtt {
+        strip_override_blocks(&entry.text)
+    } else {
+        entry.text.clone()
+    };
+
+    // Mantém apenas a marcação comum aos dois formatos; no VTT o texto fora
+    // das tags leva &, < e > como entidades
+    let text = rewrite_html_tags(&text, to_vtt, |tag| match tag_name(tag).as_str() {
+        "i" | "b" | "u" | "/i" | "/b" | "/u" => Some(format!("<{}>", tag_name(tag))),
+        "v" | "/v" if to_vtt => Some(format!("<{}>", tag)),
+        _ => None,
+    });
+
+    let text = if *source == SubtitleFormat::Vtt {
+        unescape_entities(&text)
+    } else {
+        text
+    };
+
+    SubtitleEntry {
+        index: entry.index,
+        start_time: entry.start_time.clone(),
+        end_time: entry.end_time.clone(),
+        text,
+        metadata: None,
+    }
+}
+
+/// Converte texto ASS para marcação HTML-like de SRT/VTT
+/// \N e \n viram quebras de linha, \h vira espaço, {\i1}/{\b1}/{\u1}/{\s1}
+/// viram tags HTML e as demais override tags são descartadas
+pub fn ass_to_html(text: &str) -> String {
+    let mut output = String::new();
+    let mut open: Vec<char> = Vec::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('{') {
+            if let Some(end) = after.find('}') {
+                let block = &after[..end];
+                // Blocos sem "\" são comentários do typesetter
+                for tag in block.split('\\').skip(1) {
+                    apply_ass_tag(tag.trim(), &mut open, &mut output);
+                }
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+
+        if let Some(after) = rest
+            .strip_prefix("\\N")
+            .or_else(|| rest.strip_prefix("\\n"))
+        {
+            output.push('\n');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("\\h") {
+            output.push(' ');
+            rest = after;
+        } else {
+            let ch = rest.chars().next().unwrap_or_default();
+            output.push(ch);
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+
+    while let Some(tag) = open.pop() {
+        output.push_str(&format!("</{}>", tag));
+    }
+
+    output
+}
+
+/// Aplica uma override tag ASS (sem a barra) ao estado de tags HTML abertas
+fn apply_ass_tag(tag: &str, open: &mut Vec<char>, output: &mut String) {
+    if tag.starts_with('r') {
+        while let Some(open_tag) = open.pop() {
+            output.push_str(&format!("</{}>", open_tag));
+        }
+        return;
+    }
+
+    let mut chars = tag.chars();
+    let Some(name @ ('i' | 'b' | 'u' | 's')) = chars.next() else {
+        return;
+    };
+    let value = chars.as_str();
+    // \b aceita pesos (\b700); \s seguido de letra é outra tag (\shad)
+    if !value.chars().all(|c| c.is_ascii_digit()) {
+        return;
+    }
+
+    let enable = !value.is_empty() && value != "0";
+    let is_open = open.contains(&name);
+
+    if enable && !is_open {
+        open.push(name);
+        output.push_str(&format!("<{}>", name));
+    } else if !enable && is_open {
+        open.retain(|t| *t != name);
+        output.push_str(&format!("</{}>", name));
+    }
+}
+
+/// Reescreve tags HTML-like; `map` recebe o conteúdo entre < e > e devolve
+/// o texto substituto (None remove a tag)
+/// Com `escape_text`, o texto entre as tags tem &, < e > escapados
+fn rewrite_html_tags(
+    text: &str,
+    escape_text: bool,
+    mut map: impl FnMut(&str) -> Option<String>,
+) -> String {
+    let plain = |segment: &str| {
+        if escape_text {
+            escape_entities(segment)
+        } else {
+            segment.to_string()
+        }
+    };
+
+    let mut output = String::new();
+    let mut last = 0;
+    for caps in HTML_TAG_REGEX.captures_iter(text) {
+        let whole = caps.get(0).unwrap();
+        output.push_str(&plain(&text[last..whole.start()]));
+        if let Some(replacement) = map(caps[1].trim()) {
+            output.push_str(&replacement);
+        }
+        last = whole.end();
+    }
+    output.push_str(&plain(&text[last..]));
+    output
+}
+
+/// Nome da tag em minúsculas, sem classes (<c.yellow> -> "c") e sem atributos
+fn tag_name(tag: &str) -> String {
+    tag.split(|c: char| c.is_whitespace() || c == '.')
+        .next()
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+/// Remove blocos {...} de override ASS
+fn strip_override_blocks(text: &str) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{\\") {
+        output.push_str(&rest[..start]);
+        match rest[start..].find('}') {
+            Some(end) => rest = &rest[start + end + 1..],
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Escapa os caracteres que o VTT exige como entidades
+fn escape_entities(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Decodifica as entidades HTML permitidas em VTT
+fn unescape_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", "\u{a0}")
+        .replace("&lrm;", "\u{200e}")
+        .replace("&rlm;", "\u{200f}")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subtitle::{ass, srt, vtt};
+
+    #[test]
+    fn test_ass_to_html_markup() {
+        assert_eq!(
+            ass_to_html(r"{\i1}Italic{\i0} and {\b1\pos(1,2)}bold{\b0}\Nnext\hline"),
+            "<i>Italic</i> and <b>bold</b>\nnext line"
+        );
+        assert_eq!(ass_to_html(r"{\i1}unclosed"), "<i>unclosed</i>");
+        assert_eq!(ass_to_html(r"{TL note}{\shad2}Text"), "Text");
+    }
+
+    #[test]
+    fn test_ass_to_srt() {
+        let content = r#"[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:05.00,0:00:06.00,Default,,0,0,0,,Second
+Comment: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,hidden
+Dialogue: 0,0:00:01.00,0:00:04.50,Default,,0,0,0,,{\i1}Hello{\i0}\Nworld
+"#;
+        let file = ass::parse(content).unwrap();
+        let converted = convert(&file, SubtitleFormat::Srt);
+
+        assert_eq!(
+            srt::serialize(&converted),
+            "1\n00:00:01,000 --> 00:00:04,500\n<i>Hello</i>\nworld\n\n2\n00:00:05,000 --> 00:00:06,000\nSecond\n"
+        );
+    }
+
+    #[test]
+    fn test_srt_to_ass_generates_default_styles() {
+        let content = "1\n00:00:01,000 --> 00:00:04,000\n<i>Hello</i>\n<font color=\"#FF8000\">world</font>\n";
+        let file = srt::parse(content).unwrap();
+        let converted = convert(&file, SubtitleFormat::Ass);
+        let output = ass::serialize(&converted);
+
+        assert!(output.starts_with("[Script Info]\n"));
+        assert!(output.contains("[V4+ Styles]\n"));
+        assert!(output.contains("Style: Default,Arial,64"));
+        assert!(output.contains(
+            "Dialogue: 0,0:00:01.00,0:00:04.00,Default,,0,0,0,,{\\i1}Hello{\\i0}\\N{\\c&H0080FF&}world{\\c}\n"
+        ));
+    }
+
+    #[test]
+    fn test_vtt_speaker_round_trip_through_ass() {
+        let content = "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\n<v Kirito>Tom &amp; Jerry\n";
+        let file = vtt::parse(content).unwrap();
+
+        let as_ass = convert(&file, SubtitleFormat::Ass);
+        let meta = as_ass.entries[0].metadata.as_ref().unwrap();
+        assert_eq!(meta.name.as_deref(), Some("Kirito"));
+        assert_eq!(as_ass.entries[0].text, "Tom & Jerry");
+
+        let back = convert(&as_ass, SubtitleFormat::Vtt);
+        assert_eq!(back.entries[0].text, "<v Kirito>Tom &amp; Jerry");
+    }
+
+    #[test]
+    fn test_vtt_entities_escaped_and_read_back() {
+        let content = "[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nDialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,{\\i1}5 > 3{\\i0} & x<y\n";
+        let file = ass::parse(content).unwrap();
+        let as_vtt = convert(&file, SubtitleFormat::Vtt);
+        assert_eq!(as_vtt.entries[0].text, "<i>5 &gt; 3</i> &amp; x&lt;y");
+
+        let back = convert(&as_vtt, SubtitleFormat::Ass);
+        assert_eq!(back.entries[0].text, "{\\i1}5 > 3{\\i0} & x<y");
+        let as_srt = convert(&as_vtt, SubtitleFormat::Srt);
+        assert_eq!(as_srt.entries[0].text, "<i>5 > 3</i> & x<y");
+
+        let content = "1\n00:00:01,000 --> 00:00:02,000\n<i>Tom & Jerry</i> <3\n";
+        let as_vtt = convert(&srt::parse(content).unwrap(), SubtitleFormat::Vtt);
+        assert_eq!(as_vtt.entries[0].text, "<i>Tom &amp; Jerry</i> &lt;3");
+    }
+
+    #[test]
+    fn test_srt_to_vtt_strips_unsupported_markup() {
+        let content =
+            "1\n00:00:01,000 --> 00:00:02,000\n{\\an8}<font color=\"red\"><b>Top</b></font>\n";
+        let file = srt::parse(content).unwrap();
+        let converted = convert(&file, SubtitleFormat::Vtt);
+        assert_eq!(
+            vtt::serialize(&converted),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\n<b>Top</b>\n"
+        );
+    }
+}
@@ -0,0 +1,347 @@
+use super::SubtitleFormat;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+/// Quantidade de linhas analisadas na detecção por conteúdo
+const SNIFF_LINES: usize = 200;
+
+/// Confiança mínima para o conteúdo prevalecer sobre a extensão
+pub const OVERRIDE_CONFIDENCE: f32 = 0.8;
+
+// Linha de tempo no estilo SRT/VTT: "00:00:01,000 --> 00:00:02,000"
+static ARROW_LINE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*(?:\d+:)?\d{1,2}:\d{1,2}([.,])\d{1,3}\s*-->\s*(?:\d+:)?\d{1,2}:\d{1,2}[.,]\d{1,3}",
+    )
+    .unwrap()
+});
+
+// Linha MicroDVD: "{100}{200}texto"
+static MICRODVD_LINE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\{\d+\}\{\d*\}").unwrap());
+
+// Linha de tempo "início,fim" (SubViewer usa centésimos, SBV milissegundos)
+static COMMA_TIMING_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*\d+:\d{2}:\d{2}\.(\d{2,3})\s*,\s*\d+:\d{2}:\d{2}\.\d{2,3}\s*$").unwrap()
+});
+
+// Linha LRC: começa com [mm:ss.xx]
+static LRC_LINE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\[\d+:\d{1,2}([.:]\d{1,3})?\]").unwrap());
+
+/// Formato detectado pelo conteúdo, com confiança entre 0.0 e 1.0
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FormatGuess {
+    pub format: SubtitleFormat,
+    pub confidence: f32,
+}
+
+/// Detecta o formato analisando o conteúdo
+/// Retorna None quando nenhum padrão conhecido é encontrado
+pub fn sniff(content: &str) -> Option<FormatGuess> {
+    let content = content.trim_start_matches('\u{feff}');
+    let lines: Vec<&str> = content
+        .lines()
+        .take(SNIFF_LINES)
+        .map(|l| l.trim_end_matches('\r'))
+        .collect();
+    let first = lines.iter().map(|l| l.trim()).find(|l| !l.is_empty())?;
+
+    // WebVTT exige a assinatura na primeira linha
+    if first == "WEBVTT" || first.starts_with("WEBVTT ") || first.starts_with("WEBVTT\t") {
+        return Some(guess(SubtitleFormat::Vtt, 1.0));
+    }
+
+    if let Some(found) = sniff_markup(content, first) {
+        return Some(found);
+    }
+
+    if let Some(found) = sniff_ass(&lines, first) {
+        return Some(found);
+    }
+
+    sniff_microdvd(&lines, first)
+        .or_else(|| sniff_comma_timed(&lines, first))
+        .or_else(|| sniff_lrc(&lines, first))
+        .or_else(|| sniff_srt(&lines))
+}
+
+fn guess(format: SubtitleFormat, confidence: f32) -> FormatGuess {
+    FormatGuess { format, confidence }
+}
+
+fn sniff_ass(lines: &[&str], first: &str) -> Option<FormatGuess> {
+    let has_line = |predicate: &dyn Fn(&str) -> bool| lines.iter().any(|l| predicate(l.trim()));
+
+    let script_info = has_line(&|l| l.eq_ignore_ascii_case("[Script Info]"));
+    let events = has_line(&|l| l.eq_ignore_ascii_case("[Events]"));
+    let dialogue = has_line(&|l| l.starts_with("Dialogue:"));
+
+    let confidence = if first.eq_ignore_ascii_case("[Script Info]") {
+        1.0
+    } else if script_info {
+        0.9
+    } else if events && dialogue {
+        0.7
+    } else {
+        return None;
+    };
+
+    // SSA v4: [V4 Styles] sem [V4+ Styles] ou ScriptType v4.00
+    let v4_styles = has_line(&|l| l.eq_ignore_ascii_case("[V4 Styles]"));
+    let v4_plus_styles = has_line(&|l| l.eq_ignore_ascii_case("[V4+ Styles]"));
+    let ssa_type = has_line(&|l| {
+        l.strip_prefix("ScriptType:")
+            .is_some_and(|t| t.trim().eq_ignore_ascii_case("v4.00"))
+    });
+
+    let format = if !v4_plus_styles && (v4_styles || ssa_type) {
+        SubtitleFormat::Ssa
+    } else {
+        SubtitleFormat::Ass
+    };
+
+    Some(guess(format, confidence))
+}
+
+/// TTML (<tt> com namespace TTML) e SAMI (<SAMI>)
+fn sniff_markup(content: &str, first: &str) -> Option<FormatGuess> {
+    let head: String = content
+        .chars()
+        .take(4096)
+        .collect::<String>()
+        .to_lowercase();
+    let first = first.to_lowercase();
+
+    if head.contains("<sami") {
+        let confidence = if first.starts_with("<sami") { 1.0 } else { 0.9 };
+        return Some(guess(SubtitleFormat::Sami, confidence));
+    }
+
+    let has_root = head.contains("<tt ") || head.contains("<tt>") || head.contains("<tt:tt");
+    if has_root && head.contains("ns/ttml") {
+        let confidence = if first.starts_with("<?xml") || first.starts_with("<tt") {
+            1.0
+        } else {
+            0.9
+        };
+        return Some(guess(SubtitleFormat::Ttml, confidence));
+    }
+
+    None
+}
+
+/// Fração de linhas não vazias que casam com o padrão
+fn matching_ratio(lines: &[&str], regex: &Regex) -> f32 {
+    let non_empty: Vec<&str> = lines
+        .iter()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect();
+    if non_empty.is_empty() {
+        return 0.0;
+    }
+    let matching = non_empty.iter().filter(|l| regex.is_match(l)).count();
+    matching as f32 / non_empty.len() as f32
+}
+
+/// MicroDVD: praticamente todas as linhas são "{início}{fim}texto"
+fn sniff_microdvd(lines: &[&str], first: &str) -> Option<FormatGuess> {
+    if !MICRODVD_LINE_REGEX.is_match(first) {
+        return None;
+    }
+    let ratio = matching_ratio(lines, &MICRODVD_LINE_REGEX);
+    (ratio >= 0.5).then(|| {
+        guess(
+            SubtitleFormat::MicroDvd,
+            if ratio >= 0.9 { 1.0 } else { 0.8 },
+        )
+    })
+}
+
+/// SubViewer 2.0 ([INFORMATION] ou tempos com centésimos) e SBV (milissegundos)
+fn sniff_comma_timed(lines: &[&str], first: &str) -> Option<FormatGuess> {
+    if first.eq_ignore_ascii_case("[INFORMATION]") {
+        return Some(guess(SubtitleFormat::SubViewer, 1.0));
+    }
+
+    let fractions: Vec<usize> = lines
+        .iter()
+        .filter_map(|l| COMMA_TIMING_REGEX.captures(l))
+        .map(|caps| caps[1].len())
+        .collect();
+    if fractions.is_empty() {
+        return None;
+    }
+
+    let format = if fractions.iter().all(|&len| len == 2) {
+        SubtitleFormat::SubViewer
+    } else {
+        SubtitleFormat::Sbv
+    };
+    let confidence = if COMMA_TIMING_REGEX.is_match(first) {
+        1.0
+    } else {
+        0.8
+    };
+    Some(guess(format, confidence))
+}
+
+/// LRC: linhas começando com [mm:ss.xx], possivelmente após tags [ar:...]
+/// Exige que o arquivo comece assim ou que ao menos metade das linhas siga
+/// o padrão; uma fala "[00:12] ..." perdida num SRT não o torna LRC
+fn sniff_lrc(lines: &[&str], first: &str) -> Option<FormatGuess> {
+    let ratio = matching_ratio(lines, &LRC_LINE_REGEX);
+    if ratio == 0.0 {
+        return None;
+    }
+    let starts_like_lrc =
+        LRC_LINE_REGEX.is_match(first) || (first.starts_with('[') && first.ends_with(']'));
+    let confidence = match (starts_like_lrc, ratio >= 0.5) {
+        (true, true) => 1.0,
+        (true, false) | (false, true) => 0.8,
+        (false, false) => return None,
+    };
+    Some(guess(SubtitleFormat::Lrc, confidence))
+}
+
+/// SRT: índice numérico seguido de uma linha de tempo com vírgula
+fn sniff_srt(lines: &[&str]) -> Option<FormatGuess> {
+    let mut cues = 0;
+    let mut indexed = 0;
+    let mut with_comma = 0;
+
+    for (i, line) in lines.iter().enumerate() {
+        let Some(caps) = ARROW_LINE_REGEX.captures(line) else {
+            continue;
+        };
+        cues += 1;
+        if &caps[1] == "," {
+            with_comma += 1;
+        }
+        let previous = i.checked_sub(1).map(|p| lines[p].trim());
+        if previous.is_some_and(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit())) {
+            indexed += 1;
+        }
+    }
+
+    if cues == 0 {
+        return None;
+    }
+
+    // Índices e vírgulas em todos os cues: SRT típico
+    // Sem índices ou com ponto decimal ainda é provavelmente SRT mal formatado
+    let confidence = if indexed == cues && with_comma == cues {
+        1.0
+    } else if indexed * 2 >= cues {
+        0.8
+    } else {
+        0.5
+    };
+
+    Some(guess(SubtitleFormat::Srt, confidence))
+}
+
+/// Decide o formato combinando extensão e conteúdo
+/// O conteúdo prevalece quando a extensão está ausente ou é contraditória
+/// e a detecção tem confiança suficiente; ASS e SSA são tratados como a
+/// mesma família (o parser SSA decide pelo conteúdo)
+pub fn resolve(filename: &str, content: &str) -> Option<SubtitleFormat> {
+    let by_extension = super::SubtitleFile::detect_format(filename);
+    let by_content = sniff(content);
+
+    match (by_extension, by_content) {
+        (None, found) => found.map(|g| g.format),
+        (Some(ext), Some(found))
+            if found.confidence >= OVERRIDE_CONFIDENCE && !same_family(&ext, &found.format) =>
+        {
+            Some(found.format)
+        }
+        (ext, _) => ext,
+    }
+}
+
+fn same_family(a: &SubtitleFormat, b: &SubtitleFormat) -> bool {
+    let is_ass = |f: &SubtitleFormat| matches!(f, SubtitleFormat::Ass | SubtitleFormat::Ssa);
+    a == b || (is_ass(a) && is_ass(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRT: &str = "1\r\n00:00:01,000 --> 00:00:02,000\r\nHello\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nWorld\r\n";
+    const ASS: &str = "[Script Info]\nScriptType: v4.00+\n\n[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nDialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Hi\n";
+
+    #[test]
+    fn test_sniff_formats() {
+        assert_eq!(sniff(SRT), Some(guess(SubtitleFormat::Srt, 1.0)));
+        assert_eq!(sniff(ASS), Some(guess(SubtitleFormat::Ass, 1.0)));
+        assert_eq!(
+            sniff("\u{feff}WEBVTT\n\n00:01.000 --> 00:02.000\nHi\n"),
+            Some(guess(SubtitleFormat::Vtt, 1.0))
+        );
+        assert_eq!(
+            sniff("[Script Info]\nScriptType: v4.00\n\n[V4 Styles]\n").map(|g| g.format),
+            Some(SubtitleFormat::Ssa)
+        );
+        assert_eq!(
+            sniff("00:00:01.000 --> 00:00:02.000\nHi\n"),
+            Some(guess(SubtitleFormat::Srt, 0.5))
+        );
+        assert_eq!(sniff("just some text\n"), None);
+        assert_eq!(
+            sniff("{1}{1}25\n{25}{75}Hello|World\n"),
+            Some(guess(SubtitleFormat::MicroDvd, 1.0))
+        );
+        assert_eq!(
+            sniff("[INFORMATION]\n[END INFORMATION]\n00:00:01.00,00:00:02.00\nHi\n"),
+            Some(guess(SubtitleFormat::SubViewer, 1.0))
+        );
+        assert_eq!(
+            sniff("0:00:01.000,0:00:02.000\nHi\n"),
+            Some(guess(SubtitleFormat::Sbv, 1.0))
+        );
+        assert_eq!(
+            sniff("<?xml version=\"1.0\"?>\n<tt xmlns=\"http://www.w3.org/ns/ttml\">\n"),
+            Some(guess(SubtitleFormat::Ttml, 1.0))
+        );
+        assert_eq!(
+            sniff("<SAMI>\n<HEAD>\n</HEAD>\n<BODY>\n<SYNC Start=0>\n"),
+            Some(guess(SubtitleFormat::Sami, 1.0))
+        );
+        assert_eq!(
+            sniff("[ti:Song]\n[00:12.00]First\n[00:15.00]Second\n"),
+            Some(guess(SubtitleFormat::Lrc, 1.0))
+        );
+        assert_eq!(
+            sniff("1\n00:00:01,000 --> 00:00:02,000\n[00:12] on the tape\n\n2\n00:00:03,000 --> 00:00:04,000\nRewind it\n"),
+            Some(guess(SubtitleFormat::Srt, 1.0))
+        );
+    }
+
+    #[test]
+    fn test_resolve_prefers_confident_content() {
+        assert_eq!(resolve("episode.txt", SRT), Some(SubtitleFormat::Srt));
+        assert_eq!(resolve("episode.sub", SRT), Some(SubtitleFormat::Srt));
+        assert_eq!(resolve("episode.srt", ASS), Some(SubtitleFormat::Ass));
+        assert_eq!(resolve("episode.ssa", ASS), Some(SubtitleFormat::Ssa));
+        assert_eq!(resolve("episode.srt", "garbage"), Some(SubtitleFormat::Srt));
+        assert_eq!(resolve("episode.txt", "garbage"), None);
+        assert_eq!(
+            resolve(
+                "episode",
+                "1\n00:00:01,000 --> 00:00:02,000\n[01:30] Hello\n"
+            ),
+            Some(SubtitleFormat::Srt)
+        );
+        assert_eq!(
+            resolve("episode.sub", "{1}{25}Hi\n"),
+            Some(SubtitleFormat::MicroDvd)
+        );
+        assert_eq!(
+            resolve("episode.sub", "00:00:01.00,00:00:02.00\nHi\n"),
+            Some(SubtitleFormat::SubViewer)
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use super::SubtitleFile;
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+/// Quantidade de bytes analisada na heurística de UTF-16 sem BOM
+const UTF16_SNIFF_LEN: usize = 4096;
+
+/// Texto decodificado de um arquivo de legenda
+#[derive(Debug, Clone)]
+pub struct DecodedText {
+    pub text: String,
+    pub encoding: &'static Encoding,
+    /// Se o arquivo começava com BOM
+    pub bom: bool,
+}
+
+/// Decodifica os bytes de um arquivo de legenda para UTF-8
+/// Com `encoding_override` usa o encoding informado (label WHATWG, ex.: "shift_jis",
+/// "windows-1252"); caso contrário detecta por BOM, heurística de UTF-16 e,
+/// por fim, detecção estatística
+pub fn decode(bytes: &[u8], encoding_override: Option<&str>) -> Result<DecodedText, String> {
+    if let Some(label) = encoding_override.map(str::trim).filter(|l| !l.is_empty()) {
+        let encoding = Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| format!("Unknown encoding: {}", label))?;
+        let bom =
+            Encoding::for_bom(bytes).is_some_and(|(bom_encoding, _)| bom_encoding == encoding);
+        let (text, _) = encoding.decode_with_bom_removal(bytes);
+        return Ok(DecodedText {
+            text: text.into_owned(),
+            encoding,
+            bom,
+        });
+    }
+
+    let (encoding, bom_len) = detect(bytes);
+    let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+
+    Ok(DecodedText {
+        text: text.into_owned(),
+        encoding,
+        bom: bom_len > 0,
+    })
+}
+
+/// Detecta o encoding, retornando também o tamanho do BOM (0 se não houver)
+pub fn detect(bytes: &[u8]) -> (&'static Encoding, usize) {
+    if let Some(found) = Encoding::for_bom(bytes) {
+        return found;
+    }
+
+    if let Some(encoding) = sniff_utf16(bytes) {
+        return (encoding, 0);
+    }
+
+    if std::str::from_utf8(bytes).is_ok() {
+        return (UTF_8, 0);
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    (detector.guess(None, true), 0)
+}
+
+/// Serializa o arquivo e codifica para gravação
+/// Sem `output_encoding` reproduz o encoding, o BOM e as quebras de linha de
+/// origem; se o texto (ex.: uma tradução) não couber no encoding legado de
+/// origem, grava em UTF-8. Um `output_encoding` explícito que não represente
+/// o texto é erro.
+pub fn encode_file(
+    file: &SubtitleFile,
+    output_encoding: Option<&str>,
+) -> Result<(Vec<u8>, &'static Encoding), String> {
+    let text = file.line_ending.apply(&file.serialize());
+
+    if let Some(label) = output_encoding.map(str::trim).filter(|l| !l.is_empty()) {
+        let encoding = Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| format!("Unknown encoding: {}", label))?;
+        let bytes = encode(&text, encoding, file.bom)
+            .ok_or_else(|| format!("Subtitle text cannot be represented in {}", encoding.name()))?;
+        return Ok((bytes, encoding));
+    }
+
+    let source = file
+        .encoding
+        .as_deref()
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .unwrap_or(UTF_8);
+
+    match encode(&text, source, file.bom) {
+        Some(bytes) => Ok((bytes, source)),
+        None => Ok((encode(&text, UTF_8, file.bom).unwrap_or_default(), UTF_8)),
+    }
+}
+
+/// Codifica o texto, retornando None se algum caractere não for representável
+/// `bom` vale para UTF-8 e UTF-16; nos encodings legados não existe BOM
+pub fn encode(text: &str, encoding: &'static Encoding, bom: bool) -> Option<Vec<u8>> {
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let big_endian = encoding == UTF_16BE;
+        let units = bom.then_some(0xFEFF).into_iter().chain(text.encode_utf16());
+        return Some(
+            units
+                .flat_map(|u| {
+                    if big_endian {
+                        u.to_be_bytes()
+                    } else {
+                        u.to_le_bytes()
+                    }
+                })
+                .collect(),
+        );
+    }
+
+    if encoding == UTF_8 {
+        let mut bytes = Vec::with_capacity(text.len() + 3);
+        if bom {
+            bytes.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+        }
+        bytes.extend_from_slice(text.as_bytes());
+        return Some(bytes);
+    }
+
+    let (bytes, _, had_errors) = encoding.encode(text);
+    if had_errors {
+        None
+    } else {
+        Some(bytes.into_owned())
+    }
+}
+
+/// Reconhece UTF-16 sem BOM pela posição dos bytes nulos
+/// Legendas têm muitos caracteres ASCII (números, "-->", vírgulas), então
+/// um dos lados de cada par de bytes fica quase sempre zerado
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(UTF16_SNIFF_LEN) & !1];
+    if sample.len() < 4 {
+        return None;
+    }
+
+    let pairs = sample.len() / 2;
+    let even_zeros = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd_zeros = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|&&b| b == 0)
+        .count();
+
+    // Mais de 30% de zeros de um lado e quase nenhum do outro
+    let mostly = |count: usize| count * 10 > pairs * 3;
+    let rarely = |count: usize| count * 20 < pairs;
+
+    if mostly(odd_zeros) && rarely(even_zeros) {
+        Some(UTF_16LE)
+    } else if mostly(even_zeros) && rarely(odd_zeros) {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{SHIFT_JIS, WINDOWS_1252};
+
+    const SRT_LATIN: &str = "1\n00:00:01,000 --> 00:00:04,000\nOlá, você já está pronto?\n\n2\n00:00:05,000 --> 00:00:08,000\nSim, é a única opção que nós temos agora.\n";
+    const SRT_JAPANESE: &str = "1\n00:00:01,000 --> 00:00:04,000\nこんにちは、世界。今日はいい天気ですね。\n\n2\n00:00:05,000 --> 00:00:08,000\n私の名前はキリトです。よろしくお願いします。\n";
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_detects_utf8_and_bom() {
+        let decoded = decode(SRT_LATIN.as_bytes(), None).unwrap();
+        assert_eq!(decoded.encoding, UTF_8);
+        assert!(!decoded.bom);
+
+        let mut with_bom = vec![0xEF, 0xBB, 0xBF];
+        with_bom.extend_from_slice(SRT_LATIN.as_bytes());
+        let decoded = decode(&with_bom, None).unwrap();
+        assert!(decoded.bom);
+        assert_eq!(decoded.text, SRT_LATIN);
+    }
+
+    #[test]
+    fn test_detects_utf16_with_and_without_bom() {
+        let mut with_bom = vec![0xFF, 0xFE];
+        with_bom.extend(utf16le(SRT_JAPANESE));
+        let decoded = decode(&with_bom, None).unwrap();
+        assert_eq!(decoded.encoding, UTF_16LE);
+        assert!(decoded.bom);
+        assert_eq!(decoded.text, SRT_JAPANESE);
+
+        let decoded = decode(&utf16le(SRT_LATIN), None).unwrap();
+        assert_eq!(decoded.encoding, UTF_16LE);
+        assert!(!decoded.bom);
+        assert_eq!(decoded.text, SRT_LATIN);
+    }
+
+    #[test]
+    fn test_detects_legacy_encodings() {
+        let (bytes, _, _) = WINDOWS_1252.encode(SRT_LATIN);
+        let decoded = decode(&bytes, None).unwrap();
+        assert_eq!(decoded.encoding, WINDOWS_1252);
+        assert_eq!(decoded.text, SRT_LATIN);
+
+        let (bytes, _, _) = SHIFT_JIS.encode(SRT_JAPANESE);
+        let decoded = decode(&bytes, None).unwrap();
+        assert_eq!(decoded.encoding, SHIFT_JIS);
+        assert_eq!(decoded.text, SRT_JAPANESE);
+    }
+
+    #[test]
+    fn test_save_reproduces_encoding_bom_and_line_endings() {
+        let crlf = SRT_LATIN.replace('\n', "\r\n");
+        let (source, _, _) = WINDOWS_1252.encode(&crlf);
+        let decoded = decode(&source, None).unwrap();
+
+        let mut file = crate::subtitle::srt::parse(&decoded.text).unwrap();
+        file.encoding = Some(decoded.encoding.name().to_string());
+        file.bom = decoded.bom;
+
+        let (bytes, encoding) = encode_file(&file, None).unwrap();
+        assert_eq!(encoding, WINDOWS_1252);
+        assert_eq!(bytes, source.into_owned());
+
+        // Origem sem BOM: UTF-16 também sai sem
+        let (bytes, _) = encode_file(&file, Some("utf-16le")).unwrap();
+        assert_eq!(bytes, utf16le(&crlf));
+        assert_eq!(decode(&bytes, None).unwrap().text, crlf);
+
+        file.bom = true;
+        let (bytes, _) = encode_file(&file, Some("utf-16le")).unwrap();
+        assert_eq!(&bytes[..2], &[0xFF, 0xFE]);
+        assert_eq!(decode(&bytes, None).unwrap().text, crlf);
+    }
+
+    #[test]
+    fn test_save_falls_back_to_utf8_when_text_does_not_fit() {
+        let (source, _, _) = WINDOWS_1252.encode(SRT_LATIN);
+        let decoded = decode(&source, None).unwrap();
+        let mut file = crate::subtitle::srt::parse(&decoded.text).unwrap();
+        file.encoding = Some(decoded.encoding.name().to_string());
+        file.entries[0].text = "こんにちは".to_string();
+
+        let (_, encoding) = encode_file(&file, None).unwrap();
+        assert_eq!(encoding, UTF_8);
+        assert!(encode_file(&file, Some("windows-1252")).is_err());
+    }
+
+    #[test]
+    fn test_encoding_override() {
+        let (bytes, _, _) = WINDOWS_1252.encode(SRT_LATIN);
+        let decoded = decode(&bytes, Some("latin1")).unwrap();
+        assert_eq!(decoded.encoding, WINDOWS_1252);
+        assert_eq!(decoded.text, SRT_LATIN);
+
+        assert!(decode(&bytes, Some("klingon")).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use super::{LineEnding, ParseDiagnostic, SubtitleEntry, SubtitleFile, SubtitleFormat, Timestamp};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Duração da última linha (LRC só marca o início de cada linha)
+const LAST_LINE_DURATION_MS: u64 = 5000;
+
+// Tag de tempo no início da linha: [mm:ss.xx] ou [mm:ss]
+static TIME_TAG_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\[(\d+):(\d{1,2})(?:[.:](\d{1,3}))?\]").unwrap());
+
+// Tag de metadados: [ar:Artista], [ti:Título], [offset:+500]...
+static METADATA_TAG_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\[([A-Za-z#]+):(.*)\]$").unwrap());
+
+/// Faz parse de um arquivo de letras LRC
+/// Tags de metadados vão para `preamble`. Uma linha com várias tags de tempo
+/// ([00:12.00][00:45.00]refrão) gera uma entrada por tag; linhas de tempo
+/// vazias marcam o fim da linha anterior. O `offset` declarado é aplicado.
+pub fn parse(content: &str) -> Result<SubtitleFile, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let line_ending = LineEnding::detect(content);
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+
+    let mut preamble = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut lines: Vec<(Timestamp, String)> = Vec::new();
+
+    for (i, raw_line) in content.split('\n').enumerate() {
+        let line = raw_line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut rest = line;
+        let mut times = Vec::new();
+        while let Some(caps) = TIME_TAG_REGEX.captures(rest) {
+            times.push(parse_time_tag(&caps));
+            rest = &rest[caps[0].len()..];
+        }
+
+        if !times.is_empty() {
+            for time in times {
+                lines.push((time, rest.trim().to_string()));
+            }
+        } else if METADATA_TAG_REGEX.is_match(line) {
+            preamble.push(line.to_string());
+        } else {
+            diagnostics.push(ParseDiagnostic::new(i + 1, "Line has no time tag", line));
+        }
+    }
+
+    // Linhas repetidas em vários instantes ficam em ordem cronológica
+    lines.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let offset = offset_ms(&preamble);
+    let mut entries = Vec::new();
+    for (i, (start, text)) in lines.iter().enumerate() {
+        if text.is_empty() {
+            continue;
+        }
+        let end = match lines.get(i + 1) {
+            Some((next, _)) => next.clone(),
+            None => start.shifted(LAST_LINE_DURATION_MS as i64),
+        };
+        // offset positivo adianta as letras
+        let (start, end) = if offset == 0 {
+            (start.clone(), end)
+        } else {
+            (start.shifted(-offset), end.shifted(-offset))
+        };
+        entries.push(SubtitleEntry {
+            index: entries.len() + 1,
+            start_time: start,
+            end_time: end,
+            text: text.clone(),
+            metadata: None,
+        });
+    }
+
+    if entries.is_empty() {
+        return Err("No valid lyric lines found".to_string());
+    }
+
+    Ok(SubtitleFile {
+        line_ending,
+        diagnostics,
+        preamble,
+        ..SubtitleFile::new(SubtitleFormat::Lrc, entries)
+    })
+}
+
+/// Converte uma tag [mm:ss.xx] ou [mm:ss:xx] (minutos podem passar de 59)
+fn parse_time_tag(caps: &regex::Captures) -> Timestamp {
+    let minutes: u64 = caps[1].parse().unwrap_or(0);
+    let seconds: u64 = caps[2].parse().unwrap_or(0);
+    let fraction = caps.get(3).map_or(0, |m| {
+        let digits = m.as_str();
+        digits.parse::<u64>().unwrap_or(0) * 10u64.pow(3 - digits.len() as u32)
+    });
+
+    let millis = (minutes * 60 + seconds) * 1000 + fraction;
+
+    let text = &caps[0][1..caps[0].len() - 1];
+    match Timestamp::parse(text) {
+        // Mantém o texto original quando ele dá o mesmo tempo ([mm:ss:xx]
+        // seria lido como H:MM:SS)
+        Ok(ts) if ts.as_millis() == millis => ts,
+        _ => Timestamp::from_millis(millis),
+    }
+}
+
+/// Lê a tag [offset:±ms] dos metadados
+fn offset_ms(preamble: &[String]) -> i64 {
+    preamble
+        .iter()
+        .filter_map(|l| METADATA_TAG_REGEX.captures(l))
+        .find(|caps| caps[1].eq_ignore_ascii_case("offset"))
+        .and_then(|caps| caps[2].trim().trim_start_matches('+').parse().ok())
+        .unwrap_or(0)
+}
+
+/// Serializa para formato LRC
+/// Quando uma linha termina antes da próxima começar, escreve uma linha de
+/// tempo vazia marcando o fim
+pub fn serialize(file: &SubtitleFile) -> String {
+    let offset = offset_ms(&file.preamble);
+    let tag = |ts: &Timestamp| {
+        let ts = if offset == 0 {
+            ts.clone()
+        } else {
+            ts.shifted(offset)
+        };
+        format!("[{}]", ts.to_string_for(&SubtitleFormat::Lrc))
+    };
+
+    let mut output = String::new();
+    for line in &file.preamble {
+        output.push_str(line);
+        output.push('\n');
+    }
+
+    for (i, entry) in file.entries.iter().enumerate() {
+        output.push_str(&tag(&entry.start_time));
+        output.push_str(&entry.text.replace('\n', " "));
+        output.push('\n');
+
+        let ends_early = match file.entries.get(i + 1) {
+            Some(next) => entry.end_time < next.start_time,
+            None => {
+                entry.end_time.as_millis() != entry.start_time.as_millis() + LAST_LINE_DURATION_MS
+            }
+        };
+        if ends_early {
+            output.push_str(&tag(&entry.end_time));
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "[ti:Song]\n[ar:Artist]\n[00:12.00]First line\n[00:15.30]Second line\n[00:18.00]\n[01:05.50]Last line\n";
+
+    #[test]
+    fn test_parse_lrc_round_trip() {
+        let result = parse(SAMPLE).unwrap();
+        assert_eq!(result.preamble, vec!["[ti:Song]", "[ar:Artist]"]);
+        assert_eq!(result.entries.len(), 3);
+        assert_eq!(result.entries[0].end_time.as_millis(), 15_300);
+        assert_eq!(result.entries[1].end_time.as_millis(), 18_000);
+        assert_eq!(result.entries[2].start_time.as_millis(), 65_500);
+        assert_eq!(serialize(&result), SAMPLE);
+    }
+
+    #[test]
+    fn test_repeated_tags_and_offset() {
+        let content =
+            "[offset:+500]\n[00:10.00][00:30.00]Chorus\n[00:20.00]Verse\n[75:00.00]Long\n";
+        let result = parse(content).unwrap();
+        let texts: Vec<&str> = result.entries.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, vec!["Chorus", "Verse", "Chorus", "Long"]);
+        assert_eq!(result.entries[0].start_time.as_millis(), 9_500);
+        assert_eq!(result.entries[3].start_time.as_millis(), 75 * 60_000 - 500);
+        assert!(serialize(&result).contains("[75:00.00]Long\n"));
+    }
+
+    #[test]
+    fn test_colon_fraction_tag() {
+        let result = parse("[01:02:50]Colon\n[01:03.00]Dot\n").unwrap();
+        assert_eq!(result.entries[0].start_time.as_millis(), 62_500);
+        assert_eq!(result.entries[0].end_time.as_millis(), 63_000);
+        assert!(serialize(&result).starts_with("[01:02.50]Colon\n"));
+    }
+}
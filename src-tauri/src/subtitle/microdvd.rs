@@ -0,0 +1,153 @@
+use super::{LineEnding, ParseDiagnostic, SubtitleEntry, SubtitleFile, SubtitleFormat, Timestamp};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Frame rate usado quando o arquivo não declara um
+pub const DEFAULT_FRAME_RATE: f64 = 23.976;
+
+/// Duração (em segundos) de uma linha sem frame final ("{100}{}texto")
+const OPEN_END_SECONDS: f64 = 3.0;
+
+// Regex para linhas MicroDVD: {início}{fim}texto
+static LINE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\{(\d+)\}\{(\d*)\}(.*)$").unwrap());
+
+/// Faz parse de um arquivo MicroDVD (.sub baseado em frames)
+/// Uma primeira linha "{1}{1}23.976" declara o frame rate; sem ela usa
+/// `DEFAULT_FRAME_RATE`. "|" separa linhas do texto.
+pub fn parse(content: &str) -> Result<SubtitleFile, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let line_ending = LineEnding::detect(content);
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+
+    let mut frame_rate = None;
+    let mut cues: Vec<(u64, Option<u64>, String)> = Vec::new();
+    let mut diagnostics = Vec::new();
+
+    for (i, line) in content.split('\n').enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some(caps) = LINE_REGEX.captures(line) else {
+            diagnostics.push(ParseDiagnostic::new(i + 1, "Invalid MicroDVD line", line));
+            continue;
+        };
+
+        let start: u64 = caps[1].parse().unwrap_or(0);
+        let end: Option<u64> = caps[2].parse().ok();
+        let text = caps[3].to_string();
+
+        // Declaração de frame rate antes do primeiro cue
+        if cues.is_empty() && frame_rate.is_none() && start <= 1 && end.is_some_and(|e| e <= 1) {
+            if let Some(fps) = text.trim().parse::<f64>().ok().filter(|f| *f > 0.0) {
+                frame_rate = Some(fps);
+                continue;
+            }
+        }
+
+        cues.push((start, end, text));
+    }
+
+    let fps = frame_rate.unwrap_or(DEFAULT_FRAME_RATE);
+    let open_end = (fps * OPEN_END_SECONDS).round() as u64;
+
+    let entries: Vec<SubtitleEntry> = cues
+        .into_iter()
+        .enumerate()
+        .map(|(i, (start, end, text))| SubtitleEntry {
+            index: i + 1,
+            start_time: Timestamp::from_frames(start, fps),
+            end_time: Timestamp::from_frames(end.unwrap_or(start + open_end), fps),
+            text: text.replace('|', "\n"),
+            metadata: None,
+        })
+        .collect();
+
+    if entries.is_empty() {
+        return Err("No valid subtitle entries found".to_string());
+    }
+
+    Ok(SubtitleFile {
+        line_ending,
+        diagnostics,
+        frame_rate,
+        ..SubtitleFile::new(SubtitleFormat::MicroDvd, entries)
+    })
+}
+
+/// Serializa para MicroDVD usando `file.frame_rate` (ou o padrão)
+/// O frame rate só é declarado no arquivo quando definido
+pub fn serialize(file: &SubtitleFile) -> String {
+    let fps = file.frame_rate.unwrap_or(DEFAULT_FRAME_RATE);
+    let mut output = String::new();
+
+    if let Some(declared) = file.frame_rate {
+        output.push_str(&format!("{{1}}{{1}}{}\n", declared));
+    }
+
+    for entry in &file.entries {
+        output.push_str(&format!(
+            "{{{}}}{{{}}}{}\n",
+            entry.start_time.to_frames(fps),
+            entry.end_time.to_frames(fps),
+            entry.text.replace('\n', "|")
+        ));
+    }
+
+    output
+}
+
+/// Troca o frame rate do arquivo
+/// Em arquivos MicroDVD os tempos são recalculados a partir dos frames
+/// originais (corrige um .sub lido com o frame rate errado); nos demais
+/// formatos só define o frame rate usado ao exportar para MicroDVD
+pub fn set_frame_rate(file: &mut SubtitleFile, frame_rate: f64) -> Result<(), String> {
+    if !(frame_rate.is_finite() && frame_rate > 0.0) {
+        return Err(format!("Invalid frame rate: {}", frame_rate));
+    }
+
+    if file.format == SubtitleFormat::MicroDvd {
+        let current = file.frame_rate.unwrap_or(DEFAULT_FRAME_RATE);
+        for entry in &mut file.entries {
+            entry.start_time =
+                Timestamp::from_frames(entry.start_time.to_frames(current), frame_rate);
+            entry.end_time = Timestamp::from_frames(entry.end_time.to_frames(current), frame_rate);
+        }
+    }
+
+    file.frame_rate = Some(frame_rate);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "{1}{1}25\n{25}{75}Hello|World\n{100}{150}{y:i}Second\n";
+
+    #[test]
+    fn test_parse_microdvd() {
+        let result = parse(SAMPLE).unwrap();
+        assert_eq!(result.frame_rate, Some(25.0));
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.entries[0].start_time.as_millis(), 1000);
+        assert_eq!(result.entries[0].end_time.as_millis(), 3000);
+        assert_eq!(result.entries[0].text, "Hello\nWorld");
+        assert_eq!(result.entries[1].text, "{y:i}Second");
+        assert_eq!(serialize(&result), SAMPLE);
+    }
+
+    #[test]
+    fn test_default_and_changed_frame_rate() {
+        let mut result = parse("{24}{48}Hi\n").unwrap();
+        assert_eq!(result.frame_rate, None);
+        assert_eq!(result.entries[0].start_time.as_millis(), 1001);
+        assert_eq!(serialize(&result), "{24}{48}Hi\n");
+
+        set_frame_rate(&mut result, 24.0).unwrap();
+        assert_eq!(result.entries[0].start_time.as_millis(), 1000);
+        assert_eq!(serialize(&result), "{1}{1}24\n{24}{48}Hi\n");
+        assert!(set_frame_rate(&mut result, 0.0).is_err());
+    }
+}
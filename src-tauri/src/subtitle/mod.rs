@@ -1,23 +1,53 @@
 pub mod ass;
+pub mod convert;
+pub mod detect;
+pub mod encoding;
+pub mod lrc;
+pub mod microdvd;
+pub mod sami;
+pub mod sbv;
 pub mod srt;
+pub mod ssa;
+pub mod subviewer;
+pub mod timestamp;
+pub mod timing;
+pub mod ttml;
+pub mod vtt;
+
+pub use timestamp::Timestamp;
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 
 /// Representa uma entrada de legenda (comum a todos os formatos)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubtitleEntry {
     pub index: usize,
-    pub start_time: String,
-    pub end_time: String,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
     pub text: String,
     /// Metadados específicos do formato (estilo, posição, etc.)
     #[serde(default)]
     pub metadata: Option<SubtitleMetadata>,
 }
 
-/// Metadados específicos do formato ASS
+impl SubtitleEntry {
+    /// Indica se a entrada deve ser enviada para tradução
+    /// Eventos que não são diálogo (Picture, Sound, etc.) nunca são traduzidos
+    pub fn is_translatable(&self, skip_comments: bool) -> bool {
+        match self.metadata.as_ref().and_then(|m| m.event_kind) {
+            None | Some(AssEventKind::Dialogue) => true,
+            Some(AssEventKind::Comment) => !skip_comments,
+            Some(_) => false,
+        }
+    }
+}
+
+/// Metadados específicos do formato (ASS, VTT, TTML, SAMI)
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SubtitleMetadata {
+    /// Estilo (ASS), referência de estilo (TTML) ou classe do <P> (SAMI)
     pub style: Option<String>,
     pub name: Option<String>,
     pub margin_l: Option<i32>,
@@ -26,6 +56,63 @@ pub struct SubtitleMetadata {
     pub effect: Option<String>,
     /// Formato do Layer (ASS)
     pub layer: Option<i32>,
+    /// Tipo do evento: Dialogue, Comment, etc. (ASS)
+    #[serde(default)]
+    pub event_kind: Option<AssEventKind>,
+    /// Campos de [Events] sem representação própria, ex: "marked" (ASS/SSA)
+    #[serde(default)]
+    pub extra_fields: BTreeMap<String, String>,
+    /// Identificador do cue (VTT), xml:id (TTML) ou ID do <P> (SAMI)
+    pub identifier: Option<String>,
+    /// Configurações do cue, ex: "position:10% line:0 align:start" (VTT)
+    /// ou coordenadas "X1:100 X2:600 Y1:50 Y2:90" (SRT)
+    pub cue_settings: Option<String>,
+    /// Referência da região de exibição (TTML)
+    pub region: Option<String>,
+}
+
+/// Tipos de evento da seção [Events] (ASS/SSA)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum AssEventKind {
+    #[default]
+    Dialogue,
+    Comment,
+    Picture,
+    Sound,
+    Movie,
+    Command,
+}
+
+impl AssEventKind {
+    const ALL: [AssEventKind; 6] = [
+        AssEventKind::Dialogue,
+        AssEventKind::Comment,
+        AssEventKind::Picture,
+        AssEventKind::Sound,
+        AssEventKind::Movie,
+        AssEventKind::Command,
+    ];
+
+    /// Nome do evento como aparece no arquivo
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssEventKind::Dialogue => "Dialogue",
+            AssEventKind::Comment => "Comment",
+            AssEventKind::Picture => "Picture",
+            AssEventKind::Sound => "Sound",
+            AssEventKind::Movie => "Movie",
+            AssEventKind::Command => "Command",
+        }
+    }
+
+    /// Separa "Tipo: resto" de uma linha de evento
+    pub fn split_line(line: &str) -> Option<(AssEventKind, &str)> {
+        Self::ALL.iter().find_map(|kind| {
+            line.strip_prefix(kind.as_str())
+                .and_then(|rest| rest.strip_prefix(':'))
+                .map(|rest| (*kind, rest))
+        })
+    }
 }
 
 /// Representa um arquivo de legenda completo
@@ -36,6 +123,86 @@ pub struct SubtitleFile {
     /// Headers e metadados do arquivo (para ASS: Script Info, Styles, etc.)
     #[serde(default)]
     pub headers: Option<AssHeaders>,
+    /// Cabeçalho e blocos NOTE/STYLE/REGION (VTT)
+    #[serde(default)]
+    pub vtt_headers: Option<VttHeaders>,
+    /// Encoding em que o arquivo foi lido (ex.: "UTF-8", "Shift_JIS")
+    #[serde(default)]
+    pub encoding: Option<String>,
+    /// Se o arquivo original começava com BOM
+    #[serde(default)]
+    pub bom: bool,
+    /// Quebra de linha usada no arquivo original
+    #[serde(default)]
+    pub line_ending: LineEnding,
+    /// Linhas ou blocos ignorados durante o parse
+    #[serde(default)]
+    pub diagnostics: Vec<ParseDiagnostic>,
+    /// Linhas de cabeçalho de formatos simples ([INFORMATION] do SubViewer, tags do LRC)
+    #[serde(default)]
+    pub preamble: Vec<String>,
+    /// Frame rate declarado ou escolhido (MicroDVD usa frames em vez de tempo)
+    #[serde(default)]
+    pub frame_rate: Option<f64>,
+    /// Documento em volta das legendas (TTML, SAMI)
+    #[serde(default)]
+    pub markup_headers: Option<MarkupHeaders>,
+}
+
+/// Problema encontrado durante o parse (entrada descartada)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ParseDiagnostic {
+    /// Linha no arquivo original (1-indexed)
+    pub line: usize,
+    pub reason: String,
+    /// Texto original da linha ou bloco descartado
+    pub raw: String,
+}
+
+impl ParseDiagnostic {
+    pub fn new(line: usize, reason: impl Into<String>, raw: impl Into<String>) -> Self {
+        Self {
+            line,
+            reason: reason.into(),
+            raw: raw.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.reason)
+    }
+}
+
+/// Estilo de quebra de linha do arquivo
+/// Os parsers normalizam tudo para "\n"; o estilo original é reaplicado ao salvar
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    /// Detecta o estilo predominante no texto
+    pub fn detect(text: &str) -> Self {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        if crlf > lf {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    /// Aplica o estilo a um texto com quebras "\n"
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            LineEnding::Lf => text.to_string(),
+            LineEnding::CrLf => text.replace('\n', "\r\n"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -44,52 +211,270 @@ pub enum SubtitleFormat {
     Ass,
     Ssa,
     Vtt,
+    MicroDvd,
+    SubViewer,
+    Sbv,
+    Lrc,
+    Ttml,
+    Sami,
+}
+
+impl SubtitleFormat {
+    /// Formato correspondente a uma extensão (sem o ponto)
+    /// ".sub" é ambíguo (MicroDVD ou SubViewer); a detecção por conteúdo decide
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "srt" => Some(SubtitleFormat::Srt),
+            "ass" => Some(SubtitleFormat::Ass),
+            "ssa" => Some(SubtitleFormat::Ssa),
+            "vtt" => Some(SubtitleFormat::Vtt),
+            "sub" => Some(SubtitleFormat::MicroDvd),
+            "sbv" => Some(SubtitleFormat::Sbv),
+            "lrc" => Some(SubtitleFormat::Lrc),
+            "ttml" | "dfxp" => Some(SubtitleFormat::Ttml),
+            "smi" | "sami" => Some(SubtitleFormat::Sami),
+            _ => None,
+        }
+    }
+
+    /// Formato pelo nome ("microdvd", "subviewer") ou pela extensão
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "microdvd" => Some(SubtitleFormat::MicroDvd),
+            "subviewer" => Some(SubtitleFormat::SubViewer),
+            other => Self::from_extension(other),
+        }
+    }
+
+    /// Extensão padrão do formato (sem o ponto)
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::Ass => "ass",
+            SubtitleFormat::Ssa => "ssa",
+            SubtitleFormat::Vtt => "vtt",
+            SubtitleFormat::MicroDvd | SubtitleFormat::SubViewer => "sub",
+            SubtitleFormat::Sbv => "sbv",
+            SubtitleFormat::Lrc => "lrc",
+            SubtitleFormat::Ttml => "ttml",
+            SubtitleFormat::Sami => "smi",
+        }
+    }
 }
 
 /// Headers específicos do formato ASS/SSA
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Guarda todas as seções na ordem original, linha a linha, para que
+/// `parse -> serialize` reproduza o arquivo sem perdas
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssHeaders {
-    pub script_info: Vec<String>,
-    pub styles: Vec<String>,
-    pub fonts: Vec<String>,
-    pub graphics: Vec<String>,
+    /// Seções na ordem em que aparecem (inclusive desconhecidas e [Events])
+    pub sections: Vec<AssSection>,
+    /// Campos do "Format:" de [Events] como declarados no arquivo
+    #[serde(default)]
+    pub events_format: Vec<String>,
+    /// Margens escritas com zeros à esquerda ("0000") em vez de "0"
+    #[serde(default = "default_pad_margins")]
+    pub pad_margins: bool,
+}
+
+impl Default for AssHeaders {
+    fn default() -> Self {
+        Self {
+            sections: Vec::new(),
+            events_format: Vec::new(),
+            pad_margins: default_pad_margins(),
+        }
+    }
+}
+
+fn default_pad_margins() -> bool {
+    true
+}
+
+impl AssHeaders {
+    /// Busca uma seção pelo nome (sem colchetes, sem diferenciar maiúsculas)
+    pub fn section(&self, name: &str) -> Option<&AssSection> {
+        self.sections
+            .iter()
+            .find(|s| s.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Seção de um arquivo ASS/SSA preservada linha a linha
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AssSection {
+    /// Linhas brutas da seção, começando pelo cabeçalho "[Nome]"
+    /// Para [Events], apenas as linhas anteriores ao primeiro evento
+    pub lines: Vec<String>,
+    /// Linhas após o último evento (apenas [Events])
+    #[serde(default)]
+    pub trailing: Vec<String>,
+    /// Linhas entre eventos (comentários, linhas em branco, eventos
+    /// inválidos) com o número de eventos que as precedem (apenas [Events])
+    #[serde(default)]
+    pub interleaved: Vec<(usize, String)>,
+}
+
+impl AssSection {
+    /// Nome da seção sem os colchetes (vazio para linhas antes da primeira seção)
+    pub fn name(&self) -> &str {
+        self.lines
+            .first()
+            .map(|l| l.trim())
+            .and_then(|l| l.strip_prefix('['))
+            .and_then(|l| l.strip_suffix(']'))
+            .unwrap_or("")
+    }
+
+    pub fn is_events(&self) -> bool {
+        self.name().eq_ignore_ascii_case("events")
+    }
+}
+
+/// Headers específicos do formato WebVTT
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct VttHeaders {
+    /// Linha "WEBVTT" e metadados do cabeçalho
+    pub header: Vec<String>,
+    /// Blocos NOTE, STYLE e REGION na ordem original
+    pub blocks: Vec<VttBlock>,
+}
+
+/// Documento XML/HTML em volta das legendas (TTML, SAMI)
+/// Guardado como texto bruto para que <head>, estilos e regiões sejam
+/// reescritos sem alteração
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MarkupHeaders {
+    /// Tudo antes da primeira legenda
+    pub prologue: String,
+    /// Tudo depois da última legenda
+    pub epilogue: String,
+    /// Trechos entre legendas (fronteiras de <div>, comentários, <p>
+    /// inválidos) com o número de legendas que os precedem (apenas TTML)
+    #[serde(default)]
+    pub interleaved: Vec<(usize, String)>,
+}
+
+/// Bloco não-cue de um arquivo WebVTT
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VttBlock {
+    pub kind: VttBlockKind,
+    /// Conteúdo bruto do bloco (incluindo a palavra-chave)
+    pub content: String,
+    /// Quantidade de cues que antecedem o bloco
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum VttBlockKind {
+    Note,
+    Style,
+    Region,
 }
 
 impl SubtitleFile {
+    /// Arquivo sem headers nem metadados de origem
+    pub fn new(format: SubtitleFormat, entries: Vec<SubtitleEntry>) -> Self {
+        Self {
+            format,
+            entries,
+            headers: None,
+            vtt_headers: None,
+            encoding: None,
+            bom: false,
+            line_ending: LineEnding::Lf,
+            diagnostics: Vec::new(),
+            preamble: Vec::new(),
+            frame_rate: None,
+            markup_headers: None,
+        }
+    }
+
     /// Detecta o formato baseado na extensão do arquivo
     pub fn detect_format(filename: &str) -> Option<SubtitleFormat> {
-        let ext = filename.rsplit('.').next()?.to_lowercase();
-        match ext.as_str() {
-            "srt" => Some(SubtitleFormat::Srt),
-            "ass" => Some(SubtitleFormat::Ass),
-            "ssa" => Some(SubtitleFormat::Ssa),
-            "vtt" => Some(SubtitleFormat::Vtt),
-            _ => None,
-        }
+        SubtitleFormat::from_extension(filename.rsplit('.').next()?)
     }
 
     /// Faz parse de um arquivo de legenda
     pub fn parse(content: &str, format: SubtitleFormat) -> Result<Self, String> {
         match format {
             SubtitleFormat::Srt => srt::parse(content),
-            SubtitleFormat::Ass | SubtitleFormat::Ssa => ass::parse(content),
-            SubtitleFormat::Vtt => Err("VTT parsing not yet implemented".to_string()),
+            SubtitleFormat::Ass => ass::parse(content),
+            SubtitleFormat::Ssa => ssa::parse(content),
+            SubtitleFormat::Vtt => vtt::parse(content),
+            SubtitleFormat::MicroDvd => microdvd::parse(content),
+            SubtitleFormat::SubViewer => subviewer::parse(content),
+            SubtitleFormat::Sbv => sbv::parse(content),
+            SubtitleFormat::Lrc => lrc::parse(content),
+            SubtitleFormat::Ttml => ttml::parse(content),
+            SubtitleFormat::Sami => sami::parse(content),
         }
     }
 
+    /// Faz parse em modo estrito: qualquer entrada descartada é erro
+    pub fn parse_strict(content: &str, format: SubtitleFormat) -> Result<Self, String> {
+        let file = Self::parse(content, format)?;
+        if file.diagnostics.is_empty() {
+            return Ok(file);
+        }
+
+        let details: Vec<String> = file.diagnostics.iter().map(|d| d.to_string()).collect();
+        Err(format!(
+            "Invalid subtitle entries found:\n{}",
+            details.join("\n")
+        ))
+    }
+
     /// Serializa de volta para o formato original
     pub fn serialize(&self) -> String {
         match self.format {
             SubtitleFormat::Srt => srt::serialize(self),
-            SubtitleFormat::Ass | SubtitleFormat::Ssa => ass::serialize(self),
-            SubtitleFormat::Vtt => "".to_string(), // TODO
+            SubtitleFormat::Ass => ass::serialize(self),
+            SubtitleFormat::Ssa => ssa::serialize(self),
+            SubtitleFormat::Vtt => vtt::serialize(self),
+            SubtitleFormat::MicroDvd => microdvd::serialize(self),
+            SubtitleFormat::SubViewer => subviewer::serialize(self),
+            SubtitleFormat::Sbv => sbv::serialize(self),
+            SubtitleFormat::Lrc => lrc::serialize(self),
+            SubtitleFormat::Ttml => ttml::serialize(self),
+            SubtitleFormat::Sami => sami::serialize(self),
         }
     }
 
+    /// Serializa e codifica como bytes prontos para gravar em disco
+    /// Sem `output_encoding` reproduz encoding, BOM e quebras de linha de origem
+    /// (ver `encoding::encode_file`)
+    pub fn to_bytes(
+        &self,
+        output_encoding: Option<&str>,
+    ) -> Result<(Vec<u8>, &'static encoding_rs::Encoding), String> {
+        encoding::encode_file(self, output_encoding)
+    }
+
+    /// Aplica uma operação de retiming (ver `timing::apply`)
+    pub fn retime(
+        &mut self,
+        operation: &timing::TimingOperation,
+        range: Option<timing::EntryRange>,
+    ) -> Result<(), String> {
+        timing::apply(self, operation, range)
+    }
+
+    /// Converte para outro formato (ver `convert::convert`)
+    pub fn convert_to(&self, format: SubtitleFormat) -> SubtitleFile {
+        convert::convert(self, format)
+    }
+
     /// Extrai apenas os textos para tradução (preservando estrutura)
     pub fn extract_texts(&self) -> Vec<(usize, String)> {
+        self.extract_texts_filtered(false)
+    }
+
+    /// Extrai textos para tradução, opcionalmente ignorando comentários (ASS)
+    pub fn extract_texts_filtered(&self, skip_comments: bool) -> Vec<(usize, String)> {
         self.entries
             .iter()
+            .filter(|e| e.is_translatable(skip_comments))
             .map(|e| (e.index, e.text.clone()))
             .collect()
     }
@@ -0,0 +1,408 @@
+use super::{
+    LineEnding, MarkupHeaders, ParseDiagnostic, SubtitleEntry, SubtitleFile, SubtitleFormat,
+    SubtitleMetadata, Timestamp,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Classe usada nas entradas sem estilo quando o arquivo não veio de um SAMI
+const DEFAULT_CLASS: &str = "SUBTTL";
+
+/// Início do documento gerado quando o arquivo não veio de um SAMI
+const DEFAULT_PROLOGUE: &str = r#"<SAMI>
+<HEAD>
+<STYLE TYPE="text/css">
+<!--
+P { margin-left: 8pt; margin-right: 8pt; text-align: center; font-family: Arial; }
+.SUBTTL { Name: Subtitles; }
+-->
+</STYLE>
+</HEAD>
+<BODY>
+"#;
+
+/// Fim do documento gerado quando o arquivo não veio de um SAMI
+const DEFAULT_EPILOGUE: &str = "</BODY>\n</SAMI>\n";
+
+/// Duração da última entrada quando não há <SYNC> marcando o fim
+const LAST_ENTRY_DURATION_MS: u64 = 5000;
+
+// Regex para <SYNC ...>
+static SYNC_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<SYNC\b([^>]*)>").unwrap());
+
+// Regex para <P ...>
+static P_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<P\b([^>]*)>").unwrap());
+
+// Regex para <br> em qualquer grafia
+static BR_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<br\s*/?>").unwrap());
+
+// Regex para tags de fechamento que não fazem parte do texto
+static CLOSING_TAG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)</(?:P|SYNC)\s*>").unwrap());
+
+// Regex para as tags de formatação mantidas no texto das entradas
+static FORMATTING_TAG_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)</?(?:i|b|u|s|em|strong|font|span|ruby|rt)\b[^<>]*>").unwrap());
+
+// Regex para o início do fim do documento
+static BODY_END_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)</BODY\s*>").unwrap());
+
+// Regex para atributos HTML (com ou sem aspas)
+static ATTRIBUTE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"([\w:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#).unwrap());
+
+/// Atributos de uma tag, com o nome em minúsculas
+fn attributes(raw: &str) -> HashMap<String, String> {
+    ATTRIBUTE_REGEX
+        .captures_iter(raw)
+        .filter_map(|caps| {
+            let value = caps
+                .get(2)
+                .or_else(|| caps.get(3))
+                .or_else(|| caps.get(4))?;
+            Some((caps[1].to_ascii_lowercase(), value.as_str().to_string()))
+        })
+        .collect()
+}
+
+/// Faz parse de um arquivo SAMI (.smi)
+/// Cada <SYNC Start=ms> abre um bloco e cada <P> dentro dele vira uma
+/// entrada; a classe do <P> (idioma/estilo) vai para `style` e o ID para
+/// `identifier`. Um <SYNC> só com &nbsp; marca o fim da entrada anterior.
+/// <HEAD> e o fim do documento ficam em `markup_headers`.
+pub fn parse(content: &str) -> Result<SubtitleFile, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let line_ending = LineEnding::detect(content);
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+    let line_at = |pos: usize| content[..pos].matches('\n').count() + 1;
+
+    let syncs: Vec<regex::Captures> = SYNC_REGEX.captures_iter(&content).collect();
+    let Some(first_sync) = syncs.first() else {
+        return Err("No <SYNC> blocks found".to_string());
+    };
+    let first = first_sync.get(0).unwrap().start();
+    let body_end = BODY_END_REGEX
+        .find_at(&content, first)
+        .map_or(content.len(), |m| m.start());
+
+    // (início, classe, id, texto) de cada <P>; texto vazio marca o fim
+    let mut blocks: Vec<(Timestamp, Option<String>, Option<String>, String)> = Vec::new();
+    let mut diagnostics = Vec::new();
+
+    for (i, sync) in syncs.iter().enumerate() {
+        let whole = sync.get(0).unwrap();
+        if whole.start() >= body_end {
+            break;
+        }
+        let block_end = syncs
+            .get(i + 1)
+            .map_or(body_end, |next| next.get(0).unwrap().start())
+            .min(body_end);
+        let block = &content[whole.end()..block_end];
+
+        let start = attributes(&sync[1])
+            .get("start")
+            .and_then(|v| v.parse::<u64>().ok());
+        let Some(start) = start else {
+            diagnostics.push(ParseDiagnostic::new(
+                line_at(whole.start()),
+                "Missing or invalid Start attribute",
+                content[whole.start()..block_end].trim_end(),
+            ));
+            continue;
+        };
+        let start = Timestamp::from_millis(start);
+
+        let paragraphs: Vec<regex::Captures> = P_REGEX.captures_iter(block).collect();
+        if paragraphs.is_empty() {
+            blocks.push((start, None, None, clean_text(block)));
+            continue;
+        }
+        for (j, p) in paragraphs.iter().enumerate() {
+            let text_start = p.get(0).unwrap().end();
+            let text_end = paragraphs
+                .get(j + 1)
+                .map_or(block.len(), |next| next.get(0).unwrap().start());
+            let mut attributes = attributes(&p[1]);
+            blocks.push((
+                start.clone(),
+                attributes.remove("class"),
+                attributes.remove("id"),
+                clean_text(&block[text_start..text_end]),
+            ));
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (i, (start, class, id, text)) in blocks.iter().enumerate() {
+        if text.is_empty() {
+            continue;
+        }
+        // Termina no próximo <SYNC> com início posterior da mesma classe
+        // (idioma); blocos sem classe encerram todas
+        let end = blocks[i + 1..]
+            .iter()
+            .filter(|(_, next_class, ..)| {
+                class.is_none() || next_class.is_none() || next_class == class
+            })
+            .map(|(next, ..)| next)
+            .find(|next| *next > start)
+            .cloned()
+            .unwrap_or_else(|| start.shifted(LAST_ENTRY_DURATION_MS as i64));
+
+        let metadata = (class.is_some() || id.is_some()).then(|| SubtitleMetadata {
+            style: class.clone(),
+            identifier: id.clone(),
+            ..Default::default()
+        });
+        entries.push(SubtitleEntry {
+            index: entries.len() + 1,
+            start_time: start.clone(),
+            end_time: end,
+            text: text.clone(),
+            metadata,
+        });
+    }
+
+    if entries.is_empty() {
+        return Err("No valid subtitle entries found".to_string());
+    }
+
+    Ok(SubtitleFile {
+        line_ending,
+        diagnostics,
+        markup_headers: Some(MarkupHeaders {
+            prologue: content[..first].to_string(),
+            epilogue: content[body_end..].to_string(),
+            interleaved: Vec::new(),
+        }),
+        ..SubtitleFile::new(SubtitleFormat::Sami, entries)
+    })
+}
+
+/// Texto de um <P>: <br> vira quebra de linha, espaços do HTML são
+/// normalizados e entidades básicas decodificadas; demais tags são mantidas
+fn clean_text(raw: &str) -> String {
+    let text = CLOSING_TAG_REGEX.replace_all(raw, "");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    BR_REGEX
+        .split(&text)
+        .map(|line| line.trim())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+/// Serializa para SAMI
+/// Entradas com o mesmo início compartilham o <SYNC>; quando a próxima
+/// entrada da mesma classe não começa logo em seguida, um <P> com &nbsp;
+/// daquela classe marca o fim, no fim da última entrada dela no grupo
+pub fn serialize(file: &SubtitleFile) -> String {
+    let (prologue, epilogue, default_class) = match &file.markup_headers {
+        Some(headers) => (headers.prologue.as_str(), headers.epilogue.as_str(), None),
+        None => (DEFAULT_PROLOGUE, DEFAULT_EPILOGUE, Some(DEFAULT_CLASS)),
+    };
+
+    let mut output = String::from(prologue);
+    let entries = &file.entries;
+    // Marcadores de fim ainda não escritos: (instante, classe)
+    let mut pending_ends: Vec<(u64, Option<&str>)> = Vec::new();
+    let mut i = 0;
+    while i < entries.len() {
+        let start = &entries[i].start_time;
+        let group_len = entries[i..]
+            .iter()
+            .take_while(|e| e.start_time == *start)
+            .count();
+        let group = &entries[i..i + group_len];
+
+        write_end_markers(&mut output, &mut pending_ends, Some(start.as_millis()));
+        output.push_str(&format!("<SYNC Start={}>", start.as_millis()));
+        for entry in group {
+            output.push_str(&paragraph_tag(entry, default_class));
+            output.push_str(&text_to_markup(&entry.text));
+            output.push('\n');
+        }
+
+        let rest = &entries[i + group_len..];
+        for (j, entry) in group.iter().enumerate() {
+            let class = entry_class(entry, default_class);
+            if group[..j]
+                .iter()
+                .any(|e| entry_class(e, default_class) == class)
+            {
+                continue;
+            }
+            let end = group
+                .iter()
+                .filter(|e| entry_class(e, default_class) == class)
+                .map(|e| &e.end_time)
+                .max()
+                .unwrap_or(start);
+            let next_start = rest
+                .iter()
+                .find(|e| entry_class(e, default_class) == class)
+                .map(|e| &e.start_time);
+            if next_start.is_none_or(|next| next > end) {
+                pending_ends.push((end.as_millis(), class));
+            }
+        }
+
+        i += group_len;
+    }
+    write_end_markers(&mut output, &mut pending_ends, None);
+
+    output.push_str(epilogue);
+    output
+}
+
+/// Escreve os marcadores de fim até `until` (inclusive), um <SYNC> por
+/// instante com um <P> &nbsp; para cada classe que termina nele
+fn write_end_markers(
+    output: &mut String,
+    pending: &mut Vec<(u64, Option<&str>)>,
+    until: Option<u64>,
+) {
+    pending.sort();
+    pending.dedup();
+    let due = pending
+        .iter()
+        .take_while(|(at, _)| until.is_none_or(|until| *at <= until))
+        .count();
+    let mut last_sync = None;
+    for (at, class) in pending.drain(..due) {
+        if last_sync != Some(at) {
+            output.push_str(&format!("<SYNC Start={}>", at));
+            last_sync = Some(at);
+        }
+        // Marcador de fim sem ID: o ID identifica só o texto
+        let class = class.map(|c| format!(" Class={}", c)).unwrap_or_default();
+        output.push_str(&format!("<P{}>&nbsp;\n", class));
+    }
+}
+
+fn paragraph_tag(entry: &SubtitleEntry, default_class: Option<&str>) -> String {
+    let mut tag = String::from("<P");
+    if let Some(class) = entry_class(entry, default_class) {
+        tag.push_str(&format!(" Class={}", class));
+    }
+    if let Some(id) = entry.metadata.as_ref().and_then(|m| m.identifier.as_ref()) {
+        tag.push_str(&format!(" ID={}", id));
+    }
+    tag.push('>');
+    tag
+}
+
+fn entry_class<'a>(entry: &'a SubtitleEntry, default_class: Option<&'a str>) -> Option<&'a str> {
+    entry
+        .metadata
+        .as_ref()
+        .and_then(|m| m.style.as_deref())
+        .or(default_class)
+}
+
+/// Texto da entrada como marcação SAMI: tags de formatação passam como
+/// estão e o resto tem &, < e > escapados
+fn text_to_markup(text: &str) -> String {
+    let escape = |segment: &str| {
+        segment
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('\n', "<br>")
+    };
+
+    let mut output = String::new();
+    let mut last = 0;
+    for tag in FORMATTING_TAG_REGEX.find_iter(text) {
+        output.push_str(&escape(&text[last..tag.start()]));
+        output.push_str(tag.as_str());
+        last = tag.end();
+    }
+    output.push_str(&escape(&text[last..]));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<SAMI>
+<HEAD>
+<TITLE>Episode 1</TITLE>
+<STYLE TYPE="text/css">
+<!--
+P { font-family: Arial; }
+.ENUSCC { Name: English; lang: en-US; }
+-->
+</STYLE>
+</HEAD>
+<BODY>
+<SYNC Start=1000><P Class=ENUSCC ID=Source>Hello<br>World &amp; all
+<SYNC Start=3500><P Class=ENUSCC>&nbsp;
+<SYNC Start=4000><P Class=ENUSCC><i>Second</i> line
+<SYNC Start=6000><P Class=ENUSCC>&nbsp;
+</BODY>
+</SAMI>
+"#;
+
+    #[test]
+    fn test_parse_sami_round_trip() {
+        let result = parse(SAMPLE).unwrap();
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.entries[0].text, "Hello\nWorld & all");
+        assert_eq!(result.entries[0].end_time.as_millis(), 3500);
+        assert_eq!(result.entries[1].text, "<i>Second</i> line");
+
+        let metadata = result.entries[0].metadata.as_ref().unwrap();
+        assert_eq!(metadata.style.as_deref(), Some("ENUSCC"));
+        assert_eq!(metadata.identifier.as_deref(), Some("Source"));
+
+        assert_eq!(serialize(&result), SAMPLE);
+    }
+
+    #[test]
+    fn test_markup_characters_round_trip() {
+        let mut file = parse(SAMPLE).unwrap();
+        file.entries[1].text = "<i>5 < 6</i> & <b>7 > 4</b>\n<SYNC> is a tag".to_string();
+
+        let output = serialize(&file);
+        assert!(output.contains("<i>5 &lt; 6</i> &amp; <b>7 &gt; 4</b><br>&lt;SYNC&gt; is a tag"));
+
+        let result = parse(&output).unwrap();
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.entries[1].text, file.entries[1].text);
+    }
+
+    #[test]
+    fn test_multi_language_end_markers() {
+        let content = "<SAMI><BODY>\n<SYNC Start=1000><P Class=ENCC>Hello\n<P Class=KRCC>Annyeong\n<SYNC Start=2000><P Class=ENCC>&nbsp;\n<SYNC Start=3000><P Class=KRCC>&nbsp;\n</BODY></SAMI>";
+        let result = parse(content).unwrap();
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.entries[0].end_time.as_millis(), 2000);
+        assert_eq!(result.entries[1].end_time.as_millis(), 3000);
+        assert_eq!(serialize(&result), content);
+
+        let mut file = result;
+        file.entries[1].end_time = Timestamp::from_millis(2000);
+        assert!(serialize(&file)
+            .contains("<SYNC Start=2000><P Class=ENCC>&nbsp;\n<P Class=KRCC>&nbsp;\n</BODY>"));
+    }
+
+    #[test]
+    fn test_parse_sami_without_end_markers() {
+        let content = "<SAMI><BODY>\n<SYNC Start=1000><P>First\n<SYNC Start=2000><P>Second\n<SYNC>Broken\n</BODY></SAMI>";
+        let result = parse(content).unwrap();
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.entries[0].end_time.as_millis(), 2000);
+        assert_eq!(result.entries[1].end_time.as_millis(), 7000);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].line, 4);
+    }
+}
@@ -0,0 +1,164 @@
+use super::{LineEnding, ParseDiagnostic, SubtitleEntry, SubtitleFile, SubtitleFormat, Timestamp};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+// Linha de tempo SBV: "0:00:01.000,0:00:04.000"
+static TIMING_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(\d+:\d{2}:\d{2}\.\d{1,3})\s*,\s*(\d+:\d{2}:\d{2}\.\d{1,3})\s*$").unwrap()
+});
+
+/// Resultado do parse de formatos com "início,fim" seguido do texto
+pub(super) struct CommaTimedBlocks {
+    /// Linhas antes do primeiro cue
+    pub preamble: Vec<String>,
+    pub entries: Vec<SubtitleEntry>,
+    pub diagnostics: Vec<ParseDiagnostic>,
+}
+
+/// Faz parse de blocos "início,fim\ntexto" separados por linhas em branco
+/// (SBV e SubViewer); `timing` captura início e fim
+pub(super) fn parse_comma_timed_blocks(content: &str, timing: &Regex) -> CommaTimedBlocks {
+    let mut result = CommaTimedBlocks {
+        preamble: Vec::new(),
+        entries: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    // Cue em leitura: (início, fim, linhas de texto)
+    let mut current: Option<(Timestamp, Timestamp, Vec<&str>)> = None;
+    // Texto de um cue com tempo inválido (vai para o diagnóstico)
+    let mut skipping = false;
+
+    for (i, line) in content.split('\n').enumerate() {
+        let line = line.trim_end();
+
+        if let Some(caps) = timing.captures(line) {
+            finish_cue(&mut current, &mut result.entries);
+            skipping = false;
+            match (Timestamp::parse(&caps[1]), Timestamp::parse(&caps[2])) {
+                (Ok(start), Ok(end)) => current = Some((start, end, Vec::new())),
+                (Err(e), _) | (_, Err(e)) => {
+                    result
+                        .diagnostics
+                        .push(ParseDiagnostic::new(i + 1, e, line));
+                    skipping = true;
+                }
+            }
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            finish_cue(&mut current, &mut result.entries);
+            skipping = false;
+            continue;
+        }
+
+        if let Some((_, _, lines)) = current.as_mut() {
+            lines.push(line);
+        } else if skipping {
+            if let Some(diagnostic) = result.diagnostics.last_mut() {
+                diagnostic.raw = format!("{}\n{}", diagnostic.raw, line);
+            }
+        } else if result.entries.is_empty() && result.diagnostics.is_empty() {
+            result.preamble.push(line.to_string());
+        } else {
+            result
+                .diagnostics
+                .push(ParseDiagnostic::new(i + 1, "Text outside of a cue", line));
+        }
+    }
+    finish_cue(&mut current, &mut result.entries);
+
+    result
+}
+
+fn finish_cue(
+    current: &mut Option<(Timestamp, Timestamp, Vec<&str>)>,
+    entries: &mut Vec<SubtitleEntry>,
+) {
+    if let Some((start_time, end_time, lines)) = current.take() {
+        entries.push(SubtitleEntry {
+            index: entries.len() + 1,
+            start_time,
+            end_time,
+            text: lines.join("\n"),
+            metadata: None,
+        });
+    }
+}
+
+/// Faz parse de um arquivo SBV (legendas do YouTube)
+pub fn parse(content: &str) -> Result<SubtitleFile, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let line_ending = LineEnding::detect(content);
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+
+    let mut parsed = parse_comma_timed_blocks(&content, &TIMING_REGEX);
+
+    if parsed.entries.is_empty() {
+        return Err("No valid subtitle entries found".to_string());
+    }
+
+    // SBV não tem cabeçalho: texto antes do primeiro cue é descartado
+    if !parsed.preamble.is_empty() {
+        let text = parsed.preamble.join("\n");
+        parsed
+            .diagnostics
+            .insert(0, ParseDiagnostic::new(1, "Text outside of a cue", text));
+    }
+
+    Ok(SubtitleFile {
+        line_ending,
+        diagnostics: parsed.diagnostics,
+        ..SubtitleFile::new(SubtitleFormat::Sbv, parsed.entries)
+    })
+}
+
+/// Serializa para formato SBV
+pub fn serialize(file: &SubtitleFile) -> String {
+    let mut output = String::new();
+
+    for entry in &file.entries {
+        output.push_str(&format!(
+            "{},{}\n",
+            entry.start_time.to_string_for(&SubtitleFormat::Sbv),
+            entry.end_time.to_string_for(&SubtitleFormat::Sbv)
+        ));
+        output.push_str(&entry.text);
+        output.push_str("\n\n");
+    }
+
+    output.trim_end().to_string() + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str =
+        "0:00:01.000,0:00:04.000\nHello\nWorld\n\n0:00:05.500,0:00:08.000\nSecond\n";
+
+    #[test]
+    fn test_parse_sbv_round_trip() {
+        let result = parse(SAMPLE).unwrap();
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.entries[0].text, "Hello\nWorld");
+        assert_eq!(result.entries[1].start_time.as_millis(), 5500);
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(serialize(&result), SAMPLE);
+    }
+
+    #[test]
+    fn test_parse_sbv_reports_invalid_timing() {
+        let result =
+            parse("0:00:01.000,0:00:02.000\nOk\n\n0:75:00.000,0:76:00.000\nBad\n").unwrap();
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(
+            result.diagnostics,
+            vec![ParseDiagnostic::new(
+                4,
+                "Invalid timestamp: 0:75:00.000",
+                "0:75:00.000,0:76:00.000\nBad"
+            )]
+        );
+    }
+}
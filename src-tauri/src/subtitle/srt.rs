@@ -1,63 +1,88 @@
-use super::{SubtitleEntry, SubtitleFile, SubtitleFormat};
+use super::{
+    LineEnding, ParseDiagnostic, SubtitleEntry, SubtitleFile, SubtitleFormat, SubtitleMetadata,
+    Timestamp,
+};
+use once_cell::sync::Lazy;
 use regex::Regex;
 
+// Linha de tempo tolerante: horas com 1+ dígitos, ponto ou vírgula nos
+// milissegundos, "-->" com ou sem espaços e coordenadas opcionais (X1: Y1:)
+// Os dois lados precisam ter forma de tempo (com ":"); setas no diálogo,
+// como "Go --> there" ou "3->1", continuam sendo texto
+static TIMING_LINE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*(\d+(?::\d+){1,2}(?:[.,]\d+)?)\s*-{1,2}>\s*(\d+(?::\d+){1,2}(?:[.,]\d+)?)((?:\s+[A-Za-z]\d*:\S*)*)\s*$",
+    )
+    .unwrap()
+});
+
+/// Dono das linhas lidas desde a última linha de tempo
+enum Owner {
+    /// Antes do primeiro cue
+    Preamble,
+    /// Cue válido aguardando o texto
+    Cue(Box<SubtitleEntry>),
+    /// Cue com linha de tempo inválida (será descartado)
+    Invalid(ParseDiagnostic),
+}
+
 /// Faz parse de um arquivo SRT
+/// Leitor por estados tolerante a arquivos mal formados: linhas separadoras
+/// com espaços, índices ausentes ou inválidos, milissegundos com ponto,
+/// "-->" sem espaços, horas com 1 dígito e linhas em branco dentro do texto.
+/// Cada linha de tempo inicia um cue; o índice é a linha imediatamente
+/// anterior e o texto vai até o índice/linha de tempo seguinte.
 pub fn parse(content: &str) -> Result<SubtitleFile, String> {
-    let mut entries = Vec::new();
+    let content = content.trim_start_matches('\u{feff}');
+    let line_ending = LineEnding::detect(content);
 
     // Normaliza line endings
     let content = content.replace("\r\n", "\n").replace('\r', "\n");
 
-    // Regex para timestamps SRT: 00:00:00,000 --> 00:00:00,000
-    let timestamp_re = Regex::new(r"(\d{2}:\d{2}:\d{2},\d{3})\s*-->\s*(\d{2}:\d{2}:\d{2},\d{3})")
-        .map_err(|e| e.to_string())?;
-
-    // Divide por blocos (separados por linha em branco)
-    let blocks: Vec<&str> = content.split("\n\n").collect();
-
-    for block in blocks {
-        let block = block.trim();
-        if block.is_empty() {
-            continue;
-        }
+    let mut entries = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut owner = Owner::Preamble;
+    // Linhas (número 1-indexed, conteúdo) desde a última linha de tempo
+    let mut pending: Vec<(usize, &str)> = Vec::new();
 
-        let lines: Vec<&str> = block.lines().collect();
-        if lines.len() < 2 {
+    for (i, line) in content.split('\n').enumerate() {
+        let line_number = i + 1;
+        if !TIMING_LINE_REGEX.is_match(line) {
+            pending.push((line_number, line.trim_end()));
             continue;
         }
 
-        // Primeira linha: índice
-        let index: usize = match lines[0].trim().parse() {
-            Ok(i) => i,
-            Err(_) => continue, // Pula blocos inválidos
-        };
-
-        // Segunda linha: timestamps
-        let timestamp_line = lines[1].trim();
-        let caps = match timestamp_re.captures(timestamp_line) {
-            Some(c) => c,
-            None => continue,
-        };
+        let index_line = take_index_line(&mut pending, &mut diagnostics);
+        finish(owner, &pending, &mut entries, &mut diagnostics);
+        pending.clear();
 
-        let start_time = caps[1].to_string();
-        let end_time = caps[2].to_string();
+        let index = index_line
+            .and_then(|(_, l)| l.trim().parse().ok())
+            .unwrap_or_else(|| entries.last().map_or(1, |e: &SubtitleEntry| e.index + 1));
 
-        // Resto: texto (pode ter múltiplas linhas)
-        let text = if lines.len() > 2 {
-            lines[2..].join("\n")
-        } else {
-            String::new()
+        owner = match parse_timing_line(line) {
+            Ok((start_time, end_time, coordinates)) => Owner::Cue(Box::new(SubtitleEntry {
+                index,
+                start_time,
+                end_time,
+                text: String::new(),
+                metadata: coordinates.map(|c| SubtitleMetadata {
+                    cue_settings: Some(c),
+                    ..Default::default()
+                }),
+            })),
+            Err(reason) => {
+                let raw = match index_line {
+                    Some((_, l)) => format!("{}\n{}", l.trim(), line.trim()),
+                    None => line.trim().to_string(),
+                };
+                Owner::Invalid(ParseDiagnostic::new(line_number, reason, raw))
+            }
         };
-
-        entries.push(SubtitleEntry {
-            index,
-            start_time,
-            end_time,
-            text,
-            metadata: None,
-        });
     }
 
+    finish(owner, &pending, &mut entries, &mut diagnostics);
+
     if entries.is_empty() {
         return Err("No valid subtitle entries found".to_string());
     }
@@ -66,16 +91,126 @@ pub fn parse(content: &str) -> Result<SubtitleFile, String> {
         format: SubtitleFormat::Srt,
         entries,
         headers: None,
+        vtt_headers: None,
+        encoding: None,
+        bom: false,
+        line_ending,
+        diagnostics,
+        preamble: Vec::new(),
+        frame_rate: None,
+        markup_headers: None,
     })
 }
 
+/// Retira de `pending` a linha de índice que precede uma linha de tempo
+/// Índice numérico é sempre aceito; uma linha não numérica logo após uma
+/// linha em branco ocupa a posição do índice e é descartada com diagnóstico
+fn take_index_line<'a>(
+    pending: &mut Vec<(usize, &'a str)>,
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) -> Option<(usize, &'a str)> {
+    let &(line_number, line) = pending.last()?;
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    if trimmed.bytes().all(|b| b.is_ascii_digit()) {
+        return pending.pop();
+    }
+
+    let after_blank = pending.len() < 2 || pending[pending.len() - 2].1.trim().is_empty();
+    if after_blank {
+        pending.pop();
+        diagnostics.push(ParseDiagnostic::new(
+            line_number,
+            format!("Invalid index: {}", trimmed),
+            trimmed,
+        ));
+    }
+    None
+}
+
+/// Entrega as linhas acumuladas ao dono atual
+fn finish(
+    owner: Owner,
+    pending: &[(usize, &str)],
+    entries: &mut Vec<SubtitleEntry>,
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) {
+    // Remove linhas em branco (ou só com espaços) nas pontas
+    let first = pending.iter().position(|(_, l)| !l.trim().is_empty());
+    let last = pending.iter().rposition(|(_, l)| !l.trim().is_empty());
+    let body = match (first, last) {
+        (Some(first), Some(last)) => &pending[first..=last],
+        _ => &[],
+    };
+    let text = body.iter().map(|(_, l)| *l).collect::<Vec<_>>().join("\n");
+
+    match owner {
+        Owner::Preamble => {
+            if let Some(&(line_number, _)) = body.first() {
+                diagnostics.push(ParseDiagnostic::new(
+                    line_number,
+                    "Text outside of a cue",
+                    text,
+                ));
+            }
+        }
+        Owner::Cue(mut entry) => {
+            entry.text = text;
+            entries.push(*entry);
+        }
+        Owner::Invalid(mut diagnostic) => {
+            if !text.is_empty() {
+                diagnostic.raw = format!("{}\n{}", diagnostic.raw, text);
+            }
+            diagnostics.push(diagnostic);
+        }
+    }
+}
+
+/// Faz parse de "início --> fim [X1:... X2:... Y1:... Y2:...]"
+fn parse_timing_line(line: &str) -> Result<(Timestamp, Timestamp, Option<String>), String> {
+    let caps = TIMING_LINE_REGEX
+        .captures(line)
+        .ok_or_else(|| format!("Invalid timestamp line: {}", line.trim()))?;
+
+    let start_time = Timestamp::parse(&caps[1])?;
+    let end_time = Timestamp::parse(&caps[2])?;
+    let coordinates = caps[3].trim();
+
+    Ok((
+        start_time,
+        end_time,
+        if coordinates.is_empty() {
+            None
+        } else {
+            Some(coordinates.to_string())
+        },
+    ))
+}
+
 /// Serializa para formato SRT
 pub fn serialize(file: &SubtitleFile) -> String {
     let mut output = String::new();
 
     for entry in &file.entries {
         output.push_str(&format!("{}\n", entry.index));
-        output.push_str(&format!("{} --> {}\n", entry.start_time, entry.end_time));
+        output.push_str(&format!(
+            "{} --> {}",
+            entry.start_time.to_string_for(&SubtitleFormat::Srt),
+            entry.end_time.to_string_for(&SubtitleFormat::Srt)
+        ));
+        if let Some(coordinates) = entry
+            .metadata
+            .as_ref()
+            .and_then(|m| m.cue_settings.as_ref())
+        {
+            output.push(' ');
+            output.push_str(coordinates);
+        }
+        output.push('\n');
         output.push_str(&entry.text);
         output.push_str("\n\n");
     }
@@ -108,18 +243,134 @@ with multiple lines
         );
     }
 
+    /// Arquivos reais mal formados: (nome, conteúdo, textos esperados)
+    const BROKEN_CORPUS: [(&str, &str, &[&str]); 6] = [
+        (
+            "whitespace_separators.srt",
+            include_str!("../../tests/fixtures/srt/whitespace_separators.srt"),
+            &["First", "Second", "Third"],
+        ),
+        (
+            "missing_indices.srt",
+            include_str!("../../tests/fixtures/srt/missing_indices.srt"),
+            &[
+                "No index here",
+                "Still none",
+                "Back on track",
+                "After seven",
+            ],
+        ),
+        (
+            "dot_milliseconds.srt",
+            include_str!("../../tests/fixtures/srt/dot_milliseconds.srt"),
+            &[
+                "Dots and short hours",
+                "Dots everywhere",
+                "Single dash arrow",
+            ],
+        ),
+        (
+            "blank_lines_in_text.srt",
+            include_str!("../../tests/fixtures/srt/blank_lines_in_text.srt"),
+            &["First paragraph\n\nSecond paragraph", "Next cue"],
+        ),
+        (
+            "coordinates.srt",
+            include_str!("../../tests/fixtures/srt/coordinates.srt"),
+            &["Positioned", "Normal"],
+        ),
+        (
+            "arrows_in_text.srt",
+            include_str!("../../tests/fixtures/srt/arrows_in_text.srt"),
+            &[
+                "Go --> there",
+                "It's 3->1 for us\n10 --> 12",
+                "<-- back -->",
+            ],
+        ),
+    ];
+
+    #[test]
+    fn test_broken_corpus_recovers_every_cue() {
+        for (name, content, expected) in BROKEN_CORPUS {
+            let result = parse(content).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            let texts: Vec<&str> = result.entries.iter().map(|e| e.text.as_str()).collect();
+            assert_eq!(texts, expected, "{}", name);
+            assert!(result.diagnostics.is_empty(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_recovered_indices_and_timestamps() {
+        let result = parse(BROKEN_CORPUS[1].1).unwrap();
+        let indices: Vec<usize> = result.entries.iter().map(|e| e.index).collect();
+        assert_eq!(indices, vec![1, 2, 7, 8]);
+
+        let result = parse(BROKEN_CORPUS[2].1).unwrap();
+        assert_eq!(result.entries[0].start_time.as_millis(), 1500);
+        assert_eq!(result.entries[0].end_time.as_millis(), 2250);
+        assert_eq!(
+            result.entries[2].start_time.as_millis(),
+            ((60 + 2) * 60 + 3) * 1000 + 4
+        );
+        assert!(serialize(&result).starts_with("1\n00:00:01,500 --> 00:00:02,250\n"));
+    }
+
+    #[test]
+    fn test_coordinates_round_trip() {
+        let content = BROKEN_CORPUS[4].1;
+        let result = parse(content).unwrap();
+        let meta = result.entries[0].metadata.as_ref().unwrap();
+        assert_eq!(
+            meta.cue_settings.as_deref(),
+            Some("X1:100 X2:600 Y1:50 Y2:90")
+        );
+        assert_eq!(serialize(&result), content);
+    }
+
+    #[test]
+    fn test_parse_reports_skipped_blocks() {
+        let content = include_str!("../../tests/fixtures/srt/garbage.srt");
+        let result = parse(content).unwrap();
+        let texts: Vec<&str> = result.entries.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, vec!["Good", "Bad index", "Last"]);
+        assert_eq!(
+            result.diagnostics,
+            vec![
+                ParseDiagnostic::new(1, "Text outside of a cue", "Subtitles ripped by someone"),
+                ParseDiagnostic::new(8, "Invalid index: x", "x"),
+                ParseDiagnostic::new(
+                    13,
+                    "Invalid timestamp: 00:61:05,000",
+                    "3\n00:61:05,000 --> 00:61:06,000\nBad time"
+                ),
+            ]
+        );
+
+        let strict = SubtitleFile::parse_strict(content, SubtitleFormat::Srt);
+        assert!(strict.unwrap_err().contains("Line 8: Invalid index: x"));
+    }
+
     #[test]
     fn test_serialize_srt() {
         let file = SubtitleFile {
             format: SubtitleFormat::Srt,
             entries: vec![SubtitleEntry {
                 index: 1,
-                start_time: "00:00:01,000".to_string(),
-                end_time: "00:00:04,000".to_string(),
+                start_time: Timestamp::parse("00:00:01,000").unwrap(),
+                end_time: Timestamp::parse("00:00:04,000").unwrap(),
                 text: "Hello".to_string(),
                 metadata: None,
             }],
             headers: None,
+            vtt_headers: None,
+            encoding: None,
+            bom: false,
+            line_ending: LineEnding::Lf,
+            diagnostics: Vec::new(),
+            preamble: Vec::new(),
+            frame_rate: None,
+            markup_headers: None,
         };
 
         let output = serialize(&file);
@@ -0,0 +1,351 @@
+use super::{ass, AssHeaders, AssSection, SubtitleFile, SubtitleFormat};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Campos de [V4 Styles] na ordem do SSA v4
+const SSA_STYLE_FORMAT: [&str; 18] = [
+    "Name",
+    "Fontname",
+    "Fontsize",
+    "PrimaryColour",
+    "SecondaryColour",
+    "TertiaryColour",
+    "BackColour",
+    "Bold",
+    "Italic",
+    "BorderStyle",
+    "Outline",
+    "Shadow",
+    "Alignment",
+    "MarginL",
+    "MarginR",
+    "MarginV",
+    "AlphaLevel",
+    "Encoding",
+];
+
+/// Campos de [V4+ Styles] quando o arquivo ASS não declara "Format:"
+const ASS_STYLE_FORMAT: [&str; 23] = [
+    "Name",
+    "Fontname",
+    "Fontsize",
+    "PrimaryColour",
+    "SecondaryColour",
+    "OutlineColour",
+    "BackColour",
+    "Bold",
+    "Italic",
+    "Underline",
+    "StrikeOut",
+    "ScaleX",
+    "ScaleY",
+    "Spacing",
+    "Angle",
+    "BorderStyle",
+    "Outline",
+    "Shadow",
+    "Alignment",
+    "MarginL",
+    "MarginR",
+    "MarginV",
+    "Encoding",
+];
+
+/// Campos de [Events] na ordem do SSA v4 (Marked no lugar de Layer)
+const SSA_EVENTS_FORMAT: [&str; 10] = [
+    "Marked", "Start", "End", "Style", "Name", "MarginL", "MarginR", "MarginV", "Effect", "Text",
+];
+
+// Regex para a tag de alinhamento numpad do ASS (\an1 a \an9)
+static AN_TAG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\\an([1-9])").unwrap());
+
+/// Faz parse de um arquivo SSA v4
+/// Usa o mesmo modelo sem perdas do ASS; o formato só é marcado como SSA
+/// quando o conteúdo é realmente v4.00 (um .ssa com conteúdo ASS continua ASS)
+pub fn parse(content: &str) -> Result<SubtitleFile, String> {
+    let mut file = ass::parse(content)?;
+    if file.headers.as_ref().is_some_and(is_ssa_script) {
+        file.format = SubtitleFormat::Ssa;
+    }
+    Ok(file)
+}
+
+/// Verifica se os headers pertencem a um script SSA v4 (e não ASS v4+)
+pub fn is_ssa_script(headers: &AssHeaders) -> bool {
+    if headers.section("V4+ Styles").is_some() {
+        return false;
+    }
+    if headers.section("V4 Styles").is_some() {
+        return true;
+    }
+    script_type(headers).is_some_and(|t| t.eq_ignore_ascii_case("v4.00"))
+}
+
+fn script_type(headers: &AssHeaders) -> Option<&str> {
+    headers
+        .section("Script Info")?
+        .lines
+        .iter()
+        .find_map(|l| l.trim().strip_prefix("ScriptType:"))
+        .map(str::trim)
+}
+
+/// Serializa para formato SSA v4
+/// Headers vindos de um SSA são escritos como estão; headers ASS (ou ausentes)
+/// são convertidos: [V4 Styles], cores decimais BGR, alinhamento legado e Marked
+pub fn serialize(file: &SubtitleFile) -> String {
+    let mut file = file.clone();
+
+    let headers = file.headers.take().unwrap_or_default();
+    file.headers = Some(if is_ssa_script(&headers) {
+        headers
+    } else {
+        convert_headers(&headers)
+    });
+
+    for entry in &mut file.entries {
+        entry.text = convert_alignment_tags(&entry.text);
+    }
+
+    ass::serialize(&file)
+}
+
+/// Converte headers ASS v4+ para SSA v4
+fn convert_headers(headers: &AssHeaders) -> AssHeaders {
+    let mut converted = AssHeaders {
+        sections: Vec::new(),
+        events_format: SSA_EVENTS_FORMAT.iter().map(|f| f.to_string()).collect(),
+        pad_margins: headers.pad_margins,
+    };
+
+    if headers.section("Script Info").is_none() {
+        converted.sections.push(AssSection {
+            lines: vec![
+                "[Script Info]".to_string(),
+                "ScriptType: v4.00".to_string(),
+                String::new(),
+            ],
+            ..Default::default()
+        });
+    }
+
+    for section in &headers.sections {
+        let name = section.name();
+        let section = if name.eq_ignore_ascii_case("Script Info") {
+            convert_script_info(section)
+        } else if name.eq_ignore_ascii_case("V4+ Styles") {
+            convert_styles(section)
+        } else if section.is_events() {
+            convert_events_section(section)
+        } else {
+            section.clone()
+        };
+        converted.sections.push(section);
+    }
+
+    converted
+}
+
+fn convert_script_info(section: &AssSection) -> AssSection {
+    let mut lines: Vec<String> = section
+        .lines
+        .iter()
+        .map(|l| {
+            if l.trim().starts_with("ScriptType:") {
+                "ScriptType: v4.00".to_string()
+            } else {
+                l.clone()
+            }
+        })
+        .collect();
+
+    if !lines.iter().any(|l| l.trim().starts_with("ScriptType:")) {
+        lines.insert(1.min(lines.len()), "ScriptType: v4.00".to_string());
+    }
+
+    AssSection {
+        lines,
+        trailing: section.trailing.clone(),
+        interleaved: section.interleaved.clone(),
+    }
+}
+
+fn convert_events_section(section: &AssSection) -> AssSection {
+    let format_line = format!("Format: {}", SSA_EVENTS_FORMAT.join(", "));
+    let mut lines: Vec<String> = section
+        .lines
+        .iter()
+        .map(|l| {
+            if l.trim().starts_with("Format:") {
+                format_line.clone()
+            } else {
+                l.clone()
+            }
+        })
+        .collect();
+
+    if !lines.iter().any(|l| l.trim().starts_with("Format:")) {
+        lines.insert(1.min(lines.len()), format_line);
+    }
+
+    AssSection {
+        lines,
+        trailing: section.trailing.clone(),
+        interleaved: section.interleaved.clone(),
+    }
+}
+
+/// Converte [V4+ Styles] em [V4 Styles], reordenando os campos de cada Style
+fn convert_styles(section: &AssSection) -> AssSection {
+    let mut ass_format: Vec<String> = ASS_STYLE_FORMAT.iter().map(|f| f.to_lowercase()).collect();
+    let mut lines = Vec::with_capacity(section.lines.len());
+
+    for (i, line) in section.lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if i == 0 {
+            lines.push("[V4 Styles]".to_string());
+        } else if let Some(format_part) = trimmed.strip_prefix("Format:") {
+            ass_format = format_part
+                .split(',')
+                .map(|s| s.trim().to_lowercase())
+                .collect();
+            lines.push(format!("Format: {}", SSA_STYLE_FORMAT.join(", ")));
+        } else if let Some(style_part) = trimmed.strip_prefix("Style:") {
+            lines.push(format!(
+                "Style: {}",
+                convert_style_line(style_part, &ass_format)
+            ));
+        } else {
+            lines.push(line.clone());
+        }
+    }
+
+    AssSection {
+        lines,
+        trailing: section.trailing.clone(),
+        interleaved: section.interleaved.clone(),
+    }
+}
+
+fn convert_style_line(style_part: &str, ass_format: &[String]) -> String {
+    let values: HashMap<&str, &str> = ass_format
+        .iter()
+        .map(String::as_str)
+        .zip(style_part.split(',').map(str::trim))
+        .collect();
+
+    let get = |field: &str| values.get(field).copied().unwrap_or("0");
+
+    SSA_STYLE_FORMAT
+        .iter()
+        .map(|field| {
+            let key = field.to_lowercase();
+            match key.as_str() {
+                "primarycolour" | "secondarycolour" | "backcolour" => {
+                    ass_colour_to_ssa(get(&key)).0
+                }
+                "tertiarycolour" => ass_colour_to_ssa(get("outlinecolour")).0,
+                "alphalevel" => ass_colour_to_ssa(get("primarycolour")).1.to_string(),
+                "alignment" => get("alignment")
+                    .parse::<u8>()
+                    .map(|a| numpad_to_ssa_alignment(a).to_string())
+                    .unwrap_or_else(|_| get("alignment").to_string()),
+                other => get(other).to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Converte uma cor ASS (&HAABBGGRR) para o decimal BGR do SSA, retornando também o alpha
+/// Valores que não estão em hexadecimal são devolvidos como estão
+fn ass_colour_to_ssa(colour: &str) -> (String, u32) {
+    let hex = colour
+        .trim()
+        .trim_start_matches("&H")
+        .trim_start_matches("&h")
+        .trim_end_matches('&');
+
+    if hex.len() == colour.trim().len() {
+        return (colour.trim().to_string(), 0);
+    }
+
+    match u32::from_str_radix(hex, 16) {
+        Ok(value) => ((value & 0x00FF_FFFF).to_string(), value >> 24),
+        Err(_) => (colour.trim().to_string(), 0),
+    }
+}
+
+/// Converte alinhamento numpad (ASS) para o esquema legado do SSA
+/// 1-3 embaixo, 5-7 em cima (+4), 9-11 no meio (+8)
+fn numpad_to_ssa_alignment(alignment: u8) -> u8 {
+    match alignment {
+        4..=6 => alignment + 5,
+        7..=9 => alignment - 2,
+        other => other,
+    }
+}
+
+/// Troca tags \anN do ASS por \aN equivalentes do SSA
+fn convert_alignment_tags(text: &str) -> String {
+    AN_TAG_REGEX
+        .replace_all(text, |caps: &regex::Captures| {
+            let alignment: u8 = caps[1].parse().unwrap_or(2);
+            format!("\\a{}", numpad_to_ssa_alignment(alignment))
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SSA_FIXTURE: &str = include_str!("../../tests/fixtures/ass/ssa_v4.ssa");
+
+    #[test]
+    fn test_parse_ssa_keeps_format_and_round_trips() {
+        let file = parse(SSA_FIXTURE).unwrap();
+        assert_eq!(file.format, SubtitleFormat::Ssa);
+        assert_eq!(serialize(&file), SSA_FIXTURE);
+    }
+
+    #[test]
+    fn test_ass_content_stays_ass() {
+        let content = "[Script Info]\nScriptType: v4.00+\n\n[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nDialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Hi\n";
+        assert_eq!(parse(content).unwrap().format, SubtitleFormat::Ass);
+    }
+
+    #[test]
+    fn test_serialize_ass_as_ssa() {
+        let content = r#"[Script Info]
+ScriptType: v4.00+
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Top,Arial,20,&H80FFFFFF,&H000000FF,&H00102030,&H00000000,-1,0,0,0,100,100,0,0,1,2,0,8,10,10,10,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:01.00,0:00:04.00,Top,,0,0,0,,{\an7}Hello
+"#;
+        let mut file = ass::parse(content).unwrap();
+        file.format = SubtitleFormat::Ssa;
+
+        let output = serialize(&file);
+        assert!(output.contains("ScriptType: v4.00\n"));
+        assert!(output.contains("[V4 Styles]\n"));
+        assert!(output.contains(&format!("Format: {}\n", SSA_STYLE_FORMAT.join(", "))));
+        assert!(output
+            .contains("Style: Top,Arial,20,16777215,255,1056816,0,-1,0,1,2,0,6,10,10,10,128,1\n"));
+        assert!(output.contains(&format!("Format: {}\n", SSA_EVENTS_FORMAT.join(", "))));
+        assert!(output.contains("Dialogue: Marked=0,0:00:01.00,0:00:04.00,Top,,0,0,0,,{\\a5}Hello"));
+        assert!(!output.contains("V4+"));
+    }
+
+    #[test]
+    fn test_alignment_conversion() {
+        let converted: Vec<u8> = (1..=9).map(numpad_to_ssa_alignment).collect();
+        assert_eq!(converted, vec![1, 2, 3, 9, 10, 11, 5, 6, 7]);
+    }
+}
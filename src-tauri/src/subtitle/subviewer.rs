@@ -0,0 +1,107 @@
+use super::sbv::parse_comma_timed_blocks;
+use super::{LineEnding, SubtitleFile, SubtitleFormat};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Cabeçalho escrito quando o arquivo não veio de um SubViewer
+const DEFAULT_PREAMBLE: [&str; 3] = ["[INFORMATION]", "[END INFORMATION]", "[SUBTITLE]"];
+
+// Linha de tempo SubViewer 2.0: "00:00:01.00,00:00:04.00"
+static TIMING_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(\d{1,2}:\d{2}:\d{2}\.\d{2,3})\s*,\s*(\d{1,2}:\d{2}:\d{2}\.\d{2,3})\s*$")
+        .unwrap()
+});
+
+/// Faz parse de um arquivo SubViewer 2.0 (.sub)
+/// O bloco [INFORMATION] e as linhas de estilo ([COLF], [STYLE]...) antes
+/// do primeiro cue são guardados em `preamble`; "[br]" separa linhas do texto
+pub fn parse(content: &str) -> Result<SubtitleFile, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let line_ending = LineEnding::detect(content);
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+
+    let mut parsed = parse_comma_timed_blocks(&content, &TIMING_REGEX);
+
+    if parsed.entries.is_empty() {
+        return Err("No valid subtitle entries found".to_string());
+    }
+
+    for entry in &mut parsed.entries {
+        entry.text = entry.text.replace("[br]", "\n").replace("[BR]", "\n");
+    }
+
+    Ok(SubtitleFile {
+        line_ending,
+        diagnostics: parsed.diagnostics,
+        preamble: parsed.preamble,
+        ..SubtitleFile::new(SubtitleFormat::SubViewer, parsed.entries)
+    })
+}
+
+/// Serializa para formato SubViewer 2.0
+pub fn serialize(file: &SubtitleFile) -> String {
+    let mut output = String::new();
+
+    if file.preamble.is_empty() {
+        for line in DEFAULT_PREAMBLE {
+            output.push_str(line);
+            output.push('\n');
+        }
+    } else {
+        for line in &file.preamble {
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+
+    for entry in &file.entries {
+        output.push_str(&format!(
+            "{},{}\n",
+            entry.start_time.to_string_for(&SubtitleFormat::SubViewer),
+            entry.end_time.to_string_for(&SubtitleFormat::SubViewer)
+        ));
+        output.push_str(&entry.text.replace('\n', "[br]"));
+        output.push_str("\n\n");
+    }
+
+    output.trim_end().to_string() + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"[INFORMATION]
+[TITLE]Episode 1
+[AUTHOR]
+[END INFORMATION]
+[SUBTITLE]
+[COLF]&HFFFFFF,[STYLE]bd,[SIZE]18,[FONT]Arial
+00:00:41.00,00:00:44.40
+The Age of Gods was closing.[br]Eternity had come to an end.
+
+00:00:55.00,00:00:58.40
+Second line
+"#;
+
+    #[test]
+    fn test_parse_subviewer_round_trip() {
+        let result = parse(SAMPLE).unwrap();
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.preamble.len(), 6);
+        assert_eq!(
+            result.entries[0].text,
+            "The Age of Gods was closing.\nEternity had come to an end."
+        );
+        assert_eq!(result.entries[0].end_time.as_millis(), 44_400);
+        assert_eq!(serialize(&result), SAMPLE);
+    }
+
+    #[test]
+    fn test_serialize_without_preamble_writes_default_header() {
+        let mut result = parse(SAMPLE).unwrap();
+        result.preamble.clear();
+        assert!(serialize(&result)
+            .starts_with("[INFORMATION]\n[END INFORMATION]\n[SUBTITLE]\n00:00:41.00,"));
+    }
+}
@@ -0,0 +1,310 @@
+use super::SubtitleFormat;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Sub};
+
+// Regex genérica: [H+:]MM:SS[.,]fração (1 a 3 dígitos)
+static TIMESTAMP_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:(\d+):)?(\d{1,2}):(\d{1,2})(?:[.,](\d{1,3}))?$").unwrap());
+
+// Sintaxe exata de cada formato (usada para decidir se o texto original pode ser reaproveitado)
+static SRT_SYNTAX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d{2,}:\d{2}:\d{2},\d{3}$").unwrap());
+static ASS_SYNTAX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d+:\d{2}:\d{2}\.\d{2}$").unwrap());
+static VTT_SYNTAX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:\d{2,}:)?\d{2}:\d{2}\.\d{3}$").unwrap());
+static SUBVIEWER_SYNTAX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\d{2}:\d{2}:\d{2}\.\d{2}$").unwrap());
+static SBV_SYNTAX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d+:\d{2}:\d{2}\.\d{3}$").unwrap());
+static LRC_SYNTAX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d{2,}:\d{2}\.\d{2,3}$").unwrap());
+static TTML_SYNTAX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\d{2,}:\d{2}:\d{2}(?:\.\d{1,3})?$").unwrap());
+
+/// Instante de uma legenda com precisão de milissegundos
+///
+/// Guarda o texto original de onde foi lido para que arquivos não alterados
+/// sejam serializados exatamente como vieram. Qualquer operação aritmética
+/// descarta o texto original e passa a usar a formatação canônica do formato.
+#[derive(Debug, Clone, Default)]
+pub struct Timestamp {
+    millis: u64,
+    raw: Option<String>,
+}
+
+impl Timestamp {
+    pub fn from_millis(millis: u64) -> Self {
+        Self { millis, raw: None }
+    }
+
+    pub fn as_millis(&self) -> u64 {
+        self.millis
+    }
+
+    /// Faz parse de qualquer sintaxe suportada (SRT, ASS/SSA, VTT)
+    /// Horas não têm limite de dígitos (suporta mais de 99h)
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let trimmed = value.trim();
+        let caps = TIMESTAMP_REGEX
+            .captures(trimmed)
+            .ok_or_else(|| format!("Invalid timestamp: {}", value))?;
+
+        let number = |i: usize| -> Result<u64, String> {
+            caps.get(i)
+                .map(|m| m.as_str().parse::<u64>())
+                .transpose()
+                .map(|n| n.unwrap_or(0))
+                .map_err(|_| format!("Invalid timestamp: {}", value))
+        };
+
+        let hours = number(1)?;
+        let minutes = number(2)?;
+        let seconds = number(3)?;
+        if minutes >= 60 || seconds >= 60 {
+            return Err(format!("Invalid timestamp: {}", value));
+        }
+
+        // Fração: 1 dígito = décimos, 2 = centésimos, 3 = milissegundos
+        let fraction = match caps.get(4) {
+            Some(m) => {
+                let digits = m.as_str();
+                let n: u64 = digits.parse().unwrap_or(0);
+                n * 10u64.pow(3 - digits.len() as u32)
+            }
+            None => 0,
+        };
+
+        Ok(Self {
+            millis: ((hours * 60 + minutes) * 60 + seconds) * 1000 + fraction,
+            raw: Some(trimmed.to_string()),
+        })
+    }
+
+    /// Instante correspondente a um número de frame
+    pub fn from_frames(frame: u64, frame_rate: f64) -> Self {
+        Self::from_millis((frame as f64 * 1000.0 / frame_rate).round() as u64)
+    }
+
+    /// Número do frame exibido neste instante
+    pub fn to_frames(&self, frame_rate: f64) -> u64 {
+        (self.millis as f64 * frame_rate / 1000.0).round() as u64
+    }
+
+    /// Formata na sintaxe do formato informado
+    /// Reaproveita o texto original quando ele já está nessa sintaxe
+    /// MicroDVD usa frames (ver `to_frames`); aqui cai na sintaxe do SRT
+    /// SAMI usa milissegundos inteiros
+    pub fn to_string_for(&self, format: &SubtitleFormat) -> String {
+        if *format == SubtitleFormat::Sami {
+            return self.millis.to_string();
+        }
+
+        if let Some(raw) = &self.raw {
+            let syntax = match format {
+                SubtitleFormat::Srt | SubtitleFormat::MicroDvd => &SRT_SYNTAX,
+                SubtitleFormat::Ass | SubtitleFormat::Ssa => &ASS_SYNTAX,
+                SubtitleFormat::Vtt => &VTT_SYNTAX,
+                SubtitleFormat::SubViewer => &SUBVIEWER_SYNTAX,
+                SubtitleFormat::Sbv => &SBV_SYNTAX,
+                SubtitleFormat::Lrc => &LRC_SYNTAX,
+                SubtitleFormat::Ttml | SubtitleFormat::Sami => &TTML_SYNTAX,
+            };
+            if syntax.is_match(raw) {
+                return raw.clone();
+            }
+        }
+
+        match format {
+            SubtitleFormat::Srt | SubtitleFormat::MicroDvd => self.to_srt(),
+            SubtitleFormat::Ass | SubtitleFormat::Ssa => self.to_ass(),
+            SubtitleFormat::Vtt | SubtitleFormat::Ttml | SubtitleFormat::Sami => self.to_vtt(),
+            SubtitleFormat::SubViewer => self.to_subviewer(),
+            SubtitleFormat::Sbv => self.to_sbv(),
+            SubtitleFormat::Lrc => self.to_lrc(),
+        }
+    }
+
+    /// "HH:MM:SS,mmm"
+    pub fn to_srt(&self) -> String {
+        let (h, m, s, ms) = components(self.millis);
+        format!("{:02}:{:02}:{:02},{:03}", h, m, s, ms)
+    }
+
+    /// "H:MM:SS.cc" (centésimos, arredondado)
+    pub fn to_ass(&self) -> String {
+        let centis = (self.millis + 5) / 10;
+        let (h, m, s, ms) = components(centis * 10);
+        format!("{}:{:02}:{:02}.{:02}", h, m, s, ms / 10)
+    }
+
+    /// "HH:MM:SS.mmm"
+    pub fn to_vtt(&self) -> String {
+        let (h, m, s, ms) = components(self.millis);
+        format!("{:02}:{:02}:{:02}.{:03}", h, m, s, ms)
+    }
+
+    /// "HH:MM:SS.cc" (centésimos, arredondado)
+    pub fn to_subviewer(&self) -> String {
+        let centis = (self.millis + 5) / 10;
+        let (h, m, s, ms) = components(centis * 10);
+        format!("{:02}:{:02}:{:02}.{:02}", h, m, s, ms / 10)
+    }
+
+    /// "H:MM:SS.mmm"
+    pub fn to_sbv(&self) -> String {
+        let (h, m, s, ms) = components(self.millis);
+        format!("{}:{:02}:{:02}.{:03}", h, m, s, ms)
+    }
+
+    /// "MM:SS.cc" com minutos totais (sem horas)
+    pub fn to_lrc(&self) -> String {
+        let centis = (self.millis + 5) / 10;
+        let (h, m, s, ms) = components(centis * 10);
+        format!("{:02}:{:02}.{:02}", h * 60 + m, s, ms / 10)
+    }
+
+    /// Desloca por um offset em milissegundos (negativo permitido, satura em zero)
+    pub fn shifted(&self, offset_ms: i64) -> Self {
+        Self::from_millis(self.millis.saturating_add_signed(offset_ms))
+    }
+}
+
+/// Decompõe milissegundos em (horas, minutos, segundos, milissegundos)
+fn components(millis: u64) -> (u64, u64, u64, u64) {
+    let total_seconds = millis / 1000;
+    (
+        total_seconds / 3600,
+        (total_seconds / 60) % 60,
+        total_seconds % 60,
+        millis % 1000,
+    )
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.raw {
+            Some(raw) => f.write_str(raw),
+            None => f.write_str(&self.to_vtt()),
+        }
+    }
+}
+
+impl PartialEq for Timestamp {
+    fn eq(&self, other: &Self) -> bool {
+        self.millis == other.millis
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.millis.cmp(&other.millis)
+    }
+}
+
+impl Add for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, rhs: Timestamp) -> Timestamp {
+        Timestamp::from_millis(self.millis + rhs.millis)
+    }
+}
+
+/// Subtração satura em zero (não existem instantes negativos)
+impl Sub for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, rhs: Timestamp) -> Timestamp {
+        Timestamp::from_millis(self.millis.saturating_sub(rhs.millis))
+    }
+}
+
+/// Serializa como string para manter compatibilidade com o frontend
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Timestamp::parse(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_each_syntax() {
+        assert_eq!(Timestamp::parse("00:00:01,500").unwrap().as_millis(), 1500);
+        assert_eq!(Timestamp::parse("0:00:01.50").unwrap().as_millis(), 1500);
+        assert_eq!(Timestamp::parse("00:01.500").unwrap().as_millis(), 1500);
+        assert_eq!(
+            Timestamp::parse("123:04:05.006").unwrap().as_millis(),
+            ((123 * 60 + 4) * 60 + 5) * 1000 + 6
+        );
+        assert!(Timestamp::parse("00:61:00,000").is_err());
+        assert!(Timestamp::parse("abc").is_err());
+    }
+
+    #[test]
+    fn test_format_per_syntax() {
+        let ts = Timestamp::from_millis(((101 * 60 + 2) * 60 + 3) * 1000 + 456);
+        assert_eq!(ts.to_srt(), "101:02:03,456");
+        assert_eq!(ts.to_ass(), "101:02:03.46");
+        assert_eq!(ts.to_vtt(), "101:02:03.456");
+        assert_eq!(Timestamp::from_millis(9_995).to_ass(), "0:00:10.00");
+        assert_eq!(ts.to_subviewer(), "101:02:03.46");
+        assert_eq!(ts.to_sbv(), "101:02:03.456");
+        assert_eq!(ts.to_lrc(), "6062:03.46");
+    }
+
+    #[test]
+    fn test_frame_conversion() {
+        let ts = Timestamp::from_frames(24, 23.976);
+        assert_eq!(ts.as_millis(), 1001);
+        assert_eq!(ts.to_frames(23.976), 24);
+        assert_eq!(Timestamp::from_millis(2000).to_frames(25.0), 50);
+    }
+
+    #[test]
+    fn test_raw_text_is_reused_until_changed() {
+        let ts = Timestamp::parse("00:05.000").unwrap();
+        assert_eq!(ts.to_string_for(&SubtitleFormat::Vtt), "00:05.000");
+        assert_eq!(ts.to_string_for(&SubtitleFormat::Srt), "00:00:05,000");
+        assert_eq!(
+            ts.shifted(250).to_string_for(&SubtitleFormat::Vtt),
+            "00:00:05.250"
+        );
+    }
+
+    #[test]
+    fn test_ordering_and_arithmetic() {
+        let a = Timestamp::parse("00:00:01,000").unwrap();
+        let b = Timestamp::parse("0:00:02.00").unwrap();
+        assert!(a < b);
+        assert_eq!(a.clone() + b.clone(), Timestamp::from_millis(3000));
+        assert_eq!(b.clone() - a.clone(), Timestamp::from_millis(1000));
+        assert_eq!(a - b, Timestamp::from_millis(0));
+        assert_eq!(Timestamp::from_millis(500).shifted(-1000).as_millis(), 0);
+    }
+
+    #[test]
+    fn test_serde_uses_string_form() {
+        let ts = Timestamp::parse("00:00:01,000").unwrap();
+        assert_eq!(serde_json::to_string(&ts).unwrap(), "\"00:00:01,000\"");
+        let back: Timestamp = serde_json::from_str("\"0:00:01.00\"").unwrap();
+        assert_eq!(back, ts);
+    }
+}
@@ -0,0 +1,164 @@
+use super::{SubtitleFile, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// Operação de retiming aplicada a um arquivo de legenda
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum TimingOperation {
+    /// Desloca todos os tempos (negativo adianta a legenda)
+    Shift { offset_ms: i64 },
+    /// Transformação linear: t' = t * factor + offset_ms
+    Scale {
+        factor: f64,
+        #[serde(default)]
+        offset_ms: i64,
+    },
+    /// Conversão de frame rate, ex: 23.976 -> 25 (speedup PAL)
+    FrameRate { from: f64, to: f64 },
+    /// Sincronização por dois pontos: cada instante original vai para o
+    /// instante desejado e o resto é interpolado linearmente
+    TwoPoint { first: SyncPoint, second: SyncPoint },
+}
+
+/// Par (instante original, instante desejado) da sincronização por dois pontos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncPoint {
+    pub original: Timestamp,
+    pub target: Timestamp,
+}
+
+/// Faixa de entradas pelo `index` (inclusiva nas duas pontas)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct EntryRange {
+    pub first: usize,
+    pub last: usize,
+}
+
+impl EntryRange {
+    fn contains(&self, index: usize) -> bool {
+        (self.first..=self.last).contains(&index)
+    }
+}
+
+impl TimingOperation {
+    /// Fator e offset (ms) equivalentes: t' = t * factor + offset
+    fn linear(&self) -> Result<(f64, f64), String> {
+        let (factor, offset) = match self {
+            TimingOperation::Shift { offset_ms } => (1.0, *offset_ms as f64),
+            TimingOperation::Scale { factor, offset_ms } => (*factor, *offset_ms as f64),
+            TimingOperation::FrameRate { from, to } => {
+                if !(from.is_finite() && *from > 0.0 && to.is_finite() && *to > 0.0) {
+                    return Err(format!("Invalid frame rates: {} -> {}", from, to));
+                }
+                // Mesmos frames exibidos mais rápido: 23.976 -> 25 encurta os tempos
+                (from / to, 0.0)
+            }
+            TimingOperation::TwoPoint { first, second } => {
+                let original_span =
+                    second.original.as_millis() as f64 - first.original.as_millis() as f64;
+                if original_span == 0.0 {
+                    return Err("Sync points must have different original times".to_string());
+                }
+                let target_span =
+                    second.target.as_millis() as f64 - first.target.as_millis() as f64;
+                let factor = target_span / original_span;
+                let offset =
+                    first.target.as_millis() as f64 - first.original.as_millis() as f64 * factor;
+                (factor, offset)
+            }
+        };
+
+        if !(factor.is_finite() && factor > 0.0) {
+            return Err(format!("Invalid timing factor: {}", factor));
+        }
+        Ok((factor, offset))
+    }
+}
+
+/// Aplica a operação às entradas (todas ou só as da faixa)
+/// Tempos que ficariam negativos param em zero; os timestamps perdem o texto
+/// original e são reescritos na sintaxe do formato ao serializar (vírgula e
+/// milissegundos no SRT, centésimos no ASS)
+pub fn apply(
+    file: &mut SubtitleFile,
+    operation: &TimingOperation,
+    range: Option<EntryRange>,
+) -> Result<(), String> {
+    let (factor, offset) = operation.linear()?;
+    let transform = |ts: &Timestamp| {
+        let millis = (ts.as_millis() as f64 * factor + offset).round();
+        Timestamp::from_millis(millis.max(0.0) as u64)
+    };
+
+    for entry in &mut file.entries {
+        if range.is_some_and(|r| !r.contains(entry.index)) {
+            continue;
+        }
+        entry.start_time = transform(&entry.start_time);
+        entry.end_time = transform(&entry.end_time);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subtitle::{ass, srt};
+
+    const SRT: &str =
+        "1\n00:00:01,000 --> 00:00:02,500\nOne\n\n2\n00:00:10,000 --> 00:00:12,000\nTwo\n";
+
+    #[test]
+    fn test_shift_range_and_clamp() {
+        let mut file = srt::parse(SRT).unwrap();
+        let shift = TimingOperation::Shift { offset_ms: -1500 };
+        apply(&mut file, &shift, Some(EntryRange { first: 1, last: 1 })).unwrap();
+        assert_eq!(file.entries[0].start_time.as_millis(), 0);
+        assert_eq!(file.entries[0].end_time.as_millis(), 1000);
+        assert_eq!(file.entries[1].start_time.as_millis(), 10_000);
+        assert!(srt::serialize(&file).contains("00:00:00,000 --> 00:00:01,000"));
+    }
+
+    #[test]
+    fn test_frame_rate_conversion_in_ass() {
+        let content = "[Script Info]\nScriptType: v4.00+\n\n[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nDialogue: 0,0:00:25.00,0:00:50.00,Default,,0,0,0,,Hi\n";
+        let mut file = ass::parse(content).unwrap();
+        let conversion = TimingOperation::FrameRate {
+            from: 23.976,
+            to: 25.0,
+        };
+        apply(&mut file, &conversion, None).unwrap();
+        assert_eq!(file.entries[0].start_time.as_millis(), 23_976);
+        assert!(ass::serialize(&file).contains("0:00:23.98,0:00:47.95"));
+    }
+
+    #[test]
+    fn test_two_point_sync() {
+        let mut file = srt::parse(SRT).unwrap();
+        let sync = TimingOperation::TwoPoint {
+            first: SyncPoint {
+                original: Timestamp::from_millis(1000),
+                target: Timestamp::from_millis(2000),
+            },
+            second: SyncPoint {
+                original: Timestamp::from_millis(10_000),
+                target: Timestamp::from_millis(20_000),
+            },
+        };
+        apply(&mut file, &sync, None).unwrap();
+        assert_eq!(file.entries[0].start_time.as_millis(), 2000);
+        assert_eq!(file.entries[0].end_time.as_millis(), 5000);
+        assert_eq!(file.entries[1].start_time.as_millis(), 20_000);
+
+        let invalid = TimingOperation::Scale {
+            factor: 0.0,
+            offset_ms: 0,
+        };
+        assert!(apply(&mut file, &invalid, None).is_err());
+    }
+}
@@ -0,0 +1,714 @@
+use super::{
+    LineEnding, MarkupHeaders, ParseDiagnostic, SubtitleEntry, SubtitleFile, SubtitleFormat,
+    SubtitleMetadata, Timestamp,
+};
+use once_cell::sync::Lazy;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use regex::Regex;
+
+/// Início do documento gerado quando o arquivo não veio de um TTML
+const DEFAULT_PROLOGUE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling" xml:lang="en">
+  <head/>
+  <body>
+    <div>
+      "#;
+
+/// Fim do documento gerado quando o arquivo não veio de um TTML
+const DEFAULT_EPILOGUE: &str = "\n    </div>\n  </body>\n</tt>\n";
+
+// Clock time: "HH:MM:SS", "HH:MM:SS.fração" ou "HH:MM:SS:frames"
+static CLOCK_TIME_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\d+):(\d{2}):(\d{2})(?:\.(\d+)|:(\d+)(?:\.\d+)?)?$").unwrap());
+
+// Offset time: "12.5s", "500ms", "90f", "1000t"...
+static OFFSET_TIME_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\d+(?:\.\d+)?)(h|m|s|ms|f|t)$").unwrap());
+
+// Tags HTML-like do texto (<i>, </b>, <span tts:color="red">...)
+static HTML_TAG_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<(/?)([a-zA-Z][\w:.-]*)[^<>]*>").unwrap());
+
+/// Instrução que marca a posição de cada legenda no esqueleto do documento
+/// ao serializar (<?subtitle-entry?>)
+const ENTRY_MARK: &str = "subtitle-entry";
+
+/// Base de tempo declarada no elemento <tt>
+struct TimeBase {
+    frame_rate: f64,
+    tick_rate: f64,
+}
+
+impl Default for TimeBase {
+    fn default() -> Self {
+        Self {
+            frame_rate: 30.0,
+            tick_rate: 1.0,
+        }
+    }
+}
+
+/// Elemento aberto em <body> (<body>, <div>...) com o que ele passa aos <p>
+/// Tempos em milissegundos absolutos, já somados aos dos ancestrais
+#[derive(Clone, Default)]
+struct Container {
+    region: Option<String>,
+    style: Option<String>,
+    begin: Option<u64>,
+    end: Option<u64>,
+}
+
+impl Container {
+    /// `begin` e `end` contam a partir do início do pai; `dur`, do próprio
+    /// início; o que faltar (ou for inválido) vem do pai
+    fn new(e: &BytesStart, parent: Option<&Container>, time_base: &TimeBase) -> Self {
+        let parent = parent.cloned().unwrap_or_default();
+        let time = |name: &str| {
+            attribute(e, name)
+                .and_then(|v| parse_time_expression(&v, time_base).ok())
+                .map(|t| t.as_millis())
+        };
+        let base = parent.begin.unwrap_or(0);
+        let begin = time("begin").map(|b| base + b).or(parent.begin);
+        let end = time("end")
+            .map(|end| base + end)
+            .or_else(|| time("dur").map(|dur| begin.unwrap_or(0) + dur))
+            .or(parent.end);
+        Self {
+            region: attribute(e, "region").or(parent.region),
+            style: attribute(e, "style").or(parent.style),
+            begin,
+            end,
+        }
+    }
+}
+
+/// <p> em leitura
+struct Paragraph {
+    line: usize,
+    start: usize,
+    begin: Option<String>,
+    end: Option<String>,
+    dur: Option<String>,
+    /// Elemento que contém o <p>, base dos tempos dele
+    parent: Container,
+    metadata: SubtitleMetadata,
+    text: String,
+    /// Tags de fechamento de cada <span> aberto (para fechar na ordem certa)
+    spans: Vec<Vec<String>>,
+}
+
+/// Faz parse de um documento TTML (DFXP, IMSC1)
+/// Cada <p> do <body> vira uma entrada; `region`, `style` e `xml:id` (do
+/// próprio <p> ou herdados de <body>/<div>) vão para os metadados. Os tempos
+/// do <p> contam a partir do `begin` de <body>/<div>, e um <p> sem tempos
+/// herda os deles. <br/> vira quebra de linha e spans itálico/negrito/
+/// sublinhado viram <i>/<b>/<u>; outros spans ficam como estão no texto.
+/// O documento antes do primeiro <p>, entre os <p> e depois do último é
+/// guardado em `markup_headers` para que a serialização mantenha <head>,
+/// estilos, regiões, <div>, comentários e os <p> que não viraram entrada.
+pub fn parse(content: &str) -> Result<SubtitleFile, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let line_ending = LineEnding::detect(content);
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+    let line_at = |pos: usize| content[..pos.min(content.len())].matches('\n').count() + 1;
+
+    let mut reader = Reader::from_str(&content);
+    let mut time_base = TimeBase::default();
+    // Elementos abertos em <body>, do mais externo ao mais interno
+    let mut inherited: Vec<Container> = Vec::new();
+    let mut in_body = false;
+    let mut paragraph: Option<Paragraph> = None;
+    // Início do primeiro <p> válido e fim do último
+    let mut first: Option<usize> = None;
+    let mut last_end = 0;
+    let mut interleaved = Vec::new();
+    let mut entries = Vec::new();
+    let mut diagnostics = Vec::new();
+
+    loop {
+        let event_start = reader.buffer_position() as usize;
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("Invalid XML at line {}: {}", line_at(event_start), e))?;
+
+        match event {
+            Event::Start(e) | Event::Empty(e) if paragraph.is_none() && local_name(&e) == "tt" => {
+                time_base = read_time_base(&e);
+            }
+            Event::Start(e) if paragraph.is_none() && local_name(&e) == "body" => {
+                in_body = true;
+                inherited.push(Container::new(&e, None, &time_base));
+            }
+            Event::End(e) if paragraph.is_none() && e.local_name().as_ref() == b"body" => {
+                in_body = false;
+                inherited.clear();
+            }
+            Event::Start(e) if in_body && paragraph.is_none() && local_name(&e) == "p" => {
+                let parent = inherited.last().cloned().unwrap_or_default();
+                paragraph = Some(Paragraph {
+                    line: line_at(event_start),
+                    start: event_start,
+                    begin: attribute(&e, "begin"),
+                    end: attribute(&e, "end"),
+                    dur: attribute(&e, "dur"),
+                    metadata: SubtitleMetadata {
+                        region: attribute(&e, "region").or_else(|| parent.region.clone()),
+                        style: attribute(&e, "style").or_else(|| parent.style.clone()),
+                        identifier: attribute(&e, "id"),
+                        ..Default::default()
+                    },
+                    parent,
+                    text: String::new(),
+                    spans: Vec::new(),
+                });
+            }
+            Event::Start(e) if in_body && paragraph.is_none() => {
+                inherited.push(Container::new(&e, inherited.last(), &time_base));
+            }
+            Event::End(_) if in_body && paragraph.is_none() => {
+                inherited.pop();
+            }
+            Event::Start(e) if paragraph.is_some() => {
+                let p = paragraph.as_mut().unwrap();
+                match local_name(&e).as_str() {
+                    "br" => {
+                        p.text.push('\n');
+                        p.spans.push(Vec::new());
+                    }
+                    "span" => match span_tags(&e) {
+                        Some(tags) => {
+                            for tag in &tags {
+                                p.text.push_str(&format!("<{}>", tag));
+                            }
+                            p.spans.push(tags.iter().map(|t| t.to_string()).collect());
+                        }
+                        // Estilo sem equivalente (cor, referência de estilo...)
+                        None => {
+                            p.text
+                                .push_str(&format!("<{}>", String::from_utf8_lossy(&e)));
+                            let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                            p.spans.push(vec![name]);
+                        }
+                    },
+                    _ => p.spans.push(Vec::new()),
+                }
+            }
+            Event::Empty(e) if paragraph.is_some() && local_name(&e) == "br" => {
+                paragraph.as_mut().unwrap().text.push('\n');
+            }
+            Event::Text(e) if paragraph.is_some() => {
+                let text = e.unescape().map_err(|err| {
+                    format!("Invalid XML at line {}: {}", line_at(event_start), err)
+                })?;
+                paragraph
+                    .as_mut()
+                    .unwrap()
+                    .text
+                    .push_str(&collapse_whitespace(&text));
+            }
+            Event::CData(e) if paragraph.is_some() => {
+                let text = String::from_utf8_lossy(&e).into_owned();
+                paragraph.as_mut().unwrap().text.push_str(&text);
+            }
+            Event::End(e) if paragraph.is_some() => {
+                if e.local_name().as_ref() == b"p" {
+                    let end = reader.buffer_position() as usize;
+                    let p = paragraph.take().unwrap();
+                    match finish_paragraph(&p, &time_base) {
+                        Ok(mut entry) => {
+                            // O que ficou desde o <p> anterior volta no mesmo lugar
+                            if first.is_none() {
+                                first = Some(p.start);
+                            } else {
+                                interleaved
+                                    .push((entries.len(), content[last_end..p.start].to_string()));
+                            }
+                            last_end = end;
+                            entry.index = entries.len() + 1;
+                            entries.push(entry);
+                        }
+                        Err(reason) => diagnostics.push(ParseDiagnostic::new(
+                            p.line,
+                            reason,
+                            &content[p.start..end],
+                        )),
+                    }
+                } else if let Some(tags) = paragraph.as_mut().unwrap().spans.pop() {
+                    let p = paragraph.as_mut().unwrap();
+                    for tag in tags.iter().rev() {
+                        p.text.push_str(&format!("</{}>", tag));
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if entries.is_empty() {
+        return Err("No valid subtitle entries found".to_string());
+    }
+
+    let first = first.unwrap_or(0);
+    Ok(SubtitleFile {
+        line_ending,
+        diagnostics,
+        markup_headers: Some(MarkupHeaders {
+            prologue: content[..first].to_string(),
+            epilogue: content[last_end..].to_string(),
+            interleaved,
+        }),
+        ..SubtitleFile::new(SubtitleFormat::Ttml, entries)
+    })
+}
+
+/// Nome local do elemento (sem prefixo de namespace)
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+}
+
+/// Valor de um atributo pelo nome local ("region", "id" para xml:id...)
+fn attribute(e: &BytesStart, name: &str) -> Option<String> {
+    e.attributes().flatten().find_map(|attr| {
+        (attr.key.local_name().as_ref() == name.as_bytes())
+            .then(|| attr.unescape_value().ok().map(|v| v.into_owned()))
+            .flatten()
+    })
+}
+
+/// Lê ttp:frameRate, ttp:frameRateMultiplier e ttp:tickRate
+fn read_time_base(e: &BytesStart) -> TimeBase {
+    let mut base = TimeBase::default();
+    if let Some(rate) = attribute(e, "frameRate").and_then(|v| v.trim().parse::<f64>().ok()) {
+        base.frame_rate = rate;
+    }
+    if let Some(multiplier) = attribute(e, "frameRateMultiplier") {
+        let parts: Vec<f64> = multiplier
+            .split_whitespace()
+            .filter_map(|p| p.parse().ok())
+            .collect();
+        if let [numerator, denominator] = parts[..] {
+            if denominator > 0.0 {
+                base.frame_rate *= numerator / denominator;
+            }
+        }
+    }
+    if let Some(rate) = attribute(e, "tickRate").and_then(|v| v.trim().parse::<f64>().ok()) {
+        base.tick_rate = rate;
+    }
+    base
+}
+
+/// Tags HTML equivalentes ao estilo inline de um <span>, ou None quando
+/// algum atributo não tem equivalente e o <span> precisa ser mantido
+fn span_tags(e: &BytesStart) -> Option<Vec<&'static str>> {
+    let mut tags = Vec::new();
+    for attr in e.attributes().flatten() {
+        let value = attr.unescape_value().ok()?;
+        let tag = match (attr.key.local_name().as_ref(), value.as_ref()) {
+            (b"fontStyle", "italic" | "oblique") => "i",
+            (b"fontWeight", "bold") => "b",
+            (b"textDecoration", "underline") => "u",
+            _ => return None,
+        };
+        tags.push(tag);
+    }
+    Some(tags)
+}
+
+/// Espaços em branco do XML (inclusive quebras de linha) viram um espaço
+fn collapse_whitespace(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut previous_space = false;
+    for ch in text.chars() {
+        if ch.is_whitespace() {
+            if !previous_space {
+                output.push(' ');
+            }
+            previous_space = true;
+        } else {
+            output.push(ch);
+            previous_space = false;
+        }
+    }
+    output
+}
+
+/// Monta a entrada a partir de um <p> completo
+/// Tempos relativos ao `begin` do elemento pai; sem `begin`/`end` próprios,
+/// valem os do pai
+fn finish_paragraph(p: &Paragraph, time_base: &TimeBase) -> Result<SubtitleEntry, String> {
+    let base = Timestamp::from_millis(p.parent.begin.unwrap_or(0));
+    let start_time = match (&p.begin, p.parent.begin) {
+        (Some(begin), _) => base.clone() + parse_time_expression(begin, time_base)?,
+        (None, Some(begin)) => Timestamp::from_millis(begin),
+        (None, None) => return Err("Missing begin attribute".to_string()),
+    };
+    let end_time = match (&p.end, &p.dur, p.parent.end) {
+        (Some(end), ..) => base + parse_time_expression(end, time_base)?,
+        (None, Some(dur), _) => start_time.clone() + parse_time_expression(dur, time_base)?,
+        (None, None, Some(end)) => Timestamp::from_millis(end),
+        (None, None, None) => return Err("Missing end or dur attribute".to_string()),
+    };
+
+    let text = p
+        .text
+        .split('\n')
+        .map(|line| line.trim())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let metadata = &p.metadata;
+    let has_metadata =
+        metadata.region.is_some() || metadata.style.is_some() || metadata.identifier.is_some();
+
+    Ok(SubtitleEntry {
+        index: 0,
+        start_time,
+        end_time,
+        text,
+        metadata: has_metadata.then(|| metadata.clone()),
+    })
+}
+
+/// Converte uma expressão de tempo TTML (clock time ou offset time)
+fn parse_time_expression(value: &str, time_base: &TimeBase) -> Result<Timestamp, String> {
+    let value = value.trim();
+    let invalid = || format!("Invalid timestamp: {}", value);
+
+    if let Some(caps) = CLOCK_TIME_REGEX.captures(value) {
+        // Até milissegundos a sintaxe é compartilhada com os outros formatos
+        if caps.get(5).is_none() && caps.get(4).is_none_or(|f| f.as_str().len() <= 3) {
+            return Timestamp::parse(value);
+        }
+
+        let number = |i: usize| caps[i].parse::<u64>().map_err(|_| invalid());
+        let (hours, minutes, seconds) = (number(1)?, number(2)?, number(3)?);
+        if minutes >= 60 || seconds >= 60 {
+            return Err(invalid());
+        }
+        let whole = ((hours * 60 + minutes) * 60 + seconds) as f64 * 1000.0;
+        let fraction = match (caps.get(4), caps.get(5)) {
+            (Some(f), _) => format!("0.{}", f.as_str()).parse::<f64>().unwrap_or(0.0) * 1000.0,
+            (None, Some(frames)) => {
+                frames.as_str().parse::<f64>().unwrap_or(0.0) * 1000.0 / time_base.frame_rate
+            }
+            (None, None) => 0.0,
+        };
+        return Ok(Timestamp::from_millis((whole + fraction).round() as u64));
+    }
+
+    let caps = OFFSET_TIME_REGEX.captures(value).ok_or_else(invalid)?;
+    let amount: f64 = caps[1].parse().map_err(|_| invalid())?;
+    let millis = match &caps[2] {
+        "h" => amount * 3_600_000.0,
+        "m" => amount * 60_000.0,
+        "s" => amount * 1000.0,
+        "ms" => amount,
+        "f" => amount * 1000.0 / time_base.frame_rate,
+        _ => amount * 1000.0 / time_base.tick_rate,
+    };
+    Ok(Timestamp::from_millis(millis.round() as u64))
+}
+
+/// Serializa para TTML
+/// Reaproveita o documento original (ou um mínimo) em volta dos <p>, com os
+/// trechos entre eles no mesmo lugar; cada entrada é escrita com begin/end
+/// em clock time relativos ao elemento que a contém e com as referências de
+/// região e estilo que ela não herda dele
+pub fn serialize(file: &SubtitleFile) -> String {
+    let (prologue, epilogue, interleaved) = match &file.markup_headers {
+        Some(headers) => (
+            headers.prologue.as_str(),
+            headers.epilogue.as_str(),
+            headers.interleaved.as_slice(),
+        ),
+        None => (DEFAULT_PROLOGUE, DEFAULT_EPILOGUE, &[][..]),
+    };
+    // Indentação do primeiro <p>, repetida entre <p> sem trecho guardado
+    let indent = &prologue[prologue.rfind('\n').map_or(prologue.len(), |i| i + 1)..];
+    let indent = if indent.trim().is_empty() { indent } else { "" };
+
+    // Trecho antes de cada entrada e o que sobra depois da última
+    let mut interleaved = interleaved.iter().peekable();
+    let mut gaps = Vec::with_capacity(file.entries.len());
+    for i in 0..file.entries.len() {
+        let mut gap = String::new();
+        let mut kept = false;
+        while let Some((_, raw)) = interleaved.next_if(|(before, _)| *before <= i) {
+            gap.push_str(raw);
+            kept = true;
+        }
+        if i > 0 && !kept {
+            gap = format!("\n{}", indent);
+        }
+        gaps.push(gap);
+    }
+    let tail: String = interleaved.map(|(_, raw)| raw.as_str()).collect();
+
+    let mut skeleton = String::from(prologue);
+    for gap in &gaps {
+        skeleton.push_str(gap);
+        skeleton.push_str(&format!("<?{}?>", ENTRY_MARK));
+    }
+    skeleton.push_str(&tail);
+    skeleton.push_str(epilogue);
+    let containers = entry_containers(&skeleton)
+        .filter(|found| found.len() == file.entries.len())
+        .unwrap_or_else(|| vec![Container::default(); file.entries.len()]);
+
+    let mut output = String::from(prologue);
+    for ((entry, gap), container) in file.entries.iter().zip(&gaps).zip(&containers) {
+        output.push_str(gap);
+        output.push_str(&serialize_entry(entry, container));
+    }
+    output.push_str(&tail);
+    output.push_str(epilogue);
+    output
+}
+
+/// Elemento que contém cada marca de legenda no esqueleto do documento, ou
+/// None se o esqueleto não for XML válido
+fn entry_containers(skeleton: &str) -> Option<Vec<Container>> {
+    let mut reader = Reader::from_str(skeleton);
+    let mut time_base = TimeBase::default();
+    let mut inherited: Vec<Container> = Vec::new();
+    let mut in_body = false;
+    let mut found = Vec::new();
+
+    loop {
+        match reader.read_event().ok()? {
+            Event::Start(e) | Event::Empty(e) if local_name(&e) == "tt" => {
+                time_base = read_time_base(&e);
+            }
+            Event::Start(e) if local_name(&e) == "body" => {
+                in_body = true;
+                inherited.push(Container::new(&e, None, &time_base));
+            }
+            Event::Start(e) if in_body => {
+                inherited.push(Container::new(&e, inherited.last(), &time_base));
+            }
+            Event::End(e) if in_body => {
+                if e.local_name().as_ref() == b"body" {
+                    in_body = false;
+                    inherited.clear();
+                } else {
+                    inherited.pop();
+                }
+            }
+            Event::PI(pi) if pi.target() == ENTRY_MARK.as_bytes() => {
+                found.push(inherited.last().cloned().unwrap_or_default());
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Some(found)
+}
+
+fn serialize_entry(entry: &SubtitleEntry, container: &Container) -> String {
+    let mut attributes = String::new();
+    let metadata = entry.metadata.as_ref();
+    if let Some(id) = metadata.and_then(|m| m.identifier.as_ref()) {
+        attributes.push_str(&format!(" xml:id=\"{}\"", escape(id)));
+    }
+    let base = container.begin.unwrap_or(0) as i64;
+    attributes.push_str(&format!(
+        " begin=\"{}\" end=\"{}\"",
+        entry
+            .start_time
+            .shifted(-base)
+            .to_string_for(&SubtitleFormat::Ttml),
+        entry
+            .end_time
+            .shifted(-base)
+            .to_string_for(&SubtitleFormat::Ttml)
+    ));
+    if let Some(region) = metadata
+        .and_then(|m| m.region.as_ref())
+        .filter(|r| container.region.as_ref() != Some(*r))
+    {
+        attributes.push_str(&format!(" region=\"{}\"", escape(region)));
+    }
+    if let Some(style) = metadata
+        .and_then(|m| m.style.as_ref())
+        .filter(|s| container.style.as_ref() != Some(*s))
+    {
+        attributes.push_str(&format!(" style=\"{}\"", escape(style)));
+    }
+
+    format!("<p{}>{}</p>", attributes, text_to_markup(&entry.text))
+}
+
+/// Converte o texto (com <i>/<b>/<u>) para conteúdo XML de um <p>
+fn text_to_markup(text: &str) -> String {
+    let mut output = String::new();
+    let mut last = 0;
+    for caps in HTML_TAG_REGEX.captures_iter(text) {
+        let whole = caps.get(0).unwrap();
+        output.push_str(&escape(&text[last..whole.start()]));
+        last = whole.end();
+
+        let closing = !caps[1].is_empty();
+        let name = caps[2].to_lowercase();
+        let style = match name.rsplit(':').next().unwrap_or_default() {
+            "i" => "tts:fontStyle=\"italic\"",
+            "b" => "tts:fontWeight=\"bold\"",
+            "u" => "tts:textDecoration=\"underline\"",
+            // <span> mantido do original passa como está
+            "span" => {
+                output.push_str(whole.as_str());
+                continue;
+            }
+            // Demais tags não têm equivalente e são descartadas
+            _ => continue,
+        };
+        if closing {
+            output.push_str("</span>");
+        } else {
+            output.push_str(&format!("<span {}>", style));
+        }
+    }
+    output.push_str(&escape(&text[last..]));
+    output.replace('\n', "<br/>")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling" xmlns:ttp="http://www.w3.org/ns/ttml#parameter" ttp:frameRate="25">
+  <head>
+    <styling>
+      <style xml:id="s1" tts:color="white"/>
+    </styling>
+    <layout>
+      <region xml:id="bottom" tts:origin="10% 80%" tts:extent="80% 20%"/>
+      <region xml:id="top" tts:origin="10% 5%" tts:extent="80% 20%"/>
+    </layout>
+  </head>
+  <body style="s1">
+    <div region="bottom">
+      <p xml:id="c1" begin="00:00:01.000" end="00:00:03.500">Hello<br/>World &amp; all</p>
+      <p xml:id="c2" begin="00:00:04.000" end="00:00:06.000" region="top"><span tts:fontStyle="italic">Second</span> line</p>
+    </div>
+  </body>
+</tt>
+"#;
+
+    #[test]
+    fn test_parse_ttml_keeps_region_and_style() {
+        let result = parse(SAMPLE).unwrap();
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.entries[0].text, "Hello\nWorld & all");
+        assert_eq!(result.entries[0].end_time.as_millis(), 3500);
+        assert_eq!(result.entries[1].text, "<i>Second</i> line");
+
+        let first = result.entries[0].metadata.as_ref().unwrap();
+        assert_eq!(first.region.as_deref(), Some("bottom"));
+        assert_eq!(first.style.as_deref(), Some("s1"));
+        assert_eq!(first.identifier.as_deref(), Some("c1"));
+        let second = result.entries[1].metadata.as_ref().unwrap();
+        assert_eq!(second.region.as_deref(), Some("top"));
+    }
+
+    #[test]
+    fn test_serialize_ttml_keeps_document() {
+        let result = parse(SAMPLE).unwrap();
+        let output = serialize(&result);
+        assert!(output.contains("<region xml:id=\"top\""));
+        // Região e estilo herdados de <div>/<body> não são repetidos no <p>
+        assert!(output.contains(
+            "<p xml:id=\"c1\" begin=\"00:00:01.000\" end=\"00:00:03.500\">Hello<br/>World &amp; all</p>\n      <p xml:id=\"c2\""
+        ));
+        assert_eq!(output, SAMPLE);
+        assert!(output.contains("<span tts:fontStyle=\"italic\">Second</span> line</p>"));
+
+        let reparsed = parse(&output).unwrap();
+        assert_eq!(reparsed.entries.len(), 2);
+        assert_eq!(reparsed.entries[1].text, result.entries[1].text);
+    }
+
+    #[test]
+    fn test_divs_comments_and_untimed_paragraphs_round_trip() {
+        let content = include_str!("../../tests/fixtures/ttml/two_divs.ttml");
+        let mut result = parse(content).unwrap();
+        assert_eq!(result.entries.len(), 4);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(
+            result.entries[1].text,
+            "<span tts:color=\"red\">Red</span> and <i>italic</i>"
+        );
+        assert_eq!(result.entries[2].start_time.as_millis(), 61_000);
+        assert_eq!(result.entries[2].end_time.as_millis(), 62_500);
+        let third = result.entries[2].metadata.as_ref().unwrap();
+        assert_eq!(third.region.as_deref(), Some("top"));
+        assert_eq!(third.style.as_deref(), Some("s2"));
+        assert_eq!(serialize(&result), content);
+
+        result.apply_translations(vec![(3, "Placa".to_string())]);
+        result.entries[2].start_time = Timestamp::from_millis(61_500);
+        let output = serialize(&result);
+        assert!(output.contains(
+            "<!-- Part 2, times relative to the div -->\n      <p xml:id=\"c3\" begin=\"00:00:01.500\" end=\"00:00:02.500\">Placa</p>"
+        ));
+        assert!(output.contains("<p>Untimed note</p>"));
+    }
+
+    #[test]
+    fn test_paragraphs_inherit_container_timing() {
+        let content = "<tt xmlns=\"http://www.w3.org/ns/ttml\"><body><div begin=\"10s\" end=\"20s\">\n<p>Whole div</p>\n<p begin=\"2s\" dur=\"1s\">Inside</p>\n</div></body></tt>";
+        let result = parse(content).unwrap();
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(result.entries[0].start_time.as_millis(), 10_000);
+        assert_eq!(result.entries[0].end_time.as_millis(), 20_000);
+        assert_eq!(result.entries[1].start_time.as_millis(), 12_000);
+        assert_eq!(result.entries[1].end_time.as_millis(), 13_000);
+
+        let reparsed = parse(&serialize(&result)).unwrap();
+        assert_eq!(reparsed.entries[1].start_time.as_millis(), 12_000);
+        assert_eq!(reparsed.entries[1].end_time.as_millis(), 13_000);
+    }
+
+    #[test]
+    fn test_time_expressions() {
+        let base = TimeBase {
+            frame_rate: 25.0,
+            tick_rate: 10_000_000.0,
+        };
+        let millis = |v: &str| parse_time_expression(v, &base).unwrap().as_millis();
+        assert_eq!(millis("00:00:01:05"), 1200);
+        assert_eq!(millis("00:00:01.2346"), 1235);
+        assert_eq!(millis("2.5s"), 2500);
+        assert_eq!(millis("1500ms"), 1500);
+        assert_eq!(millis("50f"), 2000);
+        assert_eq!(millis("15000000t"), 1500);
+        assert!(parse_time_expression("soon", &base).is_err());
+    }
+
+    #[test]
+    fn test_parse_reports_untimed_paragraphs() {
+        let content = "<tt xmlns=\"http://www.w3.org/ns/ttml\"><body><div>\n<p begin=\"1s\" end=\"2s\">Ok</p>\n<p>No timing</p>\n</div></body></tt>";
+        let result = parse(content).unwrap();
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(
+            result.diagnostics,
+            vec![ParseDiagnostic::new(
+                3,
+                "Missing begin attribute",
+                "<p>No timing</p>"
+            )]
+        );
+    }
+}
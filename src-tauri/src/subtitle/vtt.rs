@@ -0,0 +1,317 @@
+use super::{
+    LineEnding, ParseDiagnostic, SubtitleEntry, SubtitleFile, SubtitleFormat, SubtitleMetadata,
+    Timestamp, VttBlock, VttBlockKind, VttHeaders,
+};
+
+/// Faz parse de um arquivo WebVTT
+/// Preserva o cabeçalho WEBVTT, blocos NOTE/STYLE/REGION, identificadores
+/// e configurações dos cues. A marcação inline (<i>, <b>, <c.classe>, <v Nome>)
+/// é mantida como está no texto.
+pub fn parse(content: &str) -> Result<SubtitleFile, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let line_ending = LineEnding::detect(content);
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+
+    let mut lines = content.lines().enumerate();
+
+    // Primeira linha: assinatura WEBVTT (pode ter texto após espaço ou tab)
+    let signature = lines.next().map(|(_, l)| l).unwrap_or("");
+    if !is_keyword_line(signature, "WEBVTT") {
+        return Err("Invalid WebVTT file: missing WEBVTT header".to_string());
+    }
+
+    let mut headers = VttHeaders {
+        header: vec![signature.trim_end().to_string()],
+        blocks: Vec::new(),
+    };
+
+    // Linhas de metadados do cabeçalho vão até a primeira linha em branco
+    for (_, line) in lines.by_ref() {
+        if line.trim().is_empty() {
+            break;
+        }
+        headers.header.push(line.trim_end().to_string());
+    }
+
+    // Divide o restante em blocos (separados por uma ou mais linhas em branco)
+    // guardando a linha (1-indexed) onde cada bloco começa
+    let mut blocks: Vec<(usize, Vec<&str>)> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut current_line = 0;
+    for (i, line) in lines {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                blocks.push((current_line, std::mem::take(&mut current)));
+            }
+        } else {
+            if current.is_empty() {
+                current_line = i + 1;
+            }
+            current.push(line.trim_end());
+        }
+    }
+    if !current.is_empty() {
+        blocks.push((current_line, current));
+    }
+
+    let mut entries = Vec::new();
+    let mut diagnostics = Vec::new();
+
+    for (block_line, block) in blocks {
+        let first = block[0];
+
+        let kind = if is_keyword_line(first, "NOTE") {
+            Some(VttBlockKind::Note)
+        } else if is_keyword_line(first, "STYLE") {
+            Some(VttBlockKind::Style)
+        } else if is_keyword_line(first, "REGION") {
+            Some(VttBlockKind::Region)
+        } else {
+            None
+        };
+
+        if let Some(kind) = kind {
+            headers.blocks.push(VttBlock {
+                kind,
+                content: block.join("\n"),
+                position: entries.len(),
+            });
+            continue;
+        }
+
+        // Cue: identificador opcional seguido da linha de tempo
+        let (identifier, timing_pos) = if first.contains("-->") {
+            (None, 0)
+        } else if block.len() > 1 && block[1].contains("-->") {
+            (Some(first.to_string()), 1)
+        } else {
+            diagnostics.push(ParseDiagnostic::new(
+                block_line,
+                "Block has no timing line",
+                block.join("\n"),
+            ));
+            continue;
+        };
+
+        let Some((start_time, end_time, settings)) = parse_timing_line(block[timing_pos]) else {
+            diagnostics.push(ParseDiagnostic::new(
+                block_line + timing_pos,
+                format!("Invalid timing line: {}", block[timing_pos]),
+                block.join("\n"),
+            ));
+            continue;
+        };
+
+        let text = block[timing_pos + 1..].join("\n");
+
+        entries.push(SubtitleEntry {
+            index: entries.len() + 1, // 1-indexed para consistência com SRT
+            start_time,
+            end_time,
+            text,
+            metadata: Some(SubtitleMetadata {
+                identifier,
+                cue_settings: settings,
+                ..Default::default()
+            }),
+        });
+    }
+
+    if entries.is_empty() {
+        return Err("No valid subtitle entries found".to_string());
+    }
+
+    Ok(SubtitleFile {
+        format: SubtitleFormat::Vtt,
+        entries,
+        headers: None,
+        vtt_headers: Some(headers),
+        encoding: None,
+        bom: false,
+        line_ending,
+        diagnostics,
+        preamble: Vec::new(),
+        frame_rate: None,
+        markup_headers: None,
+    })
+}
+
+/// Verifica se a linha é a palavra-chave sozinha ou seguida de espaço/tab
+fn is_keyword_line(line: &str, keyword: &str) -> bool {
+    match line.strip_prefix(keyword) {
+        Some(rest) => rest.is_empty() || rest.starts_with(' ') || rest.starts_with('\t'),
+        None => false,
+    }
+}
+
+/// Faz parse da linha "início --> fim [configurações]"
+fn parse_timing_line(line: &str) -> Option<(Timestamp, Timestamp, Option<String>)> {
+    let (start, rest) = line.split_once("-->")?;
+    let start = start.trim();
+    let rest = rest.trim_start();
+
+    let end_len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let end = &rest[..end_len];
+    let settings = rest[end_len..].trim();
+
+    Some((
+        Timestamp::parse(start).ok()?,
+        Timestamp::parse(end).ok()?,
+        if settings.is_empty() {
+            None
+        } else {
+            Some(settings.to_string())
+        },
+    ))
+}
+
+/// Serializa para formato WebVTT
+pub fn serialize(file: &SubtitleFile) -> String {
+    let mut output = String::new();
+
+    let default_headers = VttHeaders::default();
+    let headers = file.vtt_headers.as_ref().unwrap_or(&default_headers);
+
+    if headers.header.is_empty() {
+        output.push_str("WEBVTT\n");
+    } else {
+        for line in &headers.header {
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+    output.push('\n');
+
+    let write_blocks = |output: &mut String, position: usize| {
+        for block in headers.blocks.iter().filter(|b| b.position == position) {
+            output.push_str(&block.content);
+            output.push_str("\n\n");
+        }
+    };
+
+    for (i, entry) in file.entries.iter().enumerate() {
+        write_blocks(&mut output, i);
+
+        let meta = entry.metadata.as_ref();
+
+        if let Some(identifier) = meta.and_then(|m| m.identifier.as_ref()) {
+            output.push_str(identifier);
+            output.push('\n');
+        }
+
+        output.push_str(&format!(
+            "{} --> {}",
+            entry.start_time.to_string_for(&SubtitleFormat::Vtt),
+            entry.end_time.to_string_for(&SubtitleFormat::Vtt)
+        ));
+        if let Some(settings) = meta.and_then(|m| m.cue_settings.as_ref()) {
+            output.push(' ');
+            output.push_str(settings);
+        }
+        output.push('\n');
+
+        output.push_str(&entry.text);
+        output.push_str("\n\n");
+    }
+
+    // Blocos após o último cue (ou posições além do número de entradas)
+    for block in headers
+        .blocks
+        .iter()
+        .filter(|b| b.position >= file.entries.len())
+    {
+        output.push_str(&block.content);
+        output.push_str("\n\n");
+    }
+
+    output.trim_end().to_string() + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"WEBVTT - Episode 1
+Kind: captions
+Language: en
+
+STYLE
+::cue(.yellow) {
+  color: yellow;
+}
+
+NOTE This is a comment
+spanning two lines
+
+intro
+00:00:01.000 --> 00:00:04.000 position:10% line:0 align:start
+<v Roger>Hello <i>World</i></v>
+
+00:05.000 --> 00:08.000
+<c.yellow>This is a test</c>
+with <b>multiple</b> lines
+
+NOTE trailing note
+"#;
+
+    #[test]
+    fn test_parse_vtt() {
+        let result = parse(SAMPLE).unwrap();
+        assert_eq!(result.format, SubtitleFormat::Vtt);
+        assert_eq!(result.entries.len(), 2);
+
+        let first = &result.entries[0];
+        assert_eq!(first.start_time.to_string(), "00:00:01.000");
+        assert_eq!(first.end_time.as_millis(), 4000);
+        assert_eq!(first.text, "<v Roger>Hello <i>World</i></v>");
+        let meta = first.metadata.as_ref().unwrap();
+        assert_eq!(meta.identifier.as_deref(), Some("intro"));
+        assert_eq!(
+            meta.cue_settings.as_deref(),
+            Some("position:10% line:0 align:start")
+        );
+
+        let second = &result.entries[1];
+        assert_eq!(second.start_time.to_string(), "00:05.000");
+        assert_eq!(
+            second.text,
+            "<c.yellow>This is a test</c>\nwith <b>multiple</b> lines"
+        );
+
+        let headers = result.vtt_headers.as_ref().unwrap();
+        assert_eq!(headers.header[0], "WEBVTT - Episode 1");
+        assert_eq!(headers.blocks.len(), 3);
+        assert_eq!(headers.blocks[0].kind, VttBlockKind::Style);
+        assert_eq!(headers.blocks[2].position, 2);
+    }
+
+    #[test]
+    fn test_vtt_round_trip() {
+        let result = parse(SAMPLE).unwrap();
+        assert_eq!(serialize(&result), SAMPLE);
+    }
+
+    #[test]
+    fn test_parse_reports_invalid_cues() {
+        let content = "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nOk\n\njust text\n\nid\n00:00:0x.000 --> 00:00:04.000\nBad\n";
+        let result = parse(content).unwrap();
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(
+            result.diagnostics,
+            vec![
+                ParseDiagnostic::new(6, "Block has no timing line", "just text"),
+                ParseDiagnostic::new(
+                    9,
+                    "Invalid timing line: 00:00:0x.000 --> 00:00:04.000",
+                    "id\n00:00:0x.000 --> 00:00:04.000\nBad"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_vtt_requires_header() {
+        assert!(parse("00:00:01.000 --> 00:00:02.000\nHello\n").is_err());
+        assert!(parse("WEBVTTX\n\n00:00:01.000 --> 00:00:02.000\nHello\n").is_err());
+    }
+}
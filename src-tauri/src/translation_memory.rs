@@ -0,0 +1,359 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Limite de entradas guardadas; as usadas há mais tempo saem primeiro
+const MAX_ENTRIES: usize = 100_000;
+
+/// Similaridade mínima (Dice sobre trigramas) para uma entrada virar sugestão
+const MIN_FUZZY_SIMILARITY: f64 = 0.6;
+
+/// Tradução já feita de uma linha, reaproveitada entre execuções
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryEntry {
+    pub source: String,
+    pub translation: String,
+    pub model: String,
+    /// Hash do prompt de tradução (o prompt inteiro não é guardado)
+    pub prompt_hash: String,
+    /// Último uso em segundos desde UNIX_EPOCH
+    #[serde(default)]
+    pub last_used: u64,
+}
+
+/// Memória de tradução persistida no app data dir
+/// A chave combina texto original, prompt e modelo: trocar o idioma alvo ou
+/// o modelo não reaproveita traduções feitas com outra configuração
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranslationMemory {
+    entries: HashMap<String, MemoryEntry>,
+}
+
+impl TranslationMemory {
+    /// Carrega a memória do arquivo (vazia se ainda não existir)
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read translation memory: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse translation memory: {}", e))
+    }
+
+    /// Grava a memória, descartando o excesso acima de MAX_ENTRIES
+    pub fn save(&mut self, path: &Path) -> Result<(), String> {
+        self.prune(MAX_ENTRIES);
+
+        let content = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize translation memory: {}", e))?;
+        fs::write(path, content).map_err(|e| format!("Failed to write translation memory: {}", e))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Tradução guardada para o texto com este prompt e modelo
+    pub fn lookup(&mut self, model: &str, prompt: &str, source: &str) -> Option<String> {
+        let key = memory_key(model, prompt, source)?;
+        let entry = self.entries.get_mut(&key)?;
+        if entry.source != source.trim() {
+            return None;
+        }
+        entry.last_used = now_secs();
+        Some(entry.translation.clone())
+    }
+
+    /// Guarda (ou atualiza) a tradução de um texto
+    pub fn record(&mut self, model: &str, prompt: &str, source: &str, translation: &str) {
+        let Some(key) = memory_key(model, prompt, source) else {
+            return;
+        };
+        if translation.trim().is_empty() {
+            return;
+        }
+
+        self.entries.insert(
+            key,
+            MemoryEntry {
+                source: source.trim().to_string(),
+                translation: translation.to_string(),
+                model: model.to_string(),
+                prompt_hash: format!("{:016x}", fnv1a(prompt.as_bytes())),
+                last_used: now_secs(),
+            },
+        );
+    }
+
+    /// Guarda as traduções de um lote, casando cada índice com o texto original
+    pub fn record_all(
+        &mut self,
+        model: &str,
+        prompt: &str,
+        sources: &[(usize, String)],
+        translations: &[(usize, String)],
+    ) {
+        let sources: HashMap<usize, &str> = sources
+            .iter()
+            .map(|(idx, text)| (*idx, text.as_str()))
+            .collect();
+        for (idx, translation) in translations {
+            if let Some(source) = sources.get(idx) {
+                self.record(model, prompt, source, translation);
+            }
+        }
+    }
+
+    /// Índice para busca aproximada nas entradas deste prompt e modelo
+    /// É uma cópia: traduções registradas depois não aparecem nele
+    pub fn fuzzy_index(&self, model: &str, prompt: &str) -> FuzzyIndex {
+        let prompt_hash = format!("{:016x}", fnv1a(prompt.as_bytes()));
+        let mut index = FuzzyIndex::default();
+        for entry in self.entries.values() {
+            if entry.model == model && entry.prompt_hash == prompt_hash {
+                index.insert(&entry.source, &entry.translation);
+            }
+        }
+        index
+    }
+
+    fn prune(&mut self, max_entries: usize) {
+        if self.entries.len() <= max_entries {
+            return;
+        }
+
+        let mut by_age: Vec<(u64, String)> = self
+            .entries
+            .iter()
+            .map(|(key, entry)| (entry.last_used, key.clone()))
+            .collect();
+        by_age.sort();
+
+        let excess = self.entries.len() - max_entries;
+        for (_, key) in by_age.into_iter().take(excess) {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// Tradução parecida encontrada na memória
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub source: String,
+    pub translation: String,
+    /// 0.0 a 1.0 (1.0 = mesmo texto ignorando caixa e pontuação)
+    pub similarity: f64,
+}
+
+/// Índice invertido de trigramas sobre as entradas da memória
+#[derive(Debug, Default)]
+pub struct FuzzyIndex {
+    entries: Vec<(String, String, usize)>,
+    postings: HashMap<String, Vec<usize>>,
+}
+
+impl FuzzyIndex {
+    fn insert(&mut self, source: &str, translation: &str) {
+        let grams = trigrams(source);
+        if grams.is_empty() {
+            return;
+        }
+
+        let id = self.entries.len();
+        self.entries
+            .push((source.to_string(), translation.to_string(), grams.len()));
+        for gram in grams {
+            self.postings.entry(gram).or_default().push(id);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// As `limit` entradas mais parecidas com o texto, da mais próxima para a
+    /// menos; o próprio texto (acerto exato) não entra
+    pub fn matches(&self, source: &str, limit: usize) -> Vec<FuzzyMatch> {
+        let grams = trigrams(source);
+        if grams.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for gram in &grams {
+            for id in self.postings.get(gram).into_iter().flatten() {
+                *shared.entry(*id).or_default() += 1;
+            }
+        }
+
+        let source = source.trim();
+        let mut matches: Vec<FuzzyMatch> = shared
+            .into_iter()
+            .filter_map(|(id, count)| {
+                let (entry_source, translation, gram_count) = &self.entries[id];
+                let similarity = 2.0 * count as f64 / (grams.len() + gram_count) as f64;
+                (similarity >= MIN_FUZZY_SIMILARITY && entry_source != source).then(|| FuzzyMatch {
+                    source: entry_source.clone(),
+                    translation: translation.clone(),
+                    similarity,
+                })
+            })
+            .collect();
+
+        matches.sort_by(|a, b| {
+            b.similarity
+                .total_cmp(&a.similarity)
+                .then_with(|| a.source.cmp(&b.source))
+        });
+        matches.truncate(limit);
+        matches
+    }
+}
+
+/// Trigramas de caracteres do texto normalizado (minúsculas, sem pontuação)
+fn trigrams(text: &str) -> HashSet<String> {
+    let normalized = text
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .to_lowercase();
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+    if words.is_empty() {
+        return HashSet::new();
+    }
+
+    let padded: Vec<char> = format!("  {} ", words.join(" ")).chars().collect();
+    padded
+        .windows(3)
+        .map(|w| w.iter().collect::<String>())
+        .collect()
+}
+
+/// Chave estável entre versões: FNV-1a de modelo, prompt e texto
+/// Espaços nas pontas do texto são ignorados; textos vazios não entram
+fn memory_key(model: &str, prompt: &str, source: &str) -> Option<String> {
+    let source = source.trim();
+    if source.is_empty() {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(model.len() + prompt.len() + source.len() + 2);
+    bytes.extend_from_slice(model.as_bytes());
+    bytes.push(0);
+    bytes.extend_from_slice(prompt.as_bytes());
+    bytes.push(0);
+    bytes.extend_from_slice(source.as_bytes());
+    Some(format!("{:016x}", fnv1a(&bytes)))
+}
+
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_is_scoped_by_prompt_and_model() {
+        let mut memory = TranslationMemory::default();
+        memory.record(
+            "gpt",
+            "To Portuguese",
+            "Previously on...",
+            "Anteriormente...",
+        );
+
+        assert_eq!(
+            memory.lookup("gpt", "To Portuguese", " Previously on... "),
+            Some("Anteriormente...".to_string())
+        );
+        assert_eq!(memory.lookup("gpt", "To Spanish", "Previously on..."), None);
+        assert_eq!(
+            memory.lookup("claude", "To Portuguese", "Previously on..."),
+            None
+        );
+
+        memory.record("gpt", "To Portuguese", "   ", "ignored");
+        assert_eq!(memory.len(), 1);
+    }
+
+    #[test]
+    fn test_fuzzy_matches_within_scope() {
+        let mut memory = TranslationMemory::default();
+        memory.record(
+            "gpt",
+            "pt",
+            "Where are you going, Asuna?",
+            "Aonde você vai, Asuna?",
+        );
+        memory.record(
+            "gpt",
+            "pt",
+            "The weather is nice today",
+            "O tempo está bom hoje",
+        );
+        memory.record(
+            "gpt",
+            "es",
+            "Where are you going, Kirito?",
+            "¿Adónde vas, Kirito?",
+        );
+
+        let index = memory.fuzzy_index("gpt", "pt");
+        let matches = index.matches("Where are you going, Kirito?", 3);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].translation, "Aonde você vai, Asuna?");
+        assert!(matches[0].similarity < 1.0);
+
+        let matches = index.matches("where are you going asuna", 3);
+        assert_eq!(matches[0].similarity, 1.0);
+        assert!(index.matches("Where are you going, Asuna?", 3).is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load_prunes_oldest() {
+        let mut memory = TranslationMemory::default();
+        let sources = vec![(1, "One".to_string()), (2, "Two".to_string())];
+        let translations = vec![(1, "Um".to_string()), (2, "Dois".to_string())];
+        memory.record_all("gpt", "pt", &sources, &translations);
+        memory.entries.values_mut().for_each(|e| {
+            e.last_used = if e.source == "One" { 1 } else { 2 };
+        });
+        memory.prune(1);
+
+        let path = std::env::temp_dir().join(format!(
+            "translation_memory_test_{}.json",
+            std::process::id()
+        ));
+        memory.save(&path).unwrap();
+        let mut loaded = TranslationMemory::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.lookup("gpt", "pt", "One"), None);
+        assert_eq!(loaded.lookup("gpt", "pt", "Two"), Some("Dois".to_string()));
+    }
+}
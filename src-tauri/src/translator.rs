@@ -2,12 +2,17 @@ use futures::future::join_all;
 use futures::StreamExt;
 use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
-    Arc,
+    Arc, Mutex,
 };
 
+use crate::glossary::{self, GlossaryTerm};
+use crate::retry::{ApiError, RetryPolicy};
+use crate::subtitle::SubtitleFile;
+use crate::translation_memory::{FuzzyIndex, FuzzyMatch, TranslationMemory};
+
 pub const TRANSLATION_CANCELLED_ERROR: &str = "Translation cancelled";
 
 /// LLM API format
@@ -142,14 +147,57 @@ pub struct TranslationSettings {
     pub max_retries: usize,
     #[serde(default)]
     pub streaming: bool,
+    /// Não envia linhas Comment (ASS) para tradução
+    #[serde(default)]
+    pub skip_comments: bool,
+    /// Reaproveita traduções já feitas (memória de tradução persistente)
+    #[serde(default = "default_use_translation_memory")]
+    pub use_translation_memory: bool,
+    /// Linhas vizinhas enviadas só como contexto antes e depois de cada batch
+    #[serde(default = "default_context_lines")]
+    pub context_lines: usize,
+    /// Gera uma sinopse do arquivo antes de traduzir e a envia em cada batch
+    #[serde(default)]
+    pub synopsis: bool,
+    /// Espera antes do primeiro retry; dobra a cada tentativa (com jitter)
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub retry_base_delay_ms: u64,
+    /// Teto da espera entre tentativas (Retry-After do servidor pode passar dele)
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub retry_max_delay_ms: u64,
+}
+
+impl TranslationSettings {
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            base_delay: std::time::Duration::from_millis(self.retry_base_delay_ms),
+            max_delay: std::time::Duration::from_millis(self.retry_max_delay_ms),
+        }
+    }
 }
 
 fn default_parallel_requests() -> usize {
     1
 }
 
+fn default_use_translation_memory() -> bool {
+    true
+}
+
+fn default_context_lines() -> usize {
+    3
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    1000
+}
+
+fn default_retry_max_delay_ms() -> u64 {
+    60_000
+}
+
 /// Removes <think>...</think> blocks from LLM responses
-fn strip_think_blocks(input: &str) -> String {
+pub(crate) fn strip_think_blocks(input: &str) -> String {
     let mut output = input.to_string();
     loop {
         let Some(start) = output.find("<think>") else {
@@ -173,6 +221,12 @@ impl Default for TranslationSettings {
             continue_on_error: false,
             max_retries: 3,
             streaming: false,
+            skip_comments: false,
+            use_translation_memory: true,
+            context_lines: default_context_lines(),
+            synopsis: false,
+            retry_base_delay_ms: default_retry_base_delay_ms(),
+            retry_max_delay_ms: default_retry_max_delay_ms(),
         }
     }
 }
@@ -211,6 +265,17 @@ pub struct TranslationBatchReport {
     pub translations: Vec<(usize, String)>,
     pub progress: TranslationProgress,
     pub error_message: Option<String>,
+    /// Entradas que ficaram sem tradução, com o motivo de cada uma
+    pub untranslated: Vec<UntranslatedEntry>,
+}
+
+/// Entrada que ficou sem tradução ao fim do processo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntranslatedEntry {
+    pub index: usize,
+    pub text: String,
+    pub reason: String,
 }
 
 /// Result of a batch translation operation
@@ -219,6 +284,9 @@ pub struct TranslationBatchReport {
 pub struct BatchTranslationResult {
     pub translations: Vec<(usize, String)>,
     pub progress: TranslationProgress,
+    /// Linhas do lote que o modelo não devolveu nem depois de pedidas de novo
+    #[serde(default)]
+    pub untranslated: Vec<UntranslatedEntry>,
 }
 
 /// Modelo disponível na API
@@ -339,6 +407,194 @@ pub struct TranslatedEntryEvent {
 /// Placeholder for newlines in subtitle text during translation
 const NEWLINE_PLACEHOLDER: &str = "<<NEWLINE>>";
 
+/// Sugestões da memória de tradução por linha do batch
+const FUZZY_HINTS_PER_LINE: usize = 2;
+
+/// Limite de sugestões enviadas junto com um batch
+const MAX_FUZZY_HINTS_PER_BATCH: usize = 20;
+
+/// Motivo informado para linhas que o modelo nunca devolveu
+const MISSING_FROM_RESPONSE: &str = "Missing from model response";
+
+/// Tamanho máximo (em caracteres) de cada parte do arquivo resumida na sinopse
+const SYNOPSIS_CHUNK_CHARS: usize = 40_000;
+
+/// Prefixo das linhas enviadas só como contexto
+const CONTEXT_LINE_PREFIX: &str = ">>";
+const CONTEXT_BEFORE_HEADER: &str = "[CONTEXT BEFORE - DO NOT TRANSLATE]";
+const CONTEXT_AFTER_HEADER: &str = "[CONTEXT AFTER - DO NOT TRANSLATE]";
+const CONTEXT_END_MARKER: &str = "[END CONTEXT]";
+
+/// Material de apoio enviado junto com um batch, fora das linhas a traduzir
+#[derive(Debug, Clone, Default)]
+pub struct BatchContext {
+    /// Traduções parecidas da memória (referência de consistência)
+    pub hints: Vec<FuzzyMatch>,
+    /// Linhas anteriores ao batch: original e tradução, se já existir
+    pub before: Vec<(String, Option<String>)>,
+    /// Linhas seguintes ao batch (só o original)
+    pub after: Vec<String>,
+}
+
+impl BatchContext {
+    fn has_neighbours(&self) -> bool {
+        !self.before.is_empty() || !self.after.is_empty()
+    }
+}
+
+/// Linhas de contexto (e seus cabeçalhos) nunca são lidas como tradução
+fn is_context_line(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with(CONTEXT_LINE_PREFIX)
+        || line.starts_with(CONTEXT_BEFORE_HEADER)
+        || line.starts_with(CONTEXT_AFTER_HEADER)
+        || line.starts_with(CONTEXT_END_MARKER)
+}
+
+/// Converte \N (ASS) e \n (real) para o placeholder de quebra de linha
+fn encode_line_breaks(text: &str) -> String {
+    text.replace("\\N", NEWLINE_PLACEHOLDER)
+        .replace("\\n", NEWLINE_PLACEHOLDER)
+        .replace('\n', NEWLINE_PLACEHOLDER)
+}
+
+/// Conteúdo enviado ao modelo: contexto anterior, linhas INDEX|TEXTO do
+/// batch e contexto seguinte
+fn format_batch_payload(
+    entries: &[(usize, String)],
+    context: &BatchContext,
+    labels: &HashMap<usize, String>,
+) -> String {
+    let mut sections = Vec::new();
+
+    if !context.before.is_empty() {
+        let mut block = vec![CONTEXT_BEFORE_HEADER.to_string()];
+        for (source, translation) in &context.before {
+            block.push(match translation {
+                Some(translation) => format!(
+                    "{} {} => {}",
+                    CONTEXT_LINE_PREFIX,
+                    encode_line_breaks(source),
+                    encode_line_breaks(translation)
+                ),
+                None => format!("{} {}", CONTEXT_LINE_PREFIX, encode_line_breaks(source)),
+            });
+        }
+        block.push(CONTEXT_END_MARKER.to_string());
+        sections.push(block.join("\n"));
+    }
+
+    sections.push(
+        entries
+            .iter()
+            .map(|(idx, text)| format_payload_line(*idx, &encode_line_breaks(text), labels))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+
+    if !context.after.is_empty() {
+        let mut block = vec![CONTEXT_AFTER_HEADER.to_string()];
+        for source in &context.after {
+            block.push(format!("{} {}", CONTEXT_LINE_PREFIX, encode_line_breaks(source)));
+        }
+        block.push(CONTEXT_END_MARKER.to_string());
+        sections.push(block.join("\n"));
+    }
+
+    sections.join("\n\n")
+}
+
+/// Linha INDEX|TEXTO, com INDEX|[FALANTE/ESTILO]|TEXTO quando há rótulo
+fn format_payload_line(idx: usize, text: &str, labels: &HashMap<usize, String>) -> String {
+    match labels.get(&idx) {
+        Some(label) => format!("{}|[{}]|{}", idx, label, text),
+        None => format!("{}|{}", idx, text),
+    }
+}
+
+/// Rótulo FALANTE/ESTILO de cada entrada traduzível, a partir do campo
+/// Name (actor) e do estilo do ASS; o estilo "Default" não diz nada e é omitido
+pub fn speaker_labels(file: &SubtitleFile) -> HashMap<usize, String> {
+    file.entries
+        .iter()
+        .filter_map(|entry| {
+            let metadata = entry.metadata.as_ref()?;
+            let name = metadata.name.as_deref().map(str::trim).unwrap_or("");
+            let style = metadata
+                .style
+                .as_deref()
+                .map(str::trim)
+                .filter(|style| !style.eq_ignore_ascii_case("default"))
+                .unwrap_or("");
+            match (name.is_empty(), style.is_empty()) {
+                (true, true) => None,
+                (false, true) => Some((entry.index, name.to_string())),
+                _ => Some((entry.index, format!("{}/{}", name, style))),
+            }
+        })
+        .collect()
+}
+
+/// Remove o rótulo [FALANTE/ESTILO]| caso o modelo repita o que foi enviado
+/// para a linha; texto que só se parece com um rótulo fica intacto
+fn strip_speaker_label<'a>(text: &'a str, label: Option<&str>) -> &'a str {
+    label
+        .and_then(|label| text.strip_prefix('[')?.strip_prefix(label)?.strip_prefix("]|"))
+        .unwrap_or(text)
+}
+
+/// Explicação dos rótulos de falante quando o batch os contém
+fn format_speaker_rule(entries: &[(usize, String)], labels: &HashMap<usize, String>) -> String {
+    if !entries.iter().any(|(idx, _)| labels.contains_key(idx)) {
+        return String::new();
+    }
+
+    "\n\nSPEAKERS: Input lines may look like INDEX|[SPEAKER/STYLE]|TEXT. The bracketed tag names who is speaking and the subtitle style (either part may be empty). Use it to choose formal or informal address and gendered forms, but do NOT include the tag in your output: keep answering INDEX|TRANSLATED_TEXT.".to_string()
+}
+
+/// Regra extra das instruções quando o batch leva linhas de contexto
+fn format_context_rule(context: &BatchContext) -> String {
+    if !context.has_neighbours() {
+        return String::new();
+    }
+
+    format!(
+        "\n5. Lines between {} / {} and {} (prefixed with {}) are the neighbouring dialogue, for reference only: use them for pronouns, gender agreement and running jokes, but do NOT translate or output them.",
+        CONTEXT_BEFORE_HEADER, CONTEXT_AFTER_HEADER, CONTEXT_END_MARKER, CONTEXT_LINE_PREFIX
+    )
+}
+
+/// Contexto de um batch: até `context_lines` vizinhas de cada lado na ordem
+/// do arquivo, com as traduções anteriores que já estiverem prontas
+fn build_batch_context(
+    entries: &[(usize, String)],
+    positions: &HashMap<usize, usize>,
+    batch: &[(usize, String)],
+    known: &HashMap<usize, String>,
+    context_lines: usize,
+    hints: &[FuzzyMatch],
+) -> BatchContext {
+    let mut context = BatchContext {
+        hints: hints.to_vec(),
+        ..Default::default()
+    };
+    let first = batch.first().and_then(|(idx, _)| positions.get(idx));
+    let last = batch.last().and_then(|(idx, _)| positions.get(idx));
+    let (Some(&first), Some(&last)) = (first, last) else {
+        return context;
+    };
+
+    context.before = entries[first.saturating_sub(context_lines)..first]
+        .iter()
+        .map(|(idx, text)| (text.clone(), known.get(idx).cloned()))
+        .collect();
+    context.after = entries[last + 1..(last + 1 + context_lines).min(entries.len())]
+        .iter()
+        .map(|(_, text)| text.clone())
+        .collect();
+    context
+}
+
 fn is_cancelled(cancel_flag: &Option<Arc<AtomicBool>>) -> bool {
     cancel_flag
         .as_ref()
@@ -353,16 +609,111 @@ fn check_cancelled(cancel_flag: &Option<Arc<AtomicBool>>) -> Result<(), String>
     Ok(())
 }
 
+/// Resultado da divisão de um batch que falhou repetidamente
+struct BisectionOutcome {
+    translations: Vec<(usize, String)>,
+    /// Linhas que o modelo não devolveu mesmo depois de pedidas de novo
+    missing: Vec<usize>,
+    /// Linhas que falharam sozinhas, com o último erro
+    failed: Vec<(usize, String)>,
+    /// Parou num erro definitivo (401, 400...): as linhas restantes estão em
+    /// `failed` sem terem sido tentadas
+    fatal: bool,
+}
+
+/// Linhas do batch que não vieram na resposta, agrupadas em trechos contíguos
+fn missing_runs(
+    batch: &[(usize, String)],
+    translations: &[(usize, String)],
+) -> Vec<Vec<(usize, String)>> {
+    let returned: HashSet<usize> = translations.iter().map(|(idx, _)| *idx).collect();
+    let mut runs: Vec<Vec<(usize, String)>> = Vec::new();
+    let mut previous_missing = false;
+    for entry in batch {
+        let missing = !returned.contains(&entry.0);
+        if missing {
+            match runs.last_mut() {
+                Some(run) if previous_missing => run.push(entry.clone()),
+                _ => runs.push(vec![entry.clone()]),
+            }
+        }
+        previous_missing = missing;
+    }
+    runs
+}
+
+/// Sugestões da memória para as linhas do batch, sem repetir o mesmo original
+fn collect_fuzzy_hints(index: &FuzzyIndex, batch: &[(usize, String)]) -> Vec<FuzzyMatch> {
+    let mut hints: Vec<FuzzyMatch> = Vec::new();
+    for (_, text) in batch {
+        for hint in index.matches(text, FUZZY_HINTS_PER_LINE) {
+            if !hints.iter().any(|h| h.source == hint.source) {
+                hints.push(hint);
+            }
+        }
+    }
+    hints.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    hints.truncate(MAX_FUZZY_HINTS_PER_BATCH);
+    hints
+}
+
+/// Bloco de referência com traduções anteriores, anexado às instruções
+fn format_reference_hints(hints: &[FuzzyMatch]) -> String {
+    if hints.is_empty() {
+        return String::new();
+    }
+
+    let examples = hints
+        .iter()
+        .map(|hint| {
+            format!(
+                "ORIGINAL: {}\nTRANSLATION: {}",
+                hint.source.replace('\n', NEWLINE_PLACEHOLDER),
+                hint.translation.replace('\n', NEWLINE_PLACEHOLDER)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    format!(
+        r#"
+
+---
+REFERENCE TRANSLATIONS (similar lines translated earlier in this series):
+Use them to keep names, terms and phrasing consistent. They are NOT part of the input: do NOT include them in your output.
+
+{}"#,
+        examples
+    )
+}
+
+/// Entrega ao streaming as traduções finais de um batch, que substituem o
+/// que foi mostrado enquanto a resposta chegava
+fn emit_entries(on_entry: &mut impl FnMut(TranslatedEntryEvent), translations: &[(usize, String)]) {
+    for (index, text) in translations {
+        on_entry(TranslatedEntryEvent {
+            index: *index,
+            text: text.clone(),
+        });
+    }
+}
+
 /// Parses a translation line in the format "INDEX|TEXT" and returns (index, translated_text)
-fn parse_translation_line(line: &str, placeholder: &str) -> Option<(usize, String)> {
-    if line.is_empty() || line.starts_with("```") {
+/// The speaker label sent for the index is removed if the model echoes it
+fn parse_translation_line(
+    line: &str,
+    placeholder: &str,
+    labels: &HashMap<usize, String>,
+) -> Option<(usize, String)> {
+    if line.is_empty() || line.starts_with("```") || is_context_line(line) {
         return None;
     }
 
     let sep_pos = line.find('|')?;
     let idx_str = &line[..sep_pos];
     let idx = idx_str.trim().parse::<usize>().ok()?;
-    let text = line[sep_pos + 1..]
+    let label = labels.get(&idx).map(String::as_str);
+    let text = strip_speaker_label(&line[sep_pos + 1..], label)
         .replace(placeholder, "\n")
         .replace("\\N", "\n")
         .replace("\\n", "\n");
@@ -374,6 +725,10 @@ fn parse_translation_line(line: &str, placeholder: &str) -> Option<(usize, Strin
 pub struct LlmClient {
     client: Client,
     config: LlmConfig,
+    glossary: Vec<GlossaryTerm>,
+    synopsis: Option<String>,
+    speaker_labels: HashMap<usize, String>,
+    retry_policy: RetryPolicy,
 }
 
 impl LlmClient {
@@ -384,9 +739,56 @@ impl LlmClient {
         Self {
             client: Client::new(),
             config,
+            glossary: Vec::new(),
+            synopsis: None,
+            speaker_labels: HashMap::new(),
+            retry_policy: RetryPolicy::default(),
         }
     }
 
+    /// Espera entre tentativas (ver `TranslationSettings::retry_policy`)
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Rótulos FALANTE/ESTILO enviados junto de cada linha (ver `speaker_labels`)
+    pub fn with_speaker_labels(mut self, labels: HashMap<usize, String>) -> Self {
+        self.speaker_labels = labels;
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.config.model
+    }
+
+    /// Sinopse do arquivo enviada antes das instruções de cada batch
+    pub fn with_synopsis(mut self, synopsis: Option<String>) -> Self {
+        self.synopsis = synopsis;
+        self
+    }
+
+    fn prompt_with_synopsis(&self, system_prompt: &str) -> String {
+        match &self.synopsis {
+            Some(synopsis) => format!(
+                "EPISODE SYNOPSIS (background for the translator, do NOT translate or output it):\n{}\n\n---\n{}",
+                synopsis, system_prompt
+            ),
+            None => system_prompt.to_string(),
+        }
+    }
+
+    /// Glossário aplicado aos batches: cada batch recebe só os termos que
+    /// aparecem nas suas linhas
+    pub fn with_glossary(mut self, glossary: Vec<GlossaryTerm>) -> Self {
+        self.glossary = glossary;
+        self
+    }
+
+    fn glossary_block(&self, batch: &[(usize, String)]) -> String {
+        glossary::format_prompt_block(&glossary::terms_in_batch(&self.glossary, batch))
+    }
+
     fn extract_ass_tags(text: &str) -> Vec<String> {
         let mut tags = Vec::new();
         let mut rest = text;
@@ -518,6 +920,17 @@ impl LlmClient {
         system_prompt: &str,
         subtitle_content: &str,
     ) -> Result<String, String> {
+        self.request(system_prompt, subtitle_content)
+            .await
+            .map_err(String::from)
+    }
+
+    /// Como `translate`, mas mantém a classificação do erro para o retry
+    async fn request(
+        &self,
+        system_prompt: &str,
+        subtitle_content: &str,
+    ) -> Result<String, ApiError> {
         match self.config.api_format {
             ApiFormat::Anthropic => {
                 self.translate_anthropic(system_prompt, subtitle_content)
@@ -534,7 +947,7 @@ impl LlmClient {
         &self,
         system_prompt: &str,
         subtitle_content: &str,
-    ) -> Result<String, String> {
+    ) -> Result<String, ApiError> {
         // Junta prompt e conteúdo em uma única mensagem (formato do projeto original)
         let full_content = if subtitle_content.is_empty() {
             system_prompt.to_string()
@@ -567,24 +980,29 @@ impl LlmClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("Translation request failed: {}", e))?;
+            .map_err(|e| ApiError::from_request(&e, format!("Translation request failed: {}", e)))?;
 
         if !response.status().is_success() {
             let status = response.status();
+            let headers = response.headers().clone();
             let body = response.text().await.unwrap_or_default();
-            return Err(format!("Translation API error {}: {}", status, body));
+            return Err(ApiError::from_status(
+                status,
+                &headers,
+                format!("Translation API error {}: {}", status, body),
+            ));
         }
 
         let chat_response: ChatResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse translation response: {}", e))?;
+            .map_err(|e| ApiError::retryable(format!("Failed to parse translation response: {}", e)))?;
 
         let content = chat_response
             .choices
             .first()
             .map(|c| c.message.content.clone())
-            .ok_or_else(|| "No response from model".to_string())?;
+            .ok_or_else(|| ApiError::retryable("No response from model"))?;
 
         Ok(content)
     }
@@ -594,7 +1012,7 @@ impl LlmClient {
         &self,
         system_prompt: &str,
         subtitle_content: &str,
-    ) -> Result<String, String> {
+    ) -> Result<String, ApiError> {
         let user_content = if subtitle_content.is_empty() {
             system_prompt.to_string()
         } else {
@@ -642,18 +1060,23 @@ impl LlmClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("Translation request failed: {}", e))?;
+            .map_err(|e| ApiError::from_request(&e, format!("Translation request failed: {}", e)))?;
 
         if !response.status().is_success() {
             let status = response.status();
+            let headers = response.headers().clone();
             let body = response.text().await.unwrap_or_default();
-            return Err(format!("Translation API error {}: {}", status, body));
+            return Err(ApiError::from_status(
+                status,
+                &headers,
+                format!("Translation API error {}: {}", status, body),
+            ));
         }
 
         let anthropic_response: AnthropicResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse translation response: {}", e))?;
+            .map_err(|e| ApiError::retryable(format!("Failed to parse translation response: {}", e)))?;
 
         anthropic_response
             .content
@@ -665,333 +1088,256 @@ impl LlmClient {
                     None
                 }
             })
-            .ok_or_else(|| "No response from model".to_string())
+            .ok_or_else(|| ApiError::retryable("No response from model"))
     }
 
-    /// Translates a single batch with streaming (OpenAI format only)
-    /// Emits events as each entry is translated
-    async fn translate_streaming_batch(
+    /// Como `translate_subtitles_with_context`, mas com a resposta em
+    /// streaming (formato OpenAI): cada linha do batch vai para `on_entry`
+    /// assim que chega. A resposta completa é lida como no modo normal, então
+    /// linhas faltando e respostas inválidas seguem o mesmo caminho de
+    /// recuperação e divisão do batch
+    async fn stream_subtitles_with_context(
         &self,
         system_prompt: &str,
-        batch: &[(usize, String)],
-        batch_index: usize,
-        max_retries: usize,
-        cancel_flag: Option<Arc<AtomicBool>>,
-        original_map: &HashMap<usize, &str>,
-        mut on_entry: impl FnMut(TranslatedEntryEvent),
-    ) -> Result<Vec<(usize, String)>, String> {
-        let mut all_results = Vec::new();
-        let mut retries = 0;
-
-        loop {
-            check_cancelled(&cancel_flag)?;
-
-            let formatted: String = batch
-                .iter()
-                .map(|(idx, text)| {
-                    let normalized = text
-                        .replace("\\N", NEWLINE_PLACEHOLDER)
-                        .replace("\\n", NEWLINE_PLACEHOLDER)
-                        .replace('\n', NEWLINE_PLACEHOLDER);
-                    format!("{}|{}", idx, normalized)
-                })
-                .collect::<Vec<_>>()
-                .join("\n");
+        entries: &[(usize, String)],
+        context: &BatchContext,
+        cancel_flag: &Option<Arc<AtomicBool>>,
+        on_entry: &mut (dyn FnMut(TranslatedEntryEvent) + Send),
+    ) -> Result<Vec<(usize, String)>, ApiError> {
+        let (instruction, formatted) = self.batch_prompt(system_prompt, entries, context);
 
-            let instruction = format!(
-                r#"{}
+        let request = ChatRequest {
+            model: self.config.model.clone(),
+            messages: vec![ChatMessage {
+                role: "user".to_string(),
+                content: format!("{}\n\n{}", instruction, formatted),
+            }],
+            stream: Some(true),
+            reasoning_effort: self
+                .config
+                .reasoning_effort
+                .as_api_value()
+                .map(str::to_string),
+        };
 
----
-CRITICAL FORMAT INSTRUCTIONS:
-1. Return translations in EXACTLY this format: INDEX|TRANSLATED_TEXT
-2. Each subtitle must be on its own line: number|translated text
-3. The marker {} represents a LINE BREAK within a subtitle. You MUST preserve it exactly as-is in your translation.
-   Example input:  5|It's a special event{}that everyone attends
-   Example output: 5|É um evento especial{}que todos participam
-4. Do NOT remove, split, or modify {} markers - they indicate where line breaks occur in the subtitle display."#,
-                system_prompt,
-                NEWLINE_PLACEHOLDER,
-                NEWLINE_PLACEHOLDER,
-                NEWLINE_PLACEHOLDER,
-                NEWLINE_PLACEHOLDER
-            );
+        let response = self
+            .apply_headers(
+                self.client
+                    .post(&self.config.endpoint)
+                    .header("Content-Type", "application/json"),
+            )
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| ApiError::from_request(&e, format!("Translation request failed: {}", e)))?;
 
-            let full_content = format!("{}\n\n{}", instruction, formatted);
+        if !response.status().is_success() {
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.text().await.unwrap_or_default();
+            return Err(ApiError::from_status(
+                status,
+                &headers,
+                format!("Translation API error {}: {}", status, body),
+            ));
+        }
 
-            let messages = vec![ChatMessage {
-                role: "user".to_string(),
-                content: full_content,
-            }];
-
-            let request = ChatRequest {
-                model: self.config.model.clone(),
-                messages,
-                stream: Some(true),
-                reasoning_effort: self
-                    .config
-                    .reasoning_effort
-                    .as_api_value()
-                    .map(str::to_string),
-            };
-
-            let response = match self
-                .apply_headers(
-                    self.client
-                        .post(&self.config.endpoint)
-                        .header("Content-Type", "application/json"),
-                )
-                .json(&request)
-                .send()
-                .await
+        // Linhas do batch com tags compatíveis aparecem antes do fim da resposta
+        let originals: HashMap<usize, &str> =
+            entries.iter().map(|(i, s)| (*i, s.as_str())).collect();
+        let mut emit_line = |line: &str| {
+            if let Some((index, text)) =
+                parse_translation_line(line.trim(), NEWLINE_PLACEHOLDER, &self.speaker_labels)
             {
-                Ok(r) => r,
-                Err(e) => {
-                    retries += 1;
-                    if retries > max_retries {
-                        return Err(format!("Batch {}: Translation request failed after {} retries: {}", batch_index, max_retries, e));
-                    }
-                    check_cancelled(&cancel_flag)?;
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                    continue;
-                }
-            };
-
-            if !response.status().is_success() {
-                let status = response.status();
-                let body = response.text().await.unwrap_or_default();
-                retries += 1;
-                if retries > max_retries {
-                    return Err(format!("Batch {}: Translation API error {} after {} retries: {}", batch_index, status, max_retries, body));
+                if originals
+                    .get(&index)
+                    .is_some_and(|original| Self::tags_compatible(original, &text))
+                {
+                    on_entry(TranslatedEntryEvent { index, text });
                 }
-                check_cancelled(&cancel_flag)?;
-                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                continue;
             }
+        };
 
-            let mut current_text = String::new();
-            let mut buffer = String::new();
-            let mut batch_results = Vec::new();
-
-            let mut stream = response.bytes_stream();
-
-            while let Some(chunk_result) = stream.next().await {
-                if is_cancelled(&cancel_flag) {
-                    return Err(TRANSLATION_CANCELLED_ERROR.to_string());
+        let mut content = String::new();
+        // Início da primeira linha de `content` ainda não emitida
+        let mut line_start = 0;
+        let mut buffer = String::new();
+        let mut stream = response.bytes_stream();
+
+        while let Some(chunk_result) = stream.next().await {
+            if is_cancelled(cancel_flag) {
+                return Err(ApiError {
+                    retryable: false,
+                    ..ApiError::retryable(TRANSLATION_CANCELLED_ERROR)
+                });
+            }
+            let chunk =
+                chunk_result.map_err(|e| ApiError::retryable(format!("Stream error: {}", e)))?;
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+            // Process complete SSE lines
+            while let Some(newline_pos) = buffer.find('\n') {
+                let line: String = buffer.drain(..=newline_pos).collect();
+                let Some(json_str) = line.trim().strip_prefix("data: ") else {
+                    continue;
+                };
+                if json_str == "[DONE]" {
+                    continue;
                 }
-                let chunk = chunk_result.map_err(|e| format!("Stream error: {}", e))?;
-                let chunk_str = String::from_utf8_lossy(&chunk);
-                buffer.push_str(&chunk_str);
 
-                // Process complete SSE lines
-                while let Some(newline_pos) = buffer.find('\n') {
-                    let line = buffer[..newline_pos].trim().to_string();
-                    buffer = buffer[newline_pos + 1..].to_string();
-
-                    if line.is_empty() || line == "data: [DONE]" {
-                        continue;
-                    }
-
-                    if let Some(json_str) = line.strip_prefix("data: ") {
-                        match serde_json::from_str::<StreamChunk>(json_str) {
-                            Ok(chunk) => {
-                                for choice in chunk.choices {
-                                    if let Some(content) = choice.delta.content {
-                                        // Process received content character by character
-                                        for ch in content.chars() {
-                                            if ch == '\n' {
-                                                // End of a line - try to parse
-                                                let line_content =
-                                                    current_text.trim().to_string();
-                                                if let Some((idx, text)) =
-                                                    parse_translation_line(
-                                                        &line_content,
-                                                        NEWLINE_PLACEHOLDER,
-                                                    )
-                                                {
-                                                    // Validate ASS tag compatibility
-                                                    let should_emit = original_map
-                                                        .get(&idx)
-                                                        .map(|orig| {
-                                                            Self::tags_compatible(orig, &text)
-                                                        })
-                                                        .unwrap_or(true);
-
-                                                    if should_emit {
-                                                        on_entry(TranslatedEntryEvent {
-                                                            index: idx,
-                                                            text: text.clone(),
-                                                        });
-                                                        batch_results.push((idx, text));
-                                                    }
-                                                }
-                                                current_text.clear();
-                                            } else {
-                                                current_text.push(ch);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                #[cfg(debug_assertions)]
-                                eprintln!(
-                                    "Failed to parse SSE chunk: {} - JSON: {}",
-                                    e, json_str
-                                );
-                                let _ = e;
+                match serde_json::from_str::<StreamChunk>(json_str) {
+                    Ok(chunk) => {
+                        for delta in chunk.choices.into_iter().filter_map(|c| c.delta.content) {
+                            content.push_str(&delta);
+                            while let Some(end) = content[line_start..].find('\n') {
+                                emit_line(&content[line_start..line_start + end]);
+                                line_start += end + 1;
                             }
                         }
                     }
+                    Err(e) => {
+                        #[cfg(debug_assertions)]
+                        eprintln!("Failed to parse SSE chunk: {} - JSON: {}", e, json_str);
+                        let _ = e;
+                    }
                 }
             }
-
-            check_cancelled(&cancel_flag)?;
-
-            // Process last line of the batch if any
-            let line_content = current_text.trim().to_string();
-            if let Some((idx, text)) =
-                parse_translation_line(&line_content, NEWLINE_PLACEHOLDER)
-            {
-                let should_emit = original_map
-                    .get(&idx)
-                    .map(|orig| Self::tags_compatible(orig, &text))
-                    .unwrap_or(true);
-
-                if should_emit {
-                    on_entry(TranslatedEntryEvent {
-                        index: idx,
-                        text: text.clone(),
-                    });
-                    batch_results.push((idx, text));
-                }
-            }
-
-            all_results.extend(batch_results);
-            break;
         }
 
-        Ok(all_results)
+        if content.trim().is_empty() {
+            return Err(ApiError::retryable("No response from model"));
+        }
+        self.parse_batch_response(&content, entries)
     }
 
-    /// Translates subtitles with streaming (OpenAI format only)
-    /// Emits events as each entry is translated
-    /// Uses batching to process in smaller groups with parallel execution
-    #[allow(clippy::too_many_arguments)]
-    pub async fn translate_subtitles_streaming(
+    /// Uma tentativa de traduzir o batch; com `on_entry` a resposta vem em
+    /// streaming, exceto no formato Anthropic, que usa a requisição normal
+    async fn translate_batch_attempt(
         &self,
         system_prompt: &str,
-        entries: &[(usize, String)],
-        batch_size: usize,
-        parallel_requests: usize,
-        max_retries: usize,
-        cancel_flag: Option<Arc<AtomicBool>>,
-        on_entry: impl FnMut(TranslatedEntryEvent) + Send + Clone,
-    ) -> Result<Vec<(usize, String)>, String> {
-        // Create map of original indices for ASS tag validation
-        let original_map: HashMap<usize, &str> =
-            entries.iter().map(|(i, s)| (*i, s.as_str())).collect();
-        let parallel_requests = parallel_requests.max(1);
-
-        // Divide entries em batches
-        let batches: Vec<Vec<(usize, String)>> = entries
-            .chunks(batch_size)
-            .map(|chunk| chunk.to_vec())
-            .collect();
-
-        let total_batches = batches.len();
-        let mut batch_results: Vec<Option<Vec<(usize, String)>>> = vec![None; total_batches];
-        let mut current_batch_group = 0;
-
-        // Processa batches em grupos de parallel_requests
-        check_cancelled(&cancel_flag)?;
-        while current_batch_group * parallel_requests < total_batches {
-            check_cancelled(&cancel_flag)?;
-            let start_idx = current_batch_group * parallel_requests;
-            let end_idx = (start_idx + parallel_requests).min(total_batches);
-
-            // Prepara futures para este grupo de batches
-            let mut futures = Vec::new();
-            for batch_idx in start_idx..end_idx {
-                if batch_results[batch_idx].is_none() {
-                    let batch = batches[batch_idx].clone();
-                    let cancel_flag = cancel_flag.clone();
-                    let original_map = &original_map;
-                    let mut on_entry_clone = on_entry.clone();
-                    
-                    futures.push(async move {
-                        let result = self.translate_streaming_batch(
-                            system_prompt,
-                            &batch,
-                            batch_idx,
-                            max_retries,
-                            cancel_flag,
-                            original_map,
-                            &mut on_entry_clone,
-                        ).await;
-                        (batch_idx, result)
-                    });
-                }
+        batch: &[(usize, String)],
+        context: &BatchContext,
+        cancel_flag: &Option<Arc<AtomicBool>>,
+        on_entry: Option<&mut (dyn FnMut(TranslatedEntryEvent) + Send)>,
+    ) -> Result<Vec<(usize, String)>, ApiError> {
+        match on_entry {
+            Some(on_entry) if self.config.api_format != ApiFormat::Anthropic => {
+                self.stream_subtitles_with_context(
+                    system_prompt,
+                    batch,
+                    context,
+                    cancel_flag,
+                    on_entry,
+                )
+                .await
+            }
+            _ => {
+                self.translate_subtitles_with_context(system_prompt, batch, context)
+                    .await
             }
+        }
+    }
 
-            if futures.is_empty() {
-                current_batch_group += 1;
+    /// Resume o arquivo inteiro (enredo, falantes, registro, ambientação)
+    /// para orientar a tradução dos batches; arquivos grandes são resumidos
+    /// em partes e as partes combinadas num resumo final
+    pub async fn summarize_subtitles(
+        &self,
+        translation_prompt: &str,
+        entries: &[(usize, String)],
+        speakers: &[String],
+    ) -> Result<String, String> {
+        let mut chunks: Vec<String> = vec![String::new()];
+        for (_, text) in entries {
+            let line = glossary::plain_text(text);
+            if line.is_empty() {
                 continue;
             }
+            let current = chunks.last_mut().unwrap();
+            if !current.is_empty() && current.len() + line.len() > SYNOPSIS_CHUNK_CHARS {
+                chunks.push(String::new());
+            }
+            let current = chunks.last_mut().unwrap();
+            current.push_str(&line);
+            current.push('\n');
+        }
+        if chunks[0].is_empty() {
+            return Err("No subtitle text to summarize".to_string());
+        }
 
-            // Executa batches em paralelo
-            let results = join_all(futures).await;
-            check_cancelled(&cancel_flag)?;
+        let speakers = if speakers.is_empty() {
+            String::new()
+        } else {
+            format!("\nSpeaker names from the file: {}", speakers.join(", "))
+        };
+        let instruction = format!(
+            r#"You are helping a subtitle translator. The translation will follow this instruction:
+[{}]{}
 
-            // Processa resultados
-            for (batch_idx, result) in results {
-                match result {
-                    Ok(translations) => {
-                        batch_results[batch_idx] = Some(translations);
-                    }
-                    Err(e) => {
-                        return Err(e);
-                    }
-                }
-            }
+Read the subtitle lines in the user message and write a concise synopsis (at most 200 words, in English) covering:
+- plot: what happens
+- speakers: names, gender and relationships between them
+- register: formal, casual, slang, how characters address each other
+- setting: time, place, genre
 
-            current_batch_group += 1;
+Do NOT translate the lines. Respond with ONLY the synopsis."#,
+            translation_prompt, speakers
+        );
+
+        let mut summaries = Vec::new();
+        for chunk in &chunks {
+            let summary = strip_think_blocks(&self.translate(&instruction, chunk).await?)
+                .trim()
+                .to_string();
+            summaries.push(summary);
         }
 
-        // Coleta e ordena todos os resultados
-        let mut all_results: Vec<(usize, String)> =
-            batch_results.into_iter().flatten().flatten().collect();
-        all_results.sort_by_key(|(idx, _)| *idx);
+        let synopsis = if summaries.len() == 1 {
+            summaries.remove(0)
+        } else {
+            let parts = summaries
+                .iter()
+                .enumerate()
+                .map(|(i, summary)| format!("PART {}:\n{}", i + 1, summary))
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            let merge_instruction = "The user message contains synopses of consecutive parts of the same subtitle file. Merge them into a single concise synopsis (at most 250 words, in English) covering plot, speakers, register and setting. Respond with ONLY the synopsis.";
+            strip_think_blocks(&self.translate(merge_instruction, &parts).await?)
+                .trim()
+                .to_string()
+        };
 
-        if all_results.is_empty() {
-            return Err("Failed to parse streaming translation response".to_string());
+        if synopsis.is_empty() {
+            return Err("Synopsis response was empty".to_string());
         }
-
-        Ok(all_results)
+        Ok(synopsis)
     }
 
-    /// Traduz legendas em batch, preservando a estrutura
-    pub async fn translate_subtitles(
+    /// Traduz legendas em batch, preservando a estrutura, e envia junto o
+    /// material de apoio do batch: traduções parecidas da memória e linhas
+    /// vizinhas de contexto
+    pub async fn translate_subtitles_with_context(
         &self,
         system_prompt: &str,
         entries: &[(usize, String)],
-    ) -> Result<Vec<(usize, String)>, String> {
-        // Placeholder para quebras de linha - único o suficiente para não aparecer em texto normal
-        const NEWLINE_PLACEHOLDER: &str = "<<NEWLINE>>";
+        context: &BatchContext,
+    ) -> Result<Vec<(usize, String)>, ApiError> {
+        let (instruction, formatted) = self.batch_prompt(system_prompt, entries, context);
+        let response = self.request(&instruction, &formatted).await?;
+        self.parse_batch_response(&response, entries)
+    }
 
+    /// Instruções e conteúdo (linhas INDEX|TEXTO) enviados para um batch
+    fn batch_prompt(
+        &self,
+        system_prompt: &str,
+        entries: &[(usize, String)],
+        context: &BatchContext,
+    ) -> (String, String) {
         // Formata as legendas para envio
         // Formato: INDEX|TEXTO (para preservar mapeamento)
         // Converte \N (ASS) e \n (real) para placeholder para evitar confusão com quebras de linha reais
-        let formatted: String = entries
-            .iter()
-            .map(|(idx, text)| {
-                let normalized = text
-                    .replace("\\N", NEWLINE_PLACEHOLDER)
-                    .replace("\\n", NEWLINE_PLACEHOLDER)
-                    .replace('\n', NEWLINE_PLACEHOLDER);
-                format!("{}|{}", idx, normalized)
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
+        let formatted = format_batch_payload(entries, context, &self.speaker_labels);
 
         let instruction = format!(
             r#"{}
@@ -1003,16 +1349,29 @@ CRITICAL FORMAT INSTRUCTIONS:
 3. The marker {} represents a LINE BREAK within a subtitle. You MUST preserve it exactly as-is in your translation.
    Example input:  5|It's a special event{}that everyone attends
    Example output: 5|É um evento especial{}que todos participam
-4. Do NOT remove, split, or modify {} markers - they indicate where line breaks occur in the subtitle display."#,
-            system_prompt,
+4. Do NOT remove, split, or modify {} markers - they indicate where line breaks occur in the subtitle display.{}{}{}{}"#,
+            self.prompt_with_synopsis(system_prompt),
+            NEWLINE_PLACEHOLDER,
             NEWLINE_PLACEHOLDER,
             NEWLINE_PLACEHOLDER,
             NEWLINE_PLACEHOLDER,
-            NEWLINE_PLACEHOLDER
+            format_context_rule(context),
+            format_speaker_rule(entries, &self.speaker_labels),
+            self.glossary_block(entries),
+            format_reference_hints(&context.hints)
         );
 
-        let response = self.translate(&instruction, &formatted).await?;
-        let cleaned_response = strip_think_blocks(&response);
+        (instruction, formatted)
+    }
+
+    /// Lê a resposta de um batch; índices fora do batch são descartados e
+    /// linhas com tags ASS incompatíveis tornam a resposta inválida
+    fn parse_batch_response(
+        &self,
+        response: &str,
+        entries: &[(usize, String)],
+    ) -> Result<Vec<(usize, String)>, ApiError> {
+        let cleaned_response = strip_think_blocks(response);
 
         // Parse da resposta (suporta quebras de linha reais no texto traduzido)
         let mut results = Vec::new();
@@ -1021,7 +1380,7 @@ CRITICAL FORMAT INSTRUCTIONS:
 
         for raw_line in cleaned_response.lines() {
             let line = raw_line.trim_end();
-            if line.is_empty() || line.starts_with("```") {
+            if line.is_empty() || line.starts_with("```") || is_context_line(line) {
                 continue;
             }
 
@@ -1038,7 +1397,8 @@ CRITICAL FORMAT INSTRUCTIONS:
                         results.push((prev_idx, text));
                     }
                     current_idx = Some(idx);
-                    current_text = line[sep_pos + 1..].to_string();
+                    let label = self.speaker_labels.get(&idx).map(String::as_str);
+                    current_text = strip_speaker_label(&line[sep_pos + 1..], label).to_string();
                     continue;
                 }
             }
@@ -1061,8 +1421,11 @@ CRITICAL FORMAT INSTRUCTIONS:
             results.push((prev_idx, text));
         }
 
+        // Índices fora do batch (contexto ecoado pelo modelo) são descartados
+        results.retain(|(idx, _)| entries.iter().any(|(entry_idx, _)| entry_idx == idx));
+
         if results.is_empty() {
-            return Err("Failed to parse translation response".to_string());
+            return Err(ApiError::retryable("Failed to parse translation response"));
         }
 
         let mut tag_warns = Vec::new();
@@ -1089,23 +1452,27 @@ CRITICAL FORMAT INSTRUCTIONS:
                 })
                 .collect::<Vec<_>>()
                 .join("\n\n");
-            return Err(format!(
+            return Err(ApiError::retryable(format!(
                 "Translated lines contain incompatible ASS tags. Sample:\n{}",
                 sample
-            ));
+            )));
         }
 
         Ok(results)
     }
 
     /// Traduz um lote específico de legendas (para batch processing)
-    /// Permite continuar de um índice específico
+    /// Permite continuar de um índice específico; falhas temporárias são
+    /// repetidas até `settings.max_retries` vezes seguindo a `RetryPolicy`
+    /// do cliente, e linhas ausentes na resposta são pedidas de novo com as
+    /// vizinhas como contexto; as que seguirem faltando saem em `untranslated`
     pub async fn translate_batch(
         &self,
         system_prompt: &str,
         entries: &[(usize, String)],
         start_index: usize,
         batch_size: usize,
+        settings: &TranslationSettings,
     ) -> Result<BatchTranslationResult, String> {
         let total_entries = entries.len();
 
@@ -1130,11 +1497,68 @@ CRITICAL FORMAT INSTRUCTIONS:
                     is_partial: false,
                     can_continue: false,
                 },
+                untranslated: Vec::new(),
             });
         }
 
+        let positions: HashMap<usize, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(pos, (idx, _))| (*idx, pos))
+            .collect();
+        let known = HashMap::new();
+        let run_context = |run: &[(usize, String)], known: &HashMap<usize, String>| {
+            build_batch_context(
+                entries,
+                &positions,
+                run,
+                known,
+                settings.context_lines.max(1),
+                &[],
+            )
+        };
+        let context =
+            build_batch_context(entries, &positions, &batch, &known, settings.context_lines, &[]);
+
         // Traduz o lote
-        let translations = self.translate_subtitles(system_prompt, &batch).await?;
+        let mut attempt = 0;
+        let translations = loop {
+            match self
+                .translate_subtitles_with_context(system_prompt, &batch, &context)
+                .await
+            {
+                Ok(translations) => break translations,
+                Err(e) if e.retryable && attempt < settings.max_retries => {
+                    attempt += 1;
+                    self.retry_policy.wait(attempt, Some(&e)).await;
+                }
+                Err(e) => return Err(e.message),
+            }
+        };
+
+        // Linhas puladas pelo modelo são pedidas de novo
+        let (mut translations, missing) = self
+            .recover_missing(
+                system_prompt,
+                &batch,
+                translations,
+                &known,
+                &run_context,
+                settings.max_retries,
+            )
+            .await;
+        translations.sort_by_key(|(idx, _)| positions.get(idx).copied());
+        let untranslated = missing
+            .into_iter()
+            .map(|idx| UntranslatedEntry {
+                index: idx,
+                text: positions
+                    .get(&idx)
+                    .map(|pos| entries[*pos].1.clone())
+                    .unwrap_or_default(),
+                reason: MISSING_FROM_RESPONSE.to_string(),
+            })
+            .collect();
 
         // Calcula progresso
         let last_translated = translations.last().map(|(idx, _)| *idx).unwrap_or(0);
@@ -1152,48 +1576,290 @@ CRITICAL FORMAT INSTRUCTIONS:
                 is_partial,
                 can_continue,
             },
+            untranslated,
         })
     }
 
-    /// Traduz um único batch (para uso em paralelo)
+    /// Traduz um único batch (para uso em paralelo); com `on_entry`, em streaming
     async fn translate_single_batch(
         &self,
         system_prompt: &str,
         batch: Vec<(usize, String)>,
+        context: &BatchContext,
         batch_index: usize,
-    ) -> (usize, Result<Vec<(usize, String)>, String>) {
-        let result = self.translate_subtitles(system_prompt, &batch).await;
+        cancel_flag: &Option<Arc<AtomicBool>>,
+        mut on_entry: Option<impl FnMut(TranslatedEntryEvent) + Send>,
+    ) -> (usize, Result<Vec<(usize, String)>, ApiError>) {
+        let on_entry = on_entry
+            .as_mut()
+            .map(|f| f as &mut (dyn FnMut(TranslatedEntryEvent) + Send));
+        let result = self
+            .translate_batch_attempt(system_prompt, &batch, context, cancel_flag, on_entry)
+            .await;
         (batch_index, result)
     }
 
+    /// Pede de novo só as linhas do batch que faltaram na resposta (o modelo
+    /// pulou ou juntou linhas), um trecho contíguo por vez e com as linhas
+    /// vizinhas como contexto, até `max_retries` rodadas
+    /// Devolve as traduções completadas e os índices que continuaram faltando
+    async fn recover_missing(
+        &self,
+        system_prompt: &str,
+        batch: &[(usize, String)],
+        mut translations: Vec<(usize, String)>,
+        known: &HashMap<usize, String>,
+        context_for_run: &impl Fn(&[(usize, String)], &HashMap<usize, String>) -> BatchContext,
+        max_retries: usize,
+    ) -> (Vec<(usize, String)>, Vec<usize>) {
+        let mut last_error: Option<ApiError> = None;
+        'rounds: for round in 0..max_retries {
+            let runs = missing_runs(batch, &translations);
+            if runs.is_empty() {
+                break;
+            }
+            if let Some(error) = last_error.take() {
+                self.retry_policy.wait(round, Some(&error)).await;
+            }
+
+            let mut known = known.clone();
+            known.extend(translations.iter().cloned());
+            for run in runs {
+                let context = context_for_run(&run, &known);
+                match self
+                    .translate_subtitles_with_context(system_prompt, &run, &context)
+                    .await
+                {
+                    Ok(recovered) => translations.extend(recovered),
+                    Err(e) if !e.retryable => break 'rounds,
+                    Err(e) => last_error = Some(e),
+                }
+            }
+        }
+
+        let missing = missing_runs(batch, &translations)
+            .into_iter()
+            .flatten()
+            .map(|(idx, _)| idx)
+            .collect();
+        (translations, missing)
+    }
+
+    /// Divide um batch que esgotou as tentativas ao meio, recursivamente,
+    /// até isolar as linhas que falham sozinhas (filtro de conteúdo, tags
+    /// quebradas...); cada trecho é tentado uma vez antes de ser dividido
+    async fn bisect_batch(
+        &self,
+        system_prompt: &str,
+        batch: &[(usize, String)],
+        known: &HashMap<usize, String>,
+        context_for_run: &impl Fn(&[(usize, String)], &HashMap<usize, String>) -> BatchContext,
+        max_retries: usize,
+        cancel_flag: &Option<Arc<AtomicBool>>,
+    ) -> Result<BisectionOutcome, String> {
+        let mut outcome = BisectionOutcome {
+            translations: Vec::new(),
+            missing: Vec::new(),
+            failed: Vec::new(),
+            fatal: false,
+        };
+        let mut rate_limit_waits = 0;
+        let mut known = known.clone();
+        let mut queue: VecDeque<Vec<(usize, String)>> = VecDeque::new();
+        if batch.len() > 1 {
+            let (first, second) = batch.split_at(batch.len() / 2);
+            queue.extend([first.to_vec(), second.to_vec()]);
+        } else {
+            queue.push_back(batch.to_vec());
+        }
+
+        // Fila em profundidade: a metade da frente é resolvida antes da
+        // seguinte, assim o contexto anterior já leva as traduções prontas
+        while let Some(part) = queue.pop_front() {
+            check_cancelled(cancel_flag)?;
+            let context = context_for_run(&part, &known);
+            match self
+                .translate_subtitles_with_context(system_prompt, &part, &context)
+                .await
+            {
+                Ok(translations) => {
+                    let (translations, missing) = self
+                        .recover_missing(
+                            system_prompt,
+                            &part,
+                            translations,
+                            &known,
+                            context_for_run,
+                            max_retries,
+                        )
+                        .await;
+                    known.extend(translations.iter().cloned());
+                    outcome.translations.extend(translations);
+                    outcome.missing.extend(missing);
+                }
+                // Dividir não resolve erro definitivo nem rate limit
+                Err(e) if !e.retryable => {
+                    outcome.failed.extend(
+                        part.iter()
+                            .chain(queue.iter().flatten())
+                            .map(|(idx, _)| (*idx, e.message.clone())),
+                    );
+                    outcome.fatal = true;
+                    break;
+                }
+                Err(e) if e.rate_limited && rate_limit_waits < max_retries => {
+                    rate_limit_waits += 1;
+                    self.retry_policy.wait(rate_limit_waits, Some(&e)).await;
+                    queue.push_front(part);
+                }
+                Err(e) if part.len() == 1 => outcome.failed.push((part[0].0, e.message)),
+                Err(_) => {
+                    let (first, second) = part.split_at(part.len() / 2);
+                    queue.push_front(second.to_vec());
+                    queue.push_front(first.to_vec());
+                }
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Guarda traduções recebidas na memória de tradução (se houver)
+    fn remember(
+        &self,
+        memory: Option<&Mutex<TranslationMemory>>,
+        system_prompt: &str,
+        sources: &[(usize, String)],
+        translations: &[(usize, String)],
+    ) {
+        if let Some(memory) = memory {
+            memory.lock().unwrap().record_all(
+                &self.config.model,
+                system_prompt,
+                sources,
+                translations,
+            );
+        }
+    }
+
     /// Traduz todas as legendas em batches, com suporte a paralelismo e auto-continue
+    /// Com `memory`, linhas já traduzidas antes (mesmo prompt e modelo) saem
+    /// da memória e só as demais são enviadas, acompanhadas das traduções
+    /// parecidas como referência; o que vier da API é registrado assim que
+    /// cada batch termina, para não se perder num cancelamento
+    /// Cada batch leva até `settings.context_lines` linhas vizinhas de cada
+    /// lado como contexto (com a tradução das anteriores, quando já pronta)
+    /// Linhas ausentes na resposta são pedidas de novo; as que seguirem
+    /// faltando saem em `untranslated`
+    /// Um batch que esgota `max_retries` é dividido ao meio até sobrarem só
+    /// as linhas que não traduzem de jeito nenhum, também em `untranslated`
+    /// Erros definitivos (401, 400...) encerram a tradução sem novas tentativas
+    /// Com `settings.streaming`, as linhas vão para `on_entry` conforme chegam
+    /// da API e a tradução final de cada batch é entregue de novo ao terminar
     #[allow(clippy::too_many_arguments)]
     pub async fn translate_all_batched(
         &self,
         system_prompt: &str,
         entries: &[(usize, String)],
         settings: &TranslationSettings,
+        memory: Option<&Mutex<TranslationMemory>>,
         cancel_flag: Option<Arc<AtomicBool>>,
         mut on_progress: impl FnMut(TranslationProgress),
         mut on_retry: impl FnMut(TranslationRetryInfo),
         mut on_error: impl FnMut(TranslationErrorInfo),
+        mut on_entry: impl FnMut(TranslatedEntryEvent) + Send + Clone,
     ) -> Result<TranslationBatchReport, String> {
         let total = entries.len();
         let parallel_requests = settings.parallel_requests.max(1);
 
+        // Separa acertos da memória das linhas que precisam ir para a API
+        let mut cached: Vec<(usize, String)> = Vec::new();
+        let mut pending: Vec<(usize, String)> = Vec::new();
+        let mut fuzzy_index = FuzzyIndex::default();
+        match memory {
+            Some(memory) => {
+                let mut memory = memory.lock().unwrap();
+                for (idx, text) in entries {
+                    match memory.lookup(&self.config.model, system_prompt, text) {
+                        Some(translation) => cached.push((*idx, translation)),
+                        None => pending.push((*idx, text.clone())),
+                    }
+                }
+                fuzzy_index = memory.fuzzy_index(&self.config.model, system_prompt);
+            }
+            None => pending = entries.to_vec(),
+        }
+        if settings.streaming {
+            emit_entries(&mut on_entry, &cached);
+        }
+
         // Divide entries em batches
-        let batches: Vec<Vec<(usize, String)>> = entries
+        let batches: Vec<Vec<(usize, String)>> = pending
             .chunks(settings.batch_size)
             .map(|chunk| chunk.to_vec())
             .collect();
+        let batch_hints: Vec<Vec<FuzzyMatch>> = batches
+            .iter()
+            .map(|batch| collect_fuzzy_hints(&fuzzy_index, batch))
+            .collect();
 
         let total_batches = batches.len();
         let mut batch_results: Vec<Option<Vec<(usize, String)>>> = vec![None; total_batches];
         let mut current_batch_group = 0;
 
+        // Posição de cada índice no arquivo, para achar as linhas vizinhas
+        let positions: HashMap<usize, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(pos, (idx, _))| (*idx, pos))
+            .collect();
+        let known_translations = |batch_results: &Vec<Option<Vec<(usize, String)>>>| {
+            cached
+                .iter()
+                .chain(batch_results.iter().flatten().flatten())
+                .cloned()
+                .collect::<HashMap<usize, String>>()
+        };
+        let context_for = |batch_idx: usize, known: &HashMap<usize, String>| {
+            build_batch_context(
+                entries,
+                &positions,
+                &batches[batch_idx],
+                known,
+                settings.context_lines,
+                &batch_hints[batch_idx],
+            )
+        };
+        // Linhas reenviadas sempre levam ao menos uma vizinha de cada lado
+        let run_context = |run: &[(usize, String)], known: &HashMap<usize, String>| {
+            build_batch_context(
+                entries,
+                &positions,
+                run,
+                known,
+                settings.context_lines.max(1),
+                &[],
+            )
+        };
+        let run_context = &run_context;
+        let mut untranslated: Vec<UntranslatedEntry> = Vec::new();
+        let untranslated_entry = |idx: usize, reason: &str| UntranslatedEntry {
+            index: idx,
+            text: positions
+                .get(&idx)
+                .map(|pos| entries[*pos].1.clone())
+                .unwrap_or_default(),
+            reason: reason.to_string(),
+        };
+
         let build_progress = |translations: &Vec<(usize, String)>| -> TranslationProgress {
-            let translated_entries = translations.len();
-            let last_translated_index = translations.iter().map(|(idx, _)| *idx).max().unwrap_or(0);
+            let translated_entries = translations.len() + cached.len();
+            let last_translated_index = translations
+                .iter()
+                .chain(cached.iter())
+                .map(|(idx, _)| *idx)
+                .max()
+                .unwrap_or(0);
             let is_partial = translated_entries < total;
             TranslationProgress {
                 total_entries: total,
@@ -1212,12 +1878,22 @@ CRITICAL FORMAT INSTRUCTIONS:
             let end_idx = (start_idx + parallel_requests).min(total_batches);
 
             // Prepara futures para este grupo de batches
+            let known = known_translations(&batch_results);
+            let contexts: Vec<(usize, BatchContext)> = (start_idx..end_idx)
+                .filter(|batch_idx| batch_results[*batch_idx].is_none())
+                .map(|batch_idx| (batch_idx, context_for(batch_idx, &known)))
+                .collect();
             let mut futures = Vec::new();
-            for batch_idx in start_idx..end_idx {
-                if batch_results[batch_idx].is_none() {
-                    let batch = batches[batch_idx].clone();
-                    futures.push(self.translate_single_batch(system_prompt, batch, batch_idx));
-                }
+            for (batch_idx, context) in &contexts {
+                let batch = batches[*batch_idx].clone();
+                futures.push(self.translate_single_batch(
+                    system_prompt,
+                    batch,
+                    context,
+                    *batch_idx,
+                    &cancel_flag,
+                    settings.streaming.then(|| on_entry.clone()),
+                ));
             }
 
             if futures.is_empty() {
@@ -1230,43 +1906,130 @@ CRITICAL FORMAT INSTRUCTIONS:
             check_cancelled(&cancel_flag)?;
 
             // Processa resultados
-            let mut last_error: Option<String> = None;
-            let mut failed_batches: Vec<usize> = Vec::new();
+            let mut failed_batches: Vec<(usize, ApiError)> = Vec::new();
+            let mut succeeded: Vec<(usize, Vec<(usize, String)>)> = Vec::new();
 
             for (batch_idx, result) in results {
                 match result {
                     Ok(translations) => {
-                        batch_results[batch_idx] = Some(translations);
+                        succeeded.push((batch_idx, translations));
                     }
                     Err(e) => {
-                        last_error = Some(e.clone());
-                        failed_batches.push(batch_idx);
+                        failed_batches.push((batch_idx, e));
                     }
                 }
             }
 
+            // Linhas que o modelo pulou ou juntou são pedidas de novo
+            let known = known_translations(&batch_results);
+            let recoveries = succeeded.into_iter().map(|(batch_idx, translations)| {
+                let known = &known;
+                let batch = &batches[batch_idx];
+                async move {
+                    let recovered = self
+                        .recover_missing(
+                            system_prompt,
+                            batch,
+                            translations,
+                            known,
+                            run_context,
+                            settings.max_retries,
+                        )
+                        .await;
+                    (batch_idx, recovered)
+                }
+            });
+            for (batch_idx, (translations, missing)) in join_all(recoveries).await {
+                self.remember(memory, system_prompt, &batches[batch_idx], &translations);
+                if settings.streaming {
+                    emit_entries(&mut on_entry, &translations);
+                }
+                batch_results[batch_idx] = Some(translations);
+                untranslated.extend(
+                    missing
+                        .into_iter()
+                        .map(|idx| untranslated_entry(idx, MISSING_FROM_RESPONSE)),
+                );
+            }
+            check_cancelled(&cancel_flag)?;
+
             // Retry para batches que falharam
-            for failed_idx in failed_batches {
+            for (failed_idx, mut last_error) in failed_batches {
                 let mut retries = 0;
                 loop {
                     check_cancelled(&cancel_flag)?;
                     retries += 1;
 
-                    // Calcula progresso atual para callback
-                    let current_translations: Vec<_> = batch_results
-                        .iter()
-                        .filter_map(|r| r.clone())
-                        .flatten()
-                        .collect();
-                    let progress = build_progress(&current_translations);
-
-                    if retries > settings.max_retries {
-                        let error_message = format!(
-                            "Translation failed after {} retries: {}",
-                            settings.max_retries,
-                            last_error.clone().unwrap_or_default()
+                    let fatal = !last_error.retryable;
+                    if retries > settings.max_retries || fatal {
+                        let (failed, stopped) = if fatal {
+                            // Erro definitivo: tentar de novo ou dividir não muda nada
+                            let failed: Vec<(usize, String)> = batches[failed_idx]
+                                .iter()
+                                .map(|(idx, _)| (*idx, last_error.message.clone()))
+                                .collect();
+                            (failed, true)
+                        } else {
+                            // Divide o batch para salvar as linhas que traduzem
+                            let known = known_translations(&batch_results);
+                            let outcome = self
+                                .bisect_batch(
+                                    system_prompt,
+                                    &batches[failed_idx],
+                                    &known,
+                                    run_context,
+                                    settings.max_retries,
+                                    &cancel_flag,
+                                )
+                                .await?;
+                            self.remember(
+                                memory,
+                                system_prompt,
+                                &batches[failed_idx],
+                                &outcome.translations,
+                            );
+                            if settings.streaming {
+                                emit_entries(&mut on_entry, &outcome.translations);
+                            }
+                            batch_results[failed_idx] = Some(outcome.translations);
+                            untranslated.extend(
+                                outcome
+                                    .missing
+                                    .into_iter()
+                                    .map(|idx| untranslated_entry(idx, MISSING_FROM_RESPONSE)),
+                            );
+                            if outcome.failed.is_empty() {
+                                break;
+                            }
+                            (outcome.failed, outcome.fatal)
+                        };
+
+                        let cause = failed
+                            .last()
+                            .map(|(_, e)| e.clone())
+                            .unwrap_or_else(|| last_error.message.clone());
+                        let error_message = if stopped {
+                            format!("Translation stopped: {}", cause)
+                        } else {
+                            format!(
+                                "{} line(s) failed after {} retries and splitting the batch: {}",
+                                failed.len(),
+                                settings.max_retries,
+                                cause
+                            )
+                        };
+                        untranslated.extend(
+                            failed
+                                .iter()
+                                .map(|(idx, e)| untranslated_entry(*idx, e)),
                         );
-                        let mut error_progress = progress.clone();
+
+                        let current_translations: Vec<_> = batch_results
+                            .iter()
+                            .filter_map(|r| r.clone())
+                            .flatten()
+                            .collect();
+                        let mut error_progress = build_progress(&current_translations);
                         error_progress.can_continue =
                             settings.continue_on_error && error_progress.is_partial;
 
@@ -1275,46 +2038,88 @@ CRITICAL FORMAT INSTRUCTIONS:
                             progress: error_progress.clone(),
                         });
 
-                        if !settings.continue_on_error {
+                        // Erro definitivo vale para os próximos batches também
+                        if stopped || !settings.continue_on_error {
                             // Coleta traduções bem-sucedidas
                             let mut translations: Vec<(usize, String)> = batch_results
                                 .iter()
                                 .filter_map(|r| r.clone())
                                 .flatten()
                                 .collect();
+                            translations.extend(cached.iter().cloned());
                             translations.sort_by_key(|(idx, _)| *idx);
 
                             return Ok(TranslationBatchReport {
                                 translations,
                                 progress: error_progress,
                                 error_message: Some(error_message),
+                                untranslated,
                             });
                         }
 
-                        // Se continue_on_error, deixa o batch como None e continua
+                        // Se continue_on_error, segue com as linhas que traduziram
                         break;
                     }
 
+                    // Calcula progresso atual para callback
+                    let current_translations: Vec<_> = batch_results
+                        .iter()
+                        .filter_map(|r| r.clone())
+                        .flatten()
+                        .collect();
+                    let progress = build_progress(&current_translations);
+
                     on_retry(TranslationRetryInfo {
                         attempt: retries,
                         max_retries: settings.max_retries,
-                        error_message: last_error.clone().unwrap_or_default(),
+                        error_message: last_error.message.clone(),
                         progress,
                     });
 
-                    // Delay antes de retry
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                    // Backoff (ou a espera pedida pelo servidor) antes do retry
+                    self.retry_policy.wait(retries, Some(&last_error)).await;
                     check_cancelled(&cancel_flag)?;
 
                     // Tenta novamente
                     let batch = batches[failed_idx].clone();
-                    match self.translate_subtitles(system_prompt, &batch).await {
+                    let context = context_for(failed_idx, &known_translations(&batch_results));
+                    let (_, result) = self
+                        .translate_single_batch(
+                            system_prompt,
+                            batch.clone(),
+                            &context,
+                            failed_idx,
+                            &cancel_flag,
+                            settings.streaming.then(|| on_entry.clone()),
+                        )
+                        .await;
+                    match result {
                         Ok(translations) => {
+                            let known = known_translations(&batch_results);
+                            let (translations, missing) = self
+                                .recover_missing(
+                                    system_prompt,
+                                    &batch,
+                                    translations,
+                                    &known,
+                                    run_context,
+                                    settings.max_retries,
+                                )
+                                .await;
+                            self.remember(memory, system_prompt, &batch, &translations);
+                            if settings.streaming {
+                                emit_entries(&mut on_entry, &translations);
+                            }
                             batch_results[failed_idx] = Some(translations);
+                            untranslated.extend(
+                                missing
+                                    .into_iter()
+                                    .map(|idx| untranslated_entry(idx, MISSING_FROM_RESPONSE)),
+                            );
                             break;
                         }
                         Err(e) => {
-                            last_error = Some(e);
+                            last_error = e;
                         }
                     }
                 }
@@ -1339,13 +2144,15 @@ CRITICAL FORMAT INSTRUCTIONS:
         // Coleta e ordena todas as traduções
         let mut all_translations: Vec<(usize, String)> =
             batch_results.into_iter().flatten().flatten().collect();
+        let progress = build_progress(&all_translations);
+        all_translations.extend(cached.iter().cloned());
         all_translations.sort_by_key(|(idx, _)| *idx);
 
-        let progress = build_progress(&all_translations);
         Ok(TranslationBatchReport {
             translations: all_translations,
             progress,
             error_message: None,
+            untranslated,
         })
     }
 }
@@ -1353,6 +2160,7 @@ CRITICAL FORMAT INSTRUCTIONS:
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::subtitle::SubtitleFormat;
 
     #[test]
     fn test_default_config() {
@@ -1360,4 +2168,157 @@ mod tests {
         assert!(config.endpoint.contains("localhost"));
         assert_eq!(config.api_key, "dummy");
     }
+
+    #[test]
+    fn test_fuzzy_hints_in_instruction() {
+        let mut memory = TranslationMemory::default();
+        memory.record(
+            "gpt",
+            "pt",
+            "Previously on\nSword Art Online",
+            "Anteriormente em\nSword Art Online",
+        );
+        let index = memory.fuzzy_index("gpt", "pt");
+
+        let batch = vec![
+            (1, "Previously on Sword Art Online...".to_string()),
+            (2, "Previously, on Sword Art Online!".to_string()),
+        ];
+        let hints = collect_fuzzy_hints(&index, &batch);
+        assert_eq!(hints.len(), 1);
+
+        let block = format_reference_hints(&hints);
+        assert!(block.contains("ORIGINAL: Previously on<<NEWLINE>>Sword Art Online"));
+        assert!(block.contains("TRANSLATION: Anteriormente em<<NEWLINE>>Sword Art Online"));
+        assert!(format_reference_hints(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_batch_context_payload() {
+        let entries: Vec<(usize, String)> = (1..=6)
+            .map(|idx| (idx, format!("Line {}", idx)))
+            .collect();
+        let positions: HashMap<usize, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(pos, (idx, _))| (*idx, pos))
+            .collect();
+        let known: HashMap<usize, String> = [(2, "Linha 2".to_string())].into_iter().collect();
+
+        let batch = entries[2..4].to_vec();
+        let context = build_batch_context(&entries, &positions, &batch, &known, 2, &[]);
+        assert_eq!(
+            context.before,
+            vec![
+                ("Line 1".to_string(), None),
+                ("Line 2".to_string(), Some("Linha 2".to_string()))
+            ]
+        );
+        assert_eq!(context.after, vec!["Line 5".to_string(), "Line 6".to_string()]);
+
+        let payload = format_batch_payload(&batch, &context, &HashMap::new());
+        assert!(payload.contains(">> Line 2 => Linha 2\n[END CONTEXT]\n\n3|Line 3\n4|Line 4"));
+        assert!(!format_context_rule(&context).is_empty());
+
+        // Contexto ecoado na resposta não vira tradução
+        let no_labels = HashMap::new();
+        assert_eq!(
+            parse_translation_line(">> Line 5", NEWLINE_PLACEHOLDER, &no_labels),
+            None
+        );
+        assert_eq!(
+            parse_translation_line("3|Linha 3", NEWLINE_PLACEHOLDER, &no_labels),
+            Some((3, "Linha 3".to_string()))
+        );
+    }
+    #[test]
+    fn test_speaker_labels_in_payload() {
+        let mut file = SubtitleFile::parse(
+            "[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nDialogue: 0,0:00:01.00,0:00:02.00,Main,Kirito,0,0,0,,Let's go\\NAsuna\nDialogue: 0,0:00:03.00,0:00:04.00,Default,,0,0,0,,Wait!\n",
+            SubtitleFormat::Ass,
+        )
+        .unwrap();
+        file.entries[1].metadata.as_mut().unwrap().name = Some("Asuna".to_string());
+        let labels = speaker_labels(&file);
+        assert_eq!(labels.get(&file.entries[0].index).unwrap(), "Kirito/Main");
+        assert_eq!(labels.get(&file.entries[1].index).unwrap(), "Asuna");
+
+        let entries = file.extract_texts();
+        let payload = format_batch_payload(&entries, &BatchContext::default(), &labels);
+        let first_line = format!("{}|[Kirito/Main]|Let's go<<NEWLINE>>Asuna", entries[0].0);
+        assert!(payload.contains(&first_line));
+        assert!(!format_speaker_rule(&entries, &labels).is_empty());
+
+        // Resposta no formato antigo ou com o rótulo repetido dá o mesmo texto
+        let first = entries[0].0;
+        let echoed = format!("{}|[Kirito/Main]|Vamos", first);
+        assert_eq!(
+            parse_translation_line(&echoed, NEWLINE_PLACEHOLDER, &labels),
+            Some((first, "Vamos".to_string()))
+        );
+        assert_eq!(
+            parse_translation_line(&format!("{}|Vamos", first), NEWLINE_PLACEHOLDER, &labels),
+            Some((first, "Vamos".to_string()))
+        );
+
+        // Só o rótulo enviado para a linha é removido
+        let sign = format!("{}|[Sign]|Saída", first);
+        assert_eq!(
+            parse_translation_line(&sign, NEWLINE_PLACEHOLDER, &labels),
+            Some((first, "[Sign]|Saída".to_string()))
+        );
+
+        // Sem rótulos, texto no formato [X]| é tradução legítima
+        assert_eq!(
+            parse_translation_line(&echoed, NEWLINE_PLACEHOLDER, &HashMap::new()),
+            Some((first, "[Kirito/Main]|Vamos".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_missing_runs() {
+        let batch: Vec<(usize, String)> = (1..=6)
+            .map(|idx| (idx, format!("Line {}", idx)))
+            .collect();
+        let translations = vec![
+            (1, "Linha 1".to_string()),
+            (4, "Linha 4".to_string()),
+            (5, "Linha 5".to_string()),
+        ];
+
+        let runs: Vec<Vec<usize>> = missing_runs(&batch, &translations)
+            .iter()
+            .map(|run| run.iter().map(|(idx, _)| *idx).collect())
+            .collect();
+        assert_eq!(runs, vec![vec![2, 3], vec![6]]);
+        assert!(missing_runs(&batch[..1], &translations).is_empty());
+    }
+
+    #[test]
+    fn test_synopsis_prefixes_prompt() {
+        let client = LlmClient::new(LlmConfig::default());
+        assert_eq!(client.prompt_with_synopsis("Translate to pt-BR"), "Translate to pt-BR");
+
+        let client = client.with_synopsis(Some("Kirito and Asuna fight a boss.".to_string()));
+        let prompt = client.prompt_with_synopsis("Translate to pt-BR");
+        assert!(prompt.starts_with("EPISODE SYNOPSIS"));
+        assert!(prompt.contains("Kirito and Asuna fight a boss."));
+        assert!(prompt.ends_with("Translate to pt-BR"));
+    }
+
+    #[test]
+    fn test_parse_batch_response() {
+        // Streaming e modo normal leem a resposta completa do mesmo jeito
+        let client = LlmClient::new(LlmConfig::default());
+        let batch = vec![(3, "Line 3".to_string()), (4, "Line 4".to_string())];
+        let response = "<think>3|rascunho</think>\n>> Line 2 => Linha 2\n3|Linha 3<<NEWLINE>>continua\n4|Linha 4\n5|Linha 5\n";
+        assert_eq!(
+            client.parse_batch_response(response, &batch).unwrap(),
+            vec![
+                (3, "Linha 3\ncontinua".to_string()),
+                (4, "Linha 4".to_string())
+            ]
+        );
+        assert!(client.parse_batch_response("Sem formato", &batch).is_err());
+    }
 }
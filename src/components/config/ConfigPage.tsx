@@ -1,3 +1,4 @@
+import { useState } from 'react';
 import { useTranslation } from 'react-i18next';
 import { motion } from 'framer-motion';
 import { 
@@ -9,7 +10,8 @@ import {
   FolderOpen,
   Translate,
   ArrowSquareOut,
-  Sparkle
+  Sparkle,
+  Trash
 } from '@phosphor-icons/react';
 import { ApiSettings } from './ApiSettings';
 import { FfmpegStatus } from './FfmpegStatus';
@@ -20,7 +22,7 @@ import { OutputSettings } from './OutputSettings';
 import { LanguageSettings } from './LanguageSettings';
 import { TextCleanerSettings } from './TextCleanerSettings';
 import { Button } from '@/components/ui/button';
-import { getAppDataDir, openFolder } from '@/utils/tauri';
+import { clearTranslationMemory, getAppDataDir, openFolder } from '@/utils/tauri';
 
 const containerVariants = {
   hidden: { opacity: 0 },
@@ -48,6 +50,15 @@ const itemVariants = {
 
 export function ConfigPage() {
   const { t } = useTranslation();
+  const [memoryCleared, setMemoryCleared] = useState<number | null>(null);
+
+  const handleClearTranslationMemory = async () => {
+    try {
+      setMemoryCleared(await clearTranslationMemory());
+    } catch (error) {
+      console.error('Failed to clear translation memory:', error);
+    }
+  };
 
   const handleOpenConfigFolder = async () => {
     try {
@@ -209,6 +220,19 @@ export function ConfigPage() {
                 <ArrowSquareOut className="w-4 h-4" />
                 {t('settings.appData.openConfigFolder')}
               </Button>
+              <Button
+                variant="outline"
+                onClick={handleClearTranslationMemory}
+                className="w-full gap-2 mt-2"
+              >
+                <Trash className="w-4 h-4" />
+                {t('settings.appData.clearTranslationMemory')}
+              </Button>
+              {memoryCleared !== null && (
+                <p className="text-xs text-muted-foreground mt-2">
+                  {t('settings.appData.translationMemoryCleared', { count: memoryCleared })}
+                </p>
+              )}
               <p className="text-xs text-muted-foreground mt-3">
                 {t('settings.appData.configFilesHint')}
               </p>
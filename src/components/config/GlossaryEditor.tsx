@@ -0,0 +1,132 @@
+import { useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { MagicWand, Plus, Trash } from '@phosphor-icons/react';
+import { Button } from '@/components/ui/button';
+import { Checkbox } from '@/components/ui/checkbox';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { SUBTITLE_EXTENSIONS } from '@/hooks/useFileHandler';
+import { useSettingsStore } from '../../stores/settingsStore';
+import { extractGlossary, loadSubtitle } from '../../utils/tauri';
+import { GlossaryTerm } from '../../types';
+
+interface GlossaryEditorProps {
+  terms: GlossaryTerm[];
+  onChange: (terms: GlossaryTerm[]) => void;
+  // Prompt do template, usado para definir o idioma alvo na extração
+  prompt: string;
+}
+
+export function GlossaryEditor({ terms, onChange, prompt }: GlossaryEditorProps) {
+  const { t } = useTranslation();
+  const { settings } = useSettingsStore();
+  const [isExtracting, setIsExtracting] = useState(false);
+  const [extractError, setExtractError] = useState<string | null>(null);
+
+  // Sugere termos a partir de legendas escolhidas; as sugestões entram na
+  // lista para revisão e só valem depois de salvar o template
+  const handleExtract = async () => {
+    const { open } = await import('@tauri-apps/plugin-dialog');
+    const selected = await open({
+      multiple: true,
+      filters: [{ name: 'Legendas', extensions: SUBTITLE_EXTENSIONS }],
+    });
+    if (!selected) return;
+
+    const paths = Array.isArray(selected) ? selected : [selected];
+    setIsExtracting(true);
+    setExtractError(null);
+    try {
+      const files = await Promise.all(paths.map((path) => loadSubtitle(path)));
+      const headers = settings.headers.reduce(
+        (acc, h) => ({ ...acc, [h.key]: h.value }),
+        {} as Record<string, string>,
+      );
+      const proposed = await extractGlossary(
+        settings.baseUrl,
+        settings.apiKey,
+        settings.apiFormat,
+        settings.customModel || settings.model,
+        prompt || settings.prompt,
+        files,
+        terms,
+        headers,
+      );
+      onChange([...terms, ...proposed]);
+    } catch (error) {
+      setExtractError(String(error));
+    } finally {
+      setIsExtracting(false);
+    }
+  };
+
+  const updateTerm = <K extends keyof GlossaryTerm>(index: number, key: K, value: GlossaryTerm[K]) => {
+    onChange(terms.map((term, i) => (i === index ? { ...term, [key]: value } : term)));
+  };
+
+  const addTerm = () => {
+    onChange([...terms, { source: '', target: '', caseSensitive: false, notes: '' }]);
+  };
+
+  const removeTerm = (index: number) => {
+    onChange(terms.filter((_, i) => i !== index));
+  };
+
+  return (
+    <div className="space-y-2">
+      <div className="flex items-center justify-between">
+        <Label>{t('settings.templates.glossary')}</Label>
+        <div className="flex items-center gap-1">
+          <Button variant="ghost" size="sm" onClick={handleExtract} disabled={isExtracting} className="gap-1">
+            <MagicWand className="w-4 h-4" />
+            {isExtracting ? t('settings.templates.extractingTerms') : t('settings.templates.extractTerms')}
+          </Button>
+          <Button variant="ghost" size="sm" onClick={addTerm} className="gap-1">
+            <Plus className="w-4 h-4" />
+            {t('settings.templates.addTerm')}
+          </Button>
+        </div>
+      </div>
+      <p className="text-xs text-muted-foreground">{t('settings.templates.glossaryHint')}</p>
+      {extractError && <p className="text-xs text-destructive">{extractError}</p>}
+
+      <div className="space-y-2 max-h-[220px] overflow-y-auto">
+        {terms.map((term, index) => (
+          <div key={index} className="grid grid-cols-[1fr_1fr_auto_auto] items-center gap-2">
+            <Input
+              placeholder={t('settings.templates.sourceTerm')}
+              value={term.source}
+              onChange={(e) => updateTerm(index, 'source', e.target.value)}
+            />
+            <Input
+              placeholder={t('settings.templates.targetTerm')}
+              value={term.target}
+              onChange={(e) => updateTerm(index, 'target', e.target.value)}
+            />
+            <div className="flex items-center gap-1" title={t('settings.templates.caseSensitive')}>
+              <Checkbox
+                checked={term.caseSensitive}
+                onCheckedChange={(checked) => updateTerm(index, 'caseSensitive', checked === true)}
+              />
+              <span className="text-xs text-muted-foreground">Aa</span>
+            </div>
+            <Button
+              size="sm"
+              variant="ghost"
+              onClick={() => removeTerm(index)}
+              className="h-8 w-8 p-0 text-destructive hover:text-destructive hover:bg-destructive/10"
+            >
+              <Trash className="w-4 h-4" />
+            </Button>
+            <Input
+              placeholder={t('settings.templates.termNotes')}
+              value={term.notes}
+              onChange={(e) => updateTerm(index, 'notes', e.target.value)}
+              className="col-span-4 h-8 text-xs"
+            />
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+}
@@ -3,12 +3,14 @@ import { useTranslation } from 'react-i18next';
 import { motion, AnimatePresence } from 'framer-motion';
 import { PencilSimple, Trash } from '@phosphor-icons/react';
 import { Button } from '@/components/ui/button';
+import { Checkbox } from '@/components/ui/checkbox';
 import { Dialog, DialogContent, DialogFooter, DialogHeader, DialogTitle } from '@/components/ui/dialog';
 import { Input } from '@/components/ui/input';
 import { Label } from '@/components/ui/label';
 import { Textarea } from '@/components/ui/textarea';
 import { useSettingsStore } from '../../stores/settingsStore';
-import { Template } from '../../types';
+import { GlossaryTerm, Template } from '../../types';
+import { GlossaryEditor } from './GlossaryEditor';
 
 export function TemplateManager() {
   const { t } = useTranslation();
@@ -19,6 +21,8 @@ export function TemplateManager() {
   const [editingTemplate, setEditingTemplate] = useState<Template | null>(null);
   const [name, setName] = useState('');
   const [content, setContent] = useState('');
+  const [glossary, setGlossary] = useState<GlossaryTerm[]>([]);
+  const [speakerLabels, setSpeakerLabels] = useState(false);
   const [isSaving, setIsSaving] = useState(false);
   const [isDeleting, setIsDeleting] = useState(false);
 
@@ -26,6 +30,8 @@ export function TemplateManager() {
     setEditingTemplate(null);
     setName('');
     setContent('');
+    setGlossary([]);
+    setSpeakerLabels(false);
     setIsModalOpen(true);
   };
 
@@ -33,6 +39,8 @@ export function TemplateManager() {
     setEditingTemplate(template);
     setName(template.name);
     setContent(template.content);
+    setGlossary(template.glossary ?? []);
+    setSpeakerLabels(template.speakerLabels ?? false);
     setIsModalOpen(true);
   };
 
@@ -40,11 +48,13 @@ export function TemplateManager() {
     if (!name.trim() || !content.trim()) return;
 
     setIsSaving(true);
+    // Linhas incompletas do glossário são descartadas
+    const terms = glossary.filter((term) => term.source.trim() && term.target.trim());
     try {
       if (editingTemplate) {
-        await updateTemplate(editingTemplate.id, name, content);
+        await updateTemplate(editingTemplate.id, name, content, terms, speakerLabels);
       } else {
-        await addTemplate(name, content);
+        await addTemplate(name, content, terms, speakerLabels);
       }
       setIsModalOpen(false);
     } catch (error) {
@@ -140,7 +150,7 @@ export function TemplateManager() {
 
       {/* Edit/Create Modal */}
       <Dialog open={isModalOpen} onOpenChange={setIsModalOpen}>
-        <DialogContent className="sm:max-w-[500px]">
+        <DialogContent className="sm:max-w-[600px]">
           <DialogHeader>
             <DialogTitle>
               {editingTemplate ? t('settings.templates.editTemplate') : t('settings.templates.newTemplateTitle')}
@@ -164,6 +174,18 @@ export function TemplateManager() {
                 className="min-h-[200px] resize-none"
               />
             </div>
+            <div className="space-y-1">
+              <div className="flex items-center gap-2">
+                <Checkbox
+                  id="template-speaker-labels"
+                  checked={speakerLabels}
+                  onCheckedChange={(checked) => setSpeakerLabels(checked === true)}
+                />
+                <Label htmlFor="template-speaker-labels">{t('settings.templates.speakerLabels')}</Label>
+              </div>
+              <p className="text-xs text-muted-foreground">{t('settings.templates.speakerLabelsHint')}</p>
+            </div>
+            <GlossaryEditor terms={glossary} onChange={setGlossary} prompt={content} />
           </div>
           <DialogFooter>
             <Button variant="ghost" onClick={() => setIsModalOpen(false)}>
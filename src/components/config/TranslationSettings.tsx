@@ -28,7 +28,10 @@ export function TranslationSettings() {
     }
   };
 
-  const handleNumberInput = (field: 'batchSize' | 'parallelRequests' | 'concurrency' | 'maxRetries', value: string) => {
+  const handleNumberInput = (
+    field: 'batchSize' | 'parallelRequests' | 'concurrency' | 'maxRetries' | 'contextLines' | 'retryBaseDelayMs' | 'retryMaxDelayMs',
+    value: string,
+  ) => {
     const num = parseInt(value, 10);
     if (!isNaN(num) && num >= 0) {
       updateSetting(field, num);
@@ -102,6 +105,21 @@ export function TranslationSettings() {
           </p>
         </div>
 
+        <div>
+          <label className="block text-sm font-medium mb-1">{t('settings.translationSettings.contextLines')}</label>
+          <Input
+            type="number"
+            value={String(settings.contextLines ?? 3)}
+            onChange={(e) => handleNumberInput('contextLines', e.target.value)}
+            className="w-24"
+            min={0}
+            max={20}
+          />
+          <p className="text-xs text-muted-foreground mt-1">
+            {t('settings.translationSettings.contextLinesHint')}
+          </p>
+        </div>
+
         <div>
           <label className="block text-sm font-medium mb-1">{t('settings.translationSettings.concurrency')}</label>
           <Input
@@ -129,6 +147,33 @@ export function TranslationSettings() {
           />
         </div>
 
+        <div>
+          <label className="block text-sm font-medium mb-1">{t('settings.translationSettings.retryDelay')}</label>
+          <div className="flex items-center gap-2">
+            <Input
+              type="number"
+              value={String(settings.retryBaseDelayMs ?? 1000)}
+              onChange={(e) => handleNumberInput('retryBaseDelayMs', e.target.value)}
+              className="w-28"
+              min={0}
+              step={500}
+            />
+            <span className="text-xs text-muted-foreground">-</span>
+            <Input
+              type="number"
+              value={String(settings.retryMaxDelayMs ?? 60000)}
+              onChange={(e) => handleNumberInput('retryMaxDelayMs', e.target.value)}
+              className="w-28"
+              min={0}
+              step={1000}
+            />
+            <span className="text-xs text-muted-foreground">ms</span>
+          </div>
+          <p className="text-xs text-muted-foreground mt-1">
+            {t('settings.translationSettings.retryDelayHint')}
+          </p>
+        </div>
+
         {isAnthropicDirect ? (
           <>
             <div>
@@ -230,6 +275,42 @@ export function TranslationSettings() {
             />
             <Label htmlFor="continue-on-error">{t('settings.translationSettings.continueOnError')}</Label>
           </div>
+
+          <div className="flex items-center gap-2">
+            <Switch
+              id="skip-comments"
+              checked={settings.skipComments}
+              onCheckedChange={(checked) => updateSetting('skipComments', checked)}
+            />
+            <Label htmlFor="skip-comments">{t('settings.translationSettings.skipComments')}</Label>
+          </div>
+          <p className="text-xs text-muted-foreground ml-10">
+            {t('settings.translationSettings.skipCommentsHint')}
+          </p>
+
+          <div className="flex items-center gap-2">
+            <Switch
+              id="translation-memory"
+              checked={settings.translationMemory}
+              onCheckedChange={(checked) => updateSetting('translationMemory', checked)}
+            />
+            <Label htmlFor="translation-memory">{t('settings.translationSettings.translationMemory')}</Label>
+          </div>
+          <p className="text-xs text-muted-foreground ml-10">
+            {t('settings.translationSettings.translationMemoryHint')}
+          </p>
+
+          <div className="flex items-center gap-2">
+            <Switch
+              id="synopsis"
+              checked={settings.synopsis}
+              onCheckedChange={(checked) => updateSetting('synopsis', checked)}
+            />
+            <Label htmlFor="synopsis">{t('settings.translationSettings.synopsis')}</Label>
+          </div>
+          <p className="text-xs text-muted-foreground ml-10">
+            {t('settings.translationSettings.synopsisHint')}
+          </p>
         </div>
       </div>
     </Card>
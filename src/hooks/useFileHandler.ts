@@ -1,7 +1,7 @@
 import { useCallback } from 'react';
 import { useTranslationStore } from '../stores/translationStore';
 
-const SUBTITLE_EXTENSIONS = ['srt', 'ass', 'ssa'];
+export const SUBTITLE_EXTENSIONS = ['srt', 'ass', 'ssa', 'vtt', 'sub', 'sbv', 'lrc', 'ttml', 'dfxp', 'smi', 'sami'];
 const VIDEO_EXTENSIONS = ['mkv', 'mp4', 'avi', 'webm', 'mov'];
 
 function getFileType(path: string): 'subtitle' | 'video' | null {
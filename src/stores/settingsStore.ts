@@ -1,5 +1,5 @@
 import { create } from 'zustand';
-import { AppSettings, DEFAULT_SETTINGS, Template } from '../types';
+import { AppSettings, DEFAULT_SETTINGS, GlossaryTerm, Template } from '../types';
 import * as TauriUtils from '../utils/tauri';
 import i18n from '../i18n';
 
@@ -14,8 +14,14 @@ interface SettingsState {
   updateSetting: <K extends keyof AppSettings>(key: K, value: AppSettings[K]) => void;
 
   loadTemplates: () => Promise<void>;
-  addTemplate: (name: string, content: string) => Promise<void>;
-  updateTemplate: (id: string, name: string, content: string) => Promise<void>;
+  addTemplate: (name: string, content: string, glossary?: GlossaryTerm[], speakerLabels?: boolean) => Promise<void>;
+  updateTemplate: (
+    id: string,
+    name: string,
+    content: string,
+    glossary?: GlossaryTerm[],
+    speakerLabels?: boolean
+  ) => Promise<void>;
   deleteTemplate: (id: string) => Promise<void>;
 
   checkFfmpeg: () => Promise<boolean>;
@@ -68,9 +74,9 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
     }
   },
 
-  addTemplate: async (name, content) => {
+  addTemplate: async (name, content, glossary, speakerLabels) => {
     try {
-      await TauriUtils.addTemplate(name, content);
+      await TauriUtils.addTemplate(name, content, glossary, speakerLabels);
       await get().loadTemplates();
     } catch (error) {
       console.error('Failed to add template:', error);
@@ -78,9 +84,9 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
     }
   },
 
-  updateTemplate: async (id, name, content) => {
+  updateTemplate: async (id, name, content, glossary, speakerLabels) => {
     try {
-      await TauriUtils.updateTemplate(id, name, content);
+      await TauriUtils.updateTemplate(id, name, content, glossary, speakerLabels);
       await get().loadTemplates();
     } catch (error) {
       console.error('Failed to update template:', error);
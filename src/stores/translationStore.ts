@@ -487,6 +487,11 @@ export const useTranslationStore = create<TranslationState>((set, get) => ({
         preservePositioning: false,
       } : undefined;
 
+      // Glossário e rótulos de falante do template selecionado
+      const template = useSettingsStore
+        .getState()
+        .templates.find((t) => t.id === settings.selectedTemplateId);
+
       const result = await TauriUtils.translateSubtitleFull(
         subtitle,
         settings.prompt,
@@ -502,11 +507,19 @@ export const useTranslationStore = create<TranslationState>((set, get) => ({
           autoContinue: settings.autoContinue,
           continueOnError: settings.continueOnError,
           maxRetries: settings.maxRetries,
+          retryBaseDelayMs: settings.retryBaseDelayMs,
+          retryMaxDelayMs: settings.retryMaxDelayMs,
           streaming: settings.streaming,
+          skipComments: settings.skipComments,
+          useTranslationMemory: settings.translationMemory,
+          contextLines: settings.contextLines,
+          synopsis: settings.synopsis,
           reasoningEffort: settings.reasoningEffort,
           anthropicThinkingEnabled: settings.anthropicThinkingEnabled,
           anthropicThinkingBudgetTokens: settings.anthropicThinkingBudgetTokens,
           textCleanerConfig,
+          glossary: template?.glossary,
+          speakerLabels: template?.speakerLabels ?? false,
         },
       );
 
@@ -525,6 +538,29 @@ export const useTranslationStore = create<TranslationState>((set, get) => ({
         );
       }
 
+      if (result.glossaryViolations.length > 0) {
+        const lines = [...new Set(result.glossaryViolations.map((v) => v.index))];
+        logs.addLog(
+          "warning",
+          i18n.t("logMessages.glossaryViolations", {
+            count: result.glossaryViolations.length,
+            lines: lines.slice(0, 10).join(", "),
+          }),
+          file.name,
+        );
+      }
+
+      if (result.untranslated.length > 0) {
+        logs.addLog(
+          "warning",
+          i18n.t("logMessages.untranslatedEntries", {
+            count: result.untranslated.length,
+            lines: result.untranslated.slice(0, 10).map((e) => e.index).join(", "),
+          }),
+          file.name,
+        );
+      }
+
       setFileStatus(file.id, "saving");
 
       let outputSubtitlePath: string;
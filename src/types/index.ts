@@ -28,8 +28,14 @@ export interface AppSettings {
   autoContinue: boolean;
   continueOnError: boolean;
   maxRetries: number;
+  retryBaseDelayMs: number;  // Espera antes do primeiro retry (dobra a cada tentativa)
+  retryMaxDelayMs: number;  // Teto da espera entre tentativas
   concurrency: number;
   streaming: boolean;  // Streaming de traduções conforme chegam da API
+  skipComments: boolean;  // Não envia linhas Comment (ASS) para tradução
+  translationMemory: boolean;  // Reaproveita traduções de execuções anteriores
+  contextLines: number;  // Linhas vizinhas enviadas como contexto em cada lote
+  synopsis: boolean;  // Resume o arquivo antes de traduzir e envia o resumo em cada lote
   reasoningEffort: ReasoningEffort;
   anthropicThinkingEnabled: boolean;
   anthropicThinkingBudgetTokens: number;
@@ -73,8 +79,14 @@ export const DEFAULT_SETTINGS: AppSettings = {
   autoContinue: true,
   continueOnError: true,
   maxRetries: 3,
+  retryBaseDelayMs: 1000,
+  retryMaxDelayMs: 60000,
   concurrency: 1,
   streaming: false,
+  skipComments: false,
+  translationMemory: true,
+  contextLines: 3,
+  synopsis: false,
   reasoningEffort: 'default',
   anthropicThinkingEnabled: false,
   anthropicThinkingBudgetTokens: 1024,
@@ -99,10 +111,33 @@ export interface Template {
   id: string;
   name: string;
   content: string;
+  glossary?: GlossaryTerm[];
+  speakerLabels?: boolean;  // Envia falante e estilo (ASS) junto de cada linha
   createdAt?: number;
   updatedAt?: number;
 }
 
+// Termo com tradução fixa (nomes, honoríficos, termos inventados)
+export interface GlossaryTerm {
+  source: string;
+  target: string;
+  caseSensitive: boolean;
+  notes: string;
+}
+
+export interface GlossaryViolation {
+  index: number;
+  sourceTerm: string;
+  expected: string;
+}
+
+// Entrada que ficou com o texto original, com o motivo
+export interface UntranslatedEntry {
+  index: number;
+  text: string;
+  reason: string;
+}
+
 // ============================================
 // MODELS
 // ============================================
@@ -121,7 +156,7 @@ export interface LLMModel {
 // SUBTITLES
 // ============================================
 
-export type SubtitleFormat = 'srt' | 'ass' | 'ssa' | 'vtt' | 'unknown';
+export type SubtitleFormat = 'srt' | 'ass' | 'ssa' | 'vtt' | 'sub' | 'sbv' | 'lrc' | 'ttml' | 'smi' | 'unknown';
 
 export interface SubtitleEntry {
   index: number;
@@ -141,6 +176,53 @@ export interface SubtitleFile {
   entries: SubtitleEntry[];
   header?: string;       // Header do ASS/SSA
   styles?: string;       // Estilos do ASS/SSA
+  encoding?: string;     // Encoding detectado na leitura
+  bom?: boolean;
+  line_ending?: 'Lf' | 'CrLf';
+  diagnostics?: ParseDiagnostic[]; // Entradas descartadas no parse
+  preamble?: string[];   // Cabeçalho SubViewer / tags LRC
+  frame_rate?: number;   // Frame rate do MicroDVD
+  markup_headers?: { prologue: string; epilogue: string; interleaved?: [number, string][] }; // Documento TTML/SAMI
+}
+
+export type TimingOperation =
+  | { kind: 'shift'; offsetMs: number }
+  | { kind: 'scale'; factor: number; offsetMs?: number }
+  | { kind: 'frameRate'; from: number; to: number }
+  | { kind: 'twoPoint'; first: SyncPoint; second: SyncPoint };
+
+export interface SyncPoint {
+  original: string;      // Timestamp em qualquer sintaxe suportada
+  target: string;
+}
+
+export interface EntryRange {
+  first: number;
+  last: number;
+}
+
+export interface SpeechSegment {
+  startMs: number;
+  endMs: number;
+}
+
+export interface SyncEstimate {
+  offsetMs: number;
+  driftFactor: number;       // 1.0 = sem drift
+  driftMsPerHour: number;
+  confidence: number;        // Fração da legenda sobre fala (0 a 1)
+  speechSegments: SpeechSegment[];
+}
+
+export interface AutoSyncResult {
+  file: SubtitleFile;
+  estimate: SyncEstimate;
+}
+
+export interface ParseDiagnostic {
+  line: number;
+  reason: string;
+  raw: string;
 }
 
 // ============================================
@@ -159,6 +241,8 @@ export interface SubtitleTranslationResult {
   file: SubtitleFile;
   progress: TranslationProgress;
   errorMessage?: string;
+  glossaryViolations: GlossaryViolation[];
+  untranslated: UntranslatedEntry[];
 }
 
 // ============================================
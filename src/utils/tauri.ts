@@ -1,6 +1,6 @@
 import { invoke } from '@tauri-apps/api/core';
 import { AppSettings, SubtitleTranslationResult, DetectedLanguage, ApiFormat } from '../types';
-import { Template, LLMModel, SubtitleFile, SubtitleTrack, TextCleanerConfig, AssClutterAnalysis, CleanedTextPreview } from '../types';
+import { Template, GlossaryTerm, LLMModel, SubtitleFile, SubtitleTrack, TimingOperation, EntryRange, AutoSyncResult, TextCleanerConfig, AssClutterAnalysis, CleanedTextPreview } from '../types';
 
 export async function loadSettings(): Promise<AppSettings> {
   return invoke<AppSettings>('load_settings');
@@ -14,12 +14,23 @@ export async function loadTemplates(): Promise<Template[]> {
   return invoke<Template[]>('load_templates');
 }
 
-export async function addTemplate(name: string, content: string): Promise<Template> {
-  return invoke('add_template', { name, content });
+export async function addTemplate(
+  name: string,
+  content: string,
+  glossary?: GlossaryTerm[],
+  speakerLabels?: boolean
+): Promise<Template> {
+  return invoke('add_template', { name, content, glossary, speakerLabels });
 }
 
-export async function updateTemplate(id: string, name: string, content: string): Promise<Template> {
-  return invoke('update_template', { templateId: id, name, content });
+export async function updateTemplate(
+  id: string,
+  name: string,
+  content: string,
+  glossary?: GlossaryTerm[],
+  speakerLabels?: boolean
+): Promise<Template> {
+  return invoke('update_template', { templateId: id, name, content, glossary, speakerLabels });
 }
 
 export async function deleteTemplate(id: string): Promise<void> {
@@ -43,12 +54,34 @@ export async function listLLMModels(
   });
 }
 
-export async function loadSubtitle(path: string): Promise<SubtitleFile> {
-  return invoke<SubtitleFile>('load_subtitle', { path });
+export async function loadSubtitle(
+  path: string,
+  encoding?: string,
+  strict?: boolean,
+  frameRate?: number
+): Promise<SubtitleFile> {
+  return invoke<SubtitleFile>('load_subtitle', { path, encoding, strict, frameRate });
 }
 
-export async function saveSubtitle(path: string, file: SubtitleFile): Promise<void> {
-  return invoke('save_subtitle', { path, file });
+export async function saveSubtitle(path: string, file: SubtitleFile, encoding?: string): Promise<string> {
+  return invoke<string>('save_subtitle', { path, file, encoding });
+}
+
+export async function retimeSubtitle(
+  file: SubtitleFile,
+  operation: TimingOperation,
+  range?: EntryRange
+): Promise<SubtitleFile> {
+  return invoke<SubtitleFile>('retime_subtitle', { file, operation, range });
+}
+
+export async function autoSyncSubtitle(
+  videoPath: string,
+  file: SubtitleFile,
+  audioTrack?: number,
+  maxOffsetMs?: number
+): Promise<AutoSyncResult> {
+  return invoke<AutoSyncResult>('auto_sync_subtitle', { videoPath, file, audioTrack, maxOffsetMs });
 }
 
 export async function checkFfmpegInstalled(): Promise<string> {
@@ -79,11 +112,19 @@ export interface TranslationOptions {
   autoContinue: boolean;
   continueOnError: boolean;
   maxRetries: number;
+  retryBaseDelayMs: number;
+  retryMaxDelayMs: number;
   streaming: boolean;
+  skipComments: boolean;
+  useTranslationMemory: boolean;
+  contextLines: number;
+  synopsis: boolean;
   reasoningEffort: AppSettings['reasoningEffort'];
   anthropicThinkingEnabled: boolean;
   anthropicThinkingBudgetTokens: number;
   textCleanerConfig?: TextCleanerConfig;
+  glossary?: GlossaryTerm[];
+  speakerLabels?: boolean;
 }
 
 export async function translateSubtitleFull(
@@ -116,10 +157,18 @@ export async function translateSubtitleFull(
       autoContinue: options.autoContinue,
       continueOnError: options.continueOnError,
       maxRetries: options.maxRetries,
+      retryBaseDelayMs: options.retryBaseDelayMs,
+      retryMaxDelayMs: options.retryMaxDelayMs,
       streaming: options.streaming,
+      skipComments: options.skipComments,
+      useTranslationMemory: options.useTranslationMemory,
+      contextLines: options.contextLines,
+      synopsis: options.synopsis,
     },
     fileId,
     textCleanerConfig: options.textCleanerConfig,
+    glossary: options.glossary,
+    speakerLabels: options.speakerLabels,
   });
 }
 
@@ -131,6 +180,10 @@ export async function cancelAllTranslations(): Promise<void> {
   return invoke('cancel_all_translations');
 }
 
+export async function clearTranslationMemory(): Promise<number> {
+  return invoke<number>('clear_translation_memory');
+}
+
 export async function backupFile(path: string): Promise<string> {
   return invoke('backup_file', { path });
 }
@@ -167,6 +220,30 @@ export async function detectLanguage(
   });
 }
 
+export async function extractGlossary(
+  baseUrl: string,
+  apiKey: string,
+  apiFormat: ApiFormat,
+  model: string,
+  translationPrompt: string,
+  files: SubtitleFile[],
+  existing: GlossaryTerm[] = [],
+  headers: Record<string, string> = {}
+): Promise<GlossaryTerm[]> {
+  return invoke<GlossaryTerm[]>('extract_glossary', {
+    config: {
+      endpoint: baseUrl,
+      apiKey: apiKey || '',
+      apiFormat,
+      headers: Object.entries(headers).map(([k, v]) => [k, v]),
+      model,
+    },
+    translationPrompt,
+    files,
+    existing,
+  });
+}
+
 export async function getAppDataDir(): Promise<string> {
   return invoke<string>('get_app_data_dir');
 }